@@ -0,0 +1,8 @@
+-- last time each account looked at an artist on the dashboard, for the "new" badge
+CREATE TABLE artist_visits (
+                               account_id INTEGER NOT NULL,
+                               artist     TEXT NOT NULL,
+                               visited_at TEXT NOT NULL,
+                               PRIMARY KEY(account_id, artist),
+                               FOREIGN KEY(account_id) REFERENCES accounts(id) ON DELETE CASCADE
+) STRICT;
@@ -0,0 +1,14 @@
+-- last full favorites sync, manual or scheduled
+ALTER TABLE accounts ADD COLUMN synced_at TEXT;
+
+-- top new recommendations of each scheduled sync, read by the UI and feeds
+CREATE TABLE digests (
+                         id         INTEGER PRIMARY KEY AUTOINCREMENT,
+                         account_id INTEGER NOT NULL,
+                         created_at TEXT NOT NULL,
+                         since      TEXT,
+                         posts      TEXT NOT NULL, -- JSON array of scored posts
+                         FOREIGN KEY(account_id) REFERENCES accounts(id) ON DELETE CASCADE
+) STRICT;
+
+CREATE INDEX idx_digests_account ON digests(account_id, id);
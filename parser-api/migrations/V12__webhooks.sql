@@ -0,0 +1,24 @@
+-- outgoing webhooks, fired by the scheduler for new posts above the threshold
+CREATE TABLE webhooks (
+                          id         INTEGER PRIMARY KEY AUTOINCREMENT,
+                          account_id INTEGER NOT NULL,
+                          url        TEXT NOT NULL,
+                          min_score  REAL NOT NULL,
+                          ratings    TEXT NOT NULL, -- e.g. "s,q"; empty for every rating
+                          secret     TEXT,
+                          created_at TEXT NOT NULL,
+                          FOREIGN KEY(account_id) REFERENCES accounts(id) ON DELETE CASCADE
+) STRICT;
+
+CREATE TABLE webhook_deliveries (
+                                    id           INTEGER PRIMARY KEY AUTOINCREMENT,
+                                    webhook_id   INTEGER NOT NULL,
+                                    event        TEXT NOT NULL,
+                                    post_id      INTEGER,
+                                    status       INTEGER, -- NULL when no response arrived
+                                    error        TEXT,
+                                    delivered_at TEXT NOT NULL,
+                                    FOREIGN KEY(webhook_id) REFERENCES webhooks(id) ON DELETE CASCADE
+) STRICT;
+
+CREATE INDEX idx_webhook_deliveries ON webhook_deliveries(webhook_id, post_id);
@@ -0,0 +1,24 @@
+-- API users; only consulted when [auth] is enabled
+CREATE TABLE users (
+                       id            INTEGER PRIMARY KEY AUTOINCREMENT,
+                       username      TEXT NOT NULL UNIQUE,
+                       password_hash TEXT NOT NULL, -- pbkdf2-sha256$<rounds>$<salt hex>$<hash hex>
+                       role          TEXT NOT NULL CHECK (role IN ('admin','user')),
+                       created_at    TEXT NOT NULL
+) STRICT;
+
+-- web sessions and long-lived API tokens; only a SHA-256 of each token is kept
+CREATE TABLE api_tokens (
+                            id           INTEGER PRIMARY KEY AUTOINCREMENT,
+                            user_id      INTEGER NOT NULL,
+                            token_hash   TEXT NOT NULL UNIQUE,
+                            name         TEXT NOT NULL,
+                            kind         TEXT NOT NULL CHECK (kind IN ('session','api')),
+                            created_at   TEXT NOT NULL,
+                            expires_at   TEXT,
+                            last_used_at TEXT,
+                            FOREIGN KEY(user_id) REFERENCES users(id) ON DELETE CASCADE
+) STRICT;
+
+-- accounts without an owner are only visible to admins
+ALTER TABLE accounts ADD COLUMN owner_id INTEGER REFERENCES users(id) ON DELETE SET NULL;
@@ -0,0 +1,15 @@
+-- every HTTP attempt of a delivery gets its own row
+ALTER TABLE webhook_deliveries ADD COLUMN attempt INTEGER NOT NULL DEFAULT 1;
+
+-- matched posts not yet delivered, tried again on later scheduler runs until one succeeds
+CREATE TABLE webhook_queue (
+                               webhook_id INTEGER NOT NULL,
+                               post_id    INTEGER NOT NULL,
+                               post       TEXT NOT NULL, -- JSON scored post, as it matched
+                               runs       INTEGER NOT NULL DEFAULT 0,
+                               queued_at  TEXT NOT NULL,
+                               PRIMARY KEY (webhook_id, post_id),
+                               FOREIGN KEY(webhook_id) REFERENCES webhooks(id) ON DELETE CASCADE
+) STRICT;
+
+CREATE INDEX idx_webhook_deliveries_time ON webhook_deliveries(delivered_at);
@@ -0,0 +1,7 @@
+ALTER TABLE accounts_post ADD COLUMN fav_position INTEGER;
+ALTER TABLE accounts_post ADD COLUMN favorited_at TEXT;
+ALTER TABLE account_tag_counts ADD COLUMN weight REAL NOT NULL DEFAULT 0;
+
+UPDATE account_tag_counts SET weight = count;
+
+CREATE INDEX idx_ap_acc_position ON accounts_post(account_id, fav_position);
\ No newline at end of file
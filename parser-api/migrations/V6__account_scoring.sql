@@ -0,0 +1,2 @@
+ALTER TABLE accounts ADD COLUMN scoring_profile TEXT;
+ALTER TABLE accounts ADD COLUMN scoring_overrides TEXT;
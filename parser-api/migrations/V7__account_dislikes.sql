@@ -0,0 +1,6 @@
+CREATE TABLE account_dislikes (
+                                  account_id INTEGER NOT NULL,
+                                  tag_name   TEXT NOT NULL,
+                                  PRIMARY KEY(account_id, tag_name),
+                                  FOREIGN KEY(account_id) REFERENCES accounts(id) ON DELETE CASCADE
+) STRICT;
@@ -0,0 +1,40 @@
+-- widen the group check to every group e621 reports; which ones are kept is now set by `tag_groups`
+CREATE TABLE tags_new (
+                      id INTEGER PRIMARY KEY AUTOINCREMENT,
+                      name TEXT NOT NULL,
+                      group_type TEXT NOT NULL CHECK (group_type IN (
+                                                                     'artist','character','contributor','copyright',
+                                                                     'general','invalid','lore','meta','species'
+                          )),
+                      df INTEGER DEFAULT 0,
+                      UNIQUE(name, group_type)
+) STRICT;
+
+INSERT INTO tags_new (id, name, group_type, df) SELECT id, name, group_type, df FROM tags;
+
+CREATE TABLE tags_posts_new (
+                            tag_id INTEGER NOT NULL,
+                            post_id INTEGER NOT NULL,
+                            PRIMARY KEY(tag_id, post_id),
+                            FOREIGN KEY(tag_id) REFERENCES tags_new(id) ON DELETE CASCADE,
+                            FOREIGN KEY(post_id) REFERENCES posts(id) ON DELETE CASCADE
+) STRICT;
+
+INSERT INTO tags_posts_new (tag_id, post_id) SELECT tag_id, post_id FROM tags_posts;
+
+DROP TABLE tags_posts;
+DROP TABLE tags;
+ALTER TABLE tags_new RENAME TO tags;
+ALTER TABLE tags_posts_new RENAME TO tags_posts;
+
+CREATE INDEX idx_tags_name_group ON tags(name, group_type);
+CREATE INDEX idx_tp_tag          ON tags_posts(tag_id);
+CREATE INDEX idx_tp_post         ON tags_posts(post_id);
+
+-- groups the stored tags were saved with, compared against `tag_groups` on startup
+CREATE TABLE settings (
+                          key   TEXT PRIMARY KEY,
+                          value TEXT NOT NULL
+) STRICT;
+
+INSERT INTO settings (key, value) VALUES ('tag_groups', 'artist,character,copyright,general,lore,species');
@@ -0,0 +1,10 @@
+CREATE TABLE pools (
+                       id          INTEGER PRIMARY KEY,
+                       name        TEXT NOT NULL,
+                       description TEXT NOT NULL,
+                       category    TEXT NOT NULL,
+                       is_active   INTEGER NOT NULL,
+                       post_ids    TEXT NOT NULL,
+                       updated_at  TEXT NOT NULL,
+                       fetched_at  TEXT NOT NULL
+) STRICT;
@@ -5,8 +5,8 @@ use tokio::time::sleep;
 use urlencoding::encode;
 
 use crate::{
-    cfg,
-    models::{Post, PostsApiResponse, TruncatedAccount, UserApiResponse},
+    cfg, metrics,
+    models::{Pool, Post, PostsApiResponse, TruncatedAccount, UserApiResponse},
 };
 
 fn build_url(path: &str, params: &[(&str, String)]) -> String {
@@ -78,6 +78,7 @@ async fn send_with_retry(builder: reqwest::RequestBuilder) -> Result<Response, S
             Ok(resp) => {
                 let status = resp.status();
                 trace!("HTTP status received: {status}");
+                metrics::inc("upstream_requests_total", &[("status", status.as_str())]);
 
                 if (status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error())
                     && attempt < cfg.max_retries
@@ -95,6 +96,7 @@ async fn send_with_retry(builder: reqwest::RequestBuilder) -> Result<Response, S
                         attempt + 1,
                         cfg.max_retries + 1
                     );
+                    metrics::inc("upstream_retries_total", &[]);
                     sleep(delay).await;
                     delay = delay.saturating_mul(2);
                     continue;
@@ -108,6 +110,7 @@ async fn send_with_retry(builder: reqwest::RequestBuilder) -> Result<Response, S
                 Ok(resp)
             }
             Err(e) => {
+                metrics::inc("upstream_requests_total", &[("status", "error")]);
                 if attempt < cfg.max_retries {
                     warn!(
                         "Request error on attempt {}/{}: {:?}. Retrying in {:?}",
@@ -116,6 +119,7 @@ async fn send_with_retry(builder: reqwest::RequestBuilder) -> Result<Response, S
                         e,
                         delay
                     );
+                    metrics::inc("upstream_retries_total", &[]);
                     sleep(delay).await;
                     delay = delay.saturating_mul(2);
                     continue;
@@ -130,6 +134,20 @@ async fn send_with_retry(builder: reqwest::RequestBuilder) -> Result<Response, S
     Err("unreachable".into())
 }
 
+/// One request for a single post, without retries, to see if the posts domain answers.
+pub async fn probe() -> Result<StatusCode, String> {
+    let cfg = cfg();
+    let url = build_url("posts.json", &[("limit", "1".to_string())]);
+    let resp = get_client()
+        .get(url)
+        .basic_auth(cfg.admin_user.clone(), Some(cfg.admin_api.clone()))
+        .timeout(Duration::from_secs(5))
+        .send()
+        .await
+        .map_err(|e| format!("Upstream request failed: {e}"))?;
+    Ok(resp.status())
+}
+
 pub async fn get_favorites(account: &TruncatedAccount, page: i32) -> Vec<Post> {
     info!("Fetching favorites: user_id={} page={}", account.id, page);
 
@@ -217,13 +235,17 @@ pub async fn get_account(account: &TruncatedAccount) -> UserApiResponse {
     parsed
 }
 
-pub async fn get_posts(account: &TruncatedAccount, page: Option<i32>) -> Vec<Post> {
+fn blacklist_query(account: &TruncatedAccount) -> String {
     let blacklisted_tags = account.blacklist.clone();
-    let blacklist = if blacklisted_tags.trim().is_empty() {
+    if blacklisted_tags.trim().is_empty() {
         String::new()
     } else {
         format!("-{}", blacklisted_tags.replace('\n', " -"))
-    };
+    }
+}
+
+pub async fn get_posts(account: &TruncatedAccount, page: Option<i32>) -> Vec<Post> {
+    let blacklist = blacklist_query(account);
     debug!(
         "Preparing posts fetch: page={} blacklist_len={}",
         page.unwrap_or(0),
@@ -256,3 +278,113 @@ pub async fn get_posts(account: &TruncatedAccount, page: Option<i32>) -> Vec<Pos
     info!("Fetched {} posts", posts.len());
     posts
 }
+
+pub async fn get_posts_by_ids(account: &TruncatedAccount, ids: &[i64]) -> Vec<Post> {
+    if ids.is_empty() {
+        return Vec::new();
+    }
+
+    let id_list = ids.iter().map(i64::to_string).collect::<Vec<_>>().join(",");
+    debug!("Preparing posts fetch by id: count={}", ids.len());
+    search_posts(account, &format!("id:{id_list}"), ids.len()).await
+}
+
+/// Newest posts matching `tags` with the account blacklist applied; empty on any failure.
+pub async fn search_posts(account: &TruncatedAccount, tags: &str, limit: usize) -> Vec<Post> {
+    let query = format!("{tags} {}", blacklist_query(account));
+
+    let cfg = cfg();
+    let client = get_client();
+    let url = build_url(
+        "posts.json",
+        &[
+            ("limit", limit.to_string()),
+            ("tags", query.trim().to_string()),
+        ],
+    );
+    let resp = match send_with_retry(
+        client
+            .get(url)
+            .basic_auth(cfg.admin_user.clone(), Some(cfg.admin_api.clone())),
+    )
+    .await
+    {
+        Ok(r) => r,
+        Err(e) => {
+            warn!("posts search request failed: {e}");
+            return Vec::new();
+        }
+    };
+
+    let body = match resp.text().await {
+        Ok(b) => b,
+        Err(e) => {
+            warn!("reading posts search body failed: {e}");
+            return Vec::new();
+        }
+    };
+
+    match json::from_str::<PostsApiResponse>(&body) {
+        Ok(r) => {
+            info!("Fetched {} posts for search", r.posts.len());
+            r.posts
+        }
+        Err(e) => {
+            let preview = body.chars().take(200).collect::<String>();
+            warn!("posts search parse failed: {e}; first bytes: {preview}");
+            Vec::new()
+        }
+    }
+}
+
+pub async fn get_pools(ids: &[i64]) -> Vec<Pool> {
+    if ids.is_empty() {
+        return Vec::new();
+    }
+
+    let id_list = ids.iter().map(i64::to_string).collect::<Vec<_>>().join(",");
+    debug!("Preparing pools fetch: count={}", ids.len());
+
+    let cfg = cfg();
+    let client = get_client();
+    let url = build_url(
+        "pools.json",
+        &[
+            ("search[id]", id_list),
+            ("limit", ids.len().to_string()),
+        ],
+    );
+    let resp = match send_with_retry(
+        client
+            .get(url)
+            .basic_auth(cfg.admin_user.clone(), Some(cfg.admin_api.clone())),
+    )
+    .await
+    {
+        Ok(r) => r,
+        Err(e) => {
+            warn!("pools request failed: {e}");
+            return Vec::new();
+        }
+    };
+
+    let body = match resp.text().await {
+        Ok(b) => b,
+        Err(e) => {
+            warn!("reading pools body failed: {e}");
+            return Vec::new();
+        }
+    };
+
+    match json::from_str::<Vec<Pool>>(&body) {
+        Ok(pools) => {
+            info!("Fetched {} pools", pools.len());
+            pools
+        }
+        Err(e) => {
+            let preview = body.chars().take(200).collect::<String>();
+            warn!("pools parse failed: {e}; first bytes: {preview}");
+            Vec::new()
+        }
+    }
+}
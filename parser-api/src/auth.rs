@@ -0,0 +1,310 @@
+use chrono::{Duration, Utc};
+use rand::RngCore;
+use rocket::http::Status;
+use rocket::request::{FromRequest, Outcome, Request};
+use rocket_okapi::r#gen::OpenApiGenerator;
+use rocket_okapi::okapi::Map;
+use rocket_okapi::okapi::openapi3::{SecurityRequirement, SecurityScheme, SecuritySchemeData};
+use rocket_okapi::request::{OpenApiFromRequest, RequestHeaderInput};
+use sha2::{Digest, Sha256};
+
+use crate::db;
+use crate::models::{IssuedToken, Role, User, cfg};
+
+// Stored with each hash, so raising it only affects passwords set afterwards.
+const PBKDF2_ROUNDS: u32 = 100_000;
+const SALT_LEN: usize = 16;
+const TOKEN_LEN: usize = 32;
+const MIN_PASSWORD_LEN: usize = 8;
+
+pub const SESSION_TOKEN: &str = "session";
+pub const API_TOKEN: &str = "api";
+
+/// Query parameter accepted in place of the `Authorization` header on feed routes.
+const TOKEN_QUERY: &str = "access_token";
+
+fn random_hex(len: usize) -> String {
+    let mut bytes = vec![0u8; len];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    hex::encode(bytes)
+}
+
+fn pbkdf2_hex(password: &str, salt: &[u8], rounds: u32) -> String {
+    let mut out = [0u8; 32];
+    pbkdf2::pbkdf2_hmac::<Sha256>(password.as_bytes(), salt, rounds, &mut out);
+    hex::encode(out)
+}
+
+/// `pbkdf2-sha256$<rounds>$<salt hex>$<hash hex>`
+pub fn hash_password(password: &str) -> Result<String, String> {
+    if password.chars().count() < MIN_PASSWORD_LEN {
+        return Err(format!("Password must be at least {MIN_PASSWORD_LEN} characters"));
+    }
+    let salt = random_hex(SALT_LEN);
+    let hash = pbkdf2_hex(password, salt.as_bytes(), PBKDF2_ROUNDS);
+    Ok(format!("pbkdf2-sha256${PBKDF2_ROUNDS}${salt}${hash}"))
+}
+
+pub fn verify_password(password: &str, stored: &str) -> bool {
+    let mut parts = stored.split('$');
+    let (Some("pbkdf2-sha256"), Some(rounds), Some(salt), Some(hash), None) =
+        (parts.next(), parts.next(), parts.next(), parts.next(), parts.next())
+    else {
+        return false;
+    };
+    let Ok(rounds) = rounds.parse::<u32>() else {
+        return false;
+    };
+    let computed = pbkdf2_hex(password, salt.as_bytes(), rounds);
+
+    // Constant time, so the comparison doesn't leak how much of the hash matched.
+    computed.len() == hash.len()
+        && computed
+            .bytes()
+            .zip(hash.bytes())
+            .fold(0u8, |acc, (a, b)| acc | (a ^ b))
+            == 0
+}
+
+pub fn token_hash(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
+
+pub fn create_user(username: &str, password: &str, role: Role) -> Result<User, String> {
+    let username = username.trim();
+    if username.is_empty() {
+        return Err("Username must not be empty".to_string());
+    }
+    db::create_user(username, &hash_password(password)?, role)
+}
+
+/// `false` when there is no such user.
+pub fn set_password(username: &str, password: &str) -> Result<bool, String> {
+    db::set_user_password(username.trim(), &hash_password(password)?)
+}
+
+/// Issues a token for `user`; sessions expire after `auth.session_days`, API tokens never do.
+pub fn issue_token(user: User, name: &str, kind: &str) -> Result<IssuedToken, String> {
+    let token = random_hex(TOKEN_LEN);
+    let expires_at = (kind == SESSION_TOKEN)
+        .then(|| Utc::now() + Duration::seconds((cfg().auth.session_days * 86400.0) as i64));
+    let info = db::create_api_token(user.id, &token_hash(&token), name, kind, expires_at)?;
+    Ok(IssuedToken { token, user, info })
+}
+
+/// Exchanges a username and password for a session token.
+pub fn login(username: &str, password: &str) -> Result<Option<IssuedToken>, String> {
+    let Some((user, stored)) = db::get_user_login(username.trim())? else {
+        return Ok(None);
+    };
+    if !verify_password(password, &stored) {
+        return Ok(None);
+    }
+    issue_token(user, "web login", SESSION_TOKEN).map(Some)
+}
+
+/// The caller of a request. With `auth.enabled` off everyone is an admin with id 0.
+pub struct AuthUser {
+    pub user: User,
+    /// Token the request was made with; `None` when auth is disabled.
+    pub token_id: Option<i64>,
+}
+
+impl AuthUser {
+    fn anonymous_admin() -> Self {
+        Self {
+            user: User {
+                id: 0,
+                username: "admin".to_string(),
+                role: Role::Admin,
+                created_at: Utc::now(),
+            },
+            token_id: None,
+        }
+    }
+
+    pub fn is_admin(&self) -> bool {
+        self.user.role == Role::Admin
+    }
+
+    /// Admins see every account, others only those they own.
+    pub fn can_access(&self, account_id: i32) -> Result<bool, String> {
+        if self.is_admin() {
+            return Ok(true);
+        }
+        Ok(db::get_account_owner(account_id)?.flatten() == Some(self.user.id))
+    }
+
+    /// Owner to record for an account this user stores; none for the anonymous admin.
+    pub fn owner_id(&self) -> Option<i64> {
+        self.token_id.map(|_| self.user.id)
+    }
+}
+
+pub fn bearer_token<'r>(req: &'r Request<'_>) -> Option<&'r str> {
+    req.headers()
+        .get_one("Authorization")
+        .and_then(|h| h.strip_prefix("Bearer "))
+        .map(str::trim)
+        .filter(|t| !t.is_empty())
+}
+
+impl AuthUser {
+    fn from_token(token: Option<&str>) -> Outcome<Self, String> {
+        if !cfg().auth.enabled {
+            return Outcome::Success(AuthUser::anonymous_admin());
+        }
+        let Some(token) = token else {
+            return Outcome::Error((Status::Unauthorized, "Missing API token".to_string()));
+        };
+        match db::get_token_user(&token_hash(token)) {
+            Ok(Some((user, token_id))) => Outcome::Success(AuthUser { user, token_id: Some(token_id) }),
+            Ok(None) => Outcome::Error((Status::Unauthorized, "Invalid or expired API token".to_string())),
+            Err(e) => {
+                error!("Failed to check API token: {e}");
+                Outcome::Error((Status::InternalServerError, e))
+            }
+        }
+    }
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for AuthUser {
+    type Error = String;
+
+    async fn from_request(req: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        AuthUser::from_token(bearer_token(req))
+    }
+}
+
+/// The caller of a feed route. Feed readers can't send headers, so the token may also
+/// come as `?access_token=`; no other route accepts it there, which keeps tokens out of
+/// the URLs that end up in access logs, browser history and `Referer` headers.
+pub struct FeedUser(pub AuthUser);
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for FeedUser {
+    type Error = String;
+
+    async fn from_request(req: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        let query = req
+            .query_value::<&str>(TOKEN_QUERY)
+            .and_then(Result::ok)
+            .map(str::trim)
+            .filter(|t| !t.is_empty());
+        AuthUser::from_token(bearer_token(req).or(query)).map(FeedUser)
+    }
+}
+
+/// Guards routes to callers with the admin role.
+pub struct AdminUser;
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for AdminUser {
+    type Error = String;
+
+    async fn from_request(req: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        let auth = rocket::outcome::try_outcome!(AuthUser::from_request(req).await);
+        if !auth.is_admin() {
+            return Outcome::Error((Status::Forbidden, "Admin role required".to_string()));
+        }
+        Outcome::Success(AdminUser)
+    }
+}
+
+// Route parameters naming the accounts a request reads or changes.
+const ACCOUNT_PARAMS: [&str; 3] = ["<account_id>", "<account_a>", "<account_b>"];
+
+/// Guards routes to callers allowed to see every account the route is about: the
+/// `<account_id>` (or compared `<account_a>`/`<account_b>`) path segments and an
+/// `account_id` query value.
+pub struct AccountAccess;
+
+impl AccountAccess {
+    fn account_ids(req: &Request<'_>) -> Result<Vec<i32>, String> {
+        let mut ids = Vec::new();
+        if let Some(route) = req.route() {
+            for (i, segment) in route.uri.unmounted_origin.path().segments().enumerate() {
+                if ACCOUNT_PARAMS.contains(&segment) {
+                    let value = req.routed_segment(i).unwrap_or_default();
+                    ids.push(value.parse().map_err(|_| format!("Invalid account id '{value}'"))?);
+                }
+            }
+        }
+        if let Some(value) = req.query_value::<i32>("account_id") {
+            ids.push(value.map_err(|e| format!("Invalid account id: {e}"))?);
+        }
+        Ok(ids)
+    }
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for AccountAccess {
+    type Error = String;
+
+    async fn from_request(req: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        let auth = rocket::outcome::try_outcome!(AuthUser::from_request(req).await);
+        if auth.is_admin() {
+            return Outcome::Success(AccountAccess);
+        }
+        // A malformed id could leave the route without one to check, so it is refused here.
+        let ids = match Self::account_ids(req) {
+            Ok(ids) => ids,
+            Err(e) => return Outcome::Error((Status::BadRequest, e)),
+        };
+        for id in ids {
+            match auth.can_access(id) {
+                Ok(true) => {}
+                Ok(false) => {
+                    return Outcome::Error((Status::Forbidden, format!("No access to account {id}")));
+                }
+                Err(e) => return Outcome::Error((Status::InternalServerError, e)),
+            }
+        }
+        Outcome::Success(AccountAccess)
+    }
+}
+
+fn bearer_security() -> rocket_okapi::Result<RequestHeaderInput> {
+    let scheme = SecurityScheme {
+        description: Some("API token from `POST /auth/login` or `POST /auth/tokens`".to_string()),
+        data: SecuritySchemeData::Http {
+            scheme: "bearer".to_string(),
+            bearer_format: None,
+        },
+        extensions: Map::new(),
+    };
+    let mut requirement = SecurityRequirement::new();
+    requirement.insert("BearerToken".to_string(), Vec::new());
+    Ok(RequestHeaderInput::Security("BearerToken".to_string(), scheme, requirement))
+}
+
+impl<'r> OpenApiFromRequest<'r> for AuthUser {
+    fn from_request_input(
+        _gen: &mut OpenApiGenerator,
+        _name: String,
+        _required: bool,
+    ) -> rocket_okapi::Result<RequestHeaderInput> {
+        bearer_security()
+    }
+}
+
+impl<'r> OpenApiFromRequest<'r> for AdminUser {
+    fn from_request_input(
+        _gen: &mut OpenApiGenerator,
+        _name: String,
+        _required: bool,
+    ) -> rocket_okapi::Result<RequestHeaderInput> {
+        bearer_security()
+    }
+}
+
+impl<'r> OpenApiFromRequest<'r> for AccountAccess {
+    fn from_request_input(
+        _gen: &mut OpenApiGenerator,
+        _name: String,
+        _required: bool,
+    ) -> rocket_okapi::Result<RequestHeaderInput> {
+        bearer_security()
+    }
+}
@@ -0,0 +1,190 @@
+use crate::auth;
+use crate::db::{self, ensure_sqlite};
+use crate::evaluation::{EvaluationData, EvaluationOptions, EvaluationReport};
+use crate::models::{Role, cfg};
+use crate::utils::ScoringModel;
+use crate::tuning::{self, Metric, Strategy, TuningOptions};
+
+const USAGE: &str = "\
+usage:
+  e621-account-parser-api                      start the API server
+  e621-account-parser-api evaluate <account_id> [--holdout 0.2] [--k 20] [--negatives N] [--seed 42]
+                                               [--profile name] [--model cosine|bm25|naive_bayes]
+  e621-account-parser-api tune <account_id>... [--strategy random|grid] [--trials 100] [--metric ndcg]
+                                               [--per-account] [--out tuned.toml] [evaluate options]
+  e621-account-parser-api create-user <username> --password <password> [--admin]
+  e621-account-parser-api set-password <username> --password <password>";
+
+fn flag_value<T: std::str::FromStr>(args: &[String], name: &str) -> Result<Option<T>, String> {
+    match args.iter().position(|a| a == name) {
+        None => Ok(None),
+        Some(i) => args
+            .get(i + 1)
+            .ok_or_else(|| format!("missing value for {name}"))?
+            .parse::<T>()
+            .map(Some)
+            .map_err(|_| format!("invalid value for {name}")),
+    }
+}
+
+fn parse_options(args: &[String]) -> Result<EvaluationOptions, String> {
+    let defaults = EvaluationOptions::default();
+    Ok(EvaluationOptions {
+        holdout: flag_value(args, "--holdout")?.unwrap_or(defaults.holdout),
+        k: flag_value(args, "--k")?.unwrap_or(defaults.k),
+        negatives: flag_value(args, "--negatives")?,
+        seed: flag_value(args, "--seed")?.unwrap_or(defaults.seed),
+    })
+}
+
+pub fn print_report(report: &EvaluationReport) {
+    println!(
+        "account {}: {} of {} favorites held out, {} negatives",
+        report.account_id, report.held_out, report.favorites, report.negatives
+    );
+    println!("precision@{:<4} {:.4}", report.k, report.metrics.precision);
+    println!("recall@{:<7} {:.4}", report.k, report.metrics.recall);
+    println!("ndcg@{:<9} {:.4}", report.k, report.metrics.ndcg);
+    println!("mrr            {:.4}", report.metrics.mrr);
+}
+
+fn evaluate(args: &[String]) -> Result<(), String> {
+    let account_id: i32 = args
+        .first()
+        .ok_or("missing <account_id>")?
+        .parse()
+        .map_err(|_| "invalid <account_id>".to_string())?;
+    let opts = parse_options(&args[1..])?;
+
+    ensure_sqlite()?;
+    let scoring = db::get_account_scoring(account_id)?;
+    let profile: Option<String> = flag_value(&args[1..], "--profile")?;
+    let mut cfg = cfg().account_scoring_for(profile.as_deref(), &scoring)?;
+    if let Some(model) = flag_value::<ScoringModel>(&args[1..], "--model")? {
+        cfg.scoring_model = model;
+    }
+
+    let data = EvaluationData::prepare(account_id, &opts)?;
+    print_report(&data.evaluate(&cfg, opts.k));
+    Ok(())
+}
+
+fn parse_account_ids(args: &[String]) -> Result<Vec<i32>, String> {
+    let ids = args
+        .iter()
+        .take_while(|a| !a.starts_with("--"))
+        .map(|a| a.parse().map_err(|_| format!("invalid <account_id> '{a}'")))
+        .collect::<Result<Vec<i32>, _>>()?;
+    if ids.is_empty() {
+        return Err("missing <account_id>".into());
+    }
+    Ok(ids)
+}
+
+fn write_snippet(snippet: &str, out: Option<&str>, account_id: Option<i32>) -> Result<(), String> {
+    let Some(out) = out else {
+        println!("{snippet}");
+        return Ok(());
+    };
+    let path = match account_id {
+        Some(id) => {
+            let p = std::path::Path::new(out);
+            let stem = p.file_stem().and_then(|s| s.to_str()).unwrap_or("tuned");
+            let ext = p.extension().and_then(|s| s.to_str()).unwrap_or("toml");
+            p.with_file_name(format!("{stem}-{id}.{ext}"))
+        }
+        None => std::path::PathBuf::from(out),
+    };
+    std::fs::write(&path, snippet).map_err(|e| format!("Failed to write {}: {e}", path.display()))?;
+    info!("Wrote {}", path.display());
+    Ok(())
+}
+
+fn tune(args: &[String]) -> Result<(), String> {
+    let account_ids = parse_account_ids(args)?;
+    let rest = &args[account_ids.len()..];
+    let eval = parse_options(rest)?;
+    let opts = TuningOptions {
+        strategy: Strategy::parse(&flag_value::<String>(rest, "--strategy")?.unwrap_or("random".into()))?,
+        trials: flag_value(rest, "--trials")?.unwrap_or(100),
+        metric: Metric::parse(&flag_value::<String>(rest, "--metric")?.unwrap_or("ndcg".into()))?,
+        k: eval.k,
+        seed: eval.seed,
+    };
+    let out: Option<String> = flag_value(rest, "--out")?;
+
+    ensure_sqlite()?;
+    let (validation, test): (Vec<_>, Vec<_>) = account_ids
+        .iter()
+        .map(|&id| EvaluationData::prepare_tuning(id, &eval))
+        .collect::<Result<Vec<_>, _>>()?
+        .into_iter()
+        .unzip();
+    let base = cfg();
+
+    if rest.iter().any(|a| a == "--per-account") {
+        for (validation, test) in validation.iter().zip(&test) {
+            let id = validation.account_id;
+            let result = tuning::tune(
+                std::slice::from_ref(validation),
+                std::slice::from_ref(test),
+                &base,
+                &opts,
+            );
+            let snippet = tuning::to_toml_snippet(&result, &[id], &opts)?;
+            write_snippet(&snippet, out.as_deref(), Some(id))?;
+        }
+    } else {
+        let result = tuning::tune(&validation, &test, &base, &opts);
+        let snippet = tuning::to_toml_snippet(&result, &account_ids, &opts)?;
+        write_snippet(&snippet, out.as_deref(), None)?;
+    }
+    Ok(())
+}
+
+fn create_user(args: &[String]) -> Result<(), String> {
+    let username = args.first().ok_or("missing <username>")?;
+    let password: String = flag_value(&args[1..], "--password")?.ok_or("missing --password")?;
+    let role = if args.iter().any(|a| a == "--admin") { Role::Admin } else { Role::User };
+
+    ensure_sqlite()?;
+    let user = auth::create_user(username, &password, role)?;
+    println!("created user '{}' ({}) with id {}", user.username, user.role, user.id);
+    Ok(())
+}
+
+fn set_password(args: &[String]) -> Result<(), String> {
+    let username = args.first().ok_or("missing <username>")?;
+    let password: String = flag_value(&args[1..], "--password")?.ok_or("missing --password")?;
+
+    ensure_sqlite()?;
+    if !auth::set_password(username, &password)? {
+        return Err(format!("no user named '{username}'"));
+    }
+    println!("password of '{username}' changed");
+    Ok(())
+}
+
+// Returns None when the arguments ask for the server, otherwise the process exit code.
+pub fn run(args: &[String]) -> Option<i32> {
+    let result = match args.first().map(String::as_str) {
+        None => return None,
+        Some("evaluate") => evaluate(&args[1..]),
+        Some("tune") => tune(&args[1..]),
+        Some("create-user") => create_user(&args[1..]),
+        Some("set-password") => set_password(&args[1..]),
+        Some("help") | Some("--help") | Some("-h") => {
+            println!("{USAGE}");
+            Ok(())
+        }
+        Some(other) => Err(format!("unknown command '{other}'")),
+    };
+
+    match result {
+        Ok(()) => Some(0),
+        Err(e) => {
+            eprintln!("{e}\n\n{USAGE}");
+            Some(1)
+        }
+    }
+}
@@ -1,13 +1,18 @@
-use crate::models::{Post, TagCount, TruncatedAccount};
-use chrono::Utc;
+use crate::models::{
+    AccountScoring, ApiToken, ArtistSummary, Digest, NewWebhook, Pool, Post, Role, ScoredPost, TAG_GROUPS, TagCount, Tags,
+    TimelinePeriod, TruncatedAccount, User, Webhook, WebhookDelivery, cfg,
+};
+use crate::metrics;
+use crate::utils::{CoCount, NON_ARTIST_TAGS, aggregate_tag_counts};
+use chrono::{DateTime, Utc};
 use rocket::{
     Build, Rocket,
     fairing::{Fairing, Info, Kind},
 };
-use rusqlite::{Connection, Result, params};
+use rusqlite::trace::{TraceEvent, TraceEventCodes};
+use rusqlite::{Connection, OptionalExtension, Result, params};
 use std::{collections::HashSet, fs};
 use std::collections::HashMap;
-use rusqlite::fallible_streaming_iterator::FallibleStreamingIterator;
 
 mod embedded {
     use refinery::embed_migrations;
@@ -26,13 +31,13 @@ impl Fairing for DbInit {
     }
 
     async fn on_ignite(&self, rocket: Rocket<Build>) -> rocket::fairing::Result {
-        match ensure_sqlite() {
+        match ensure_sqlite().and_then(|_| sync_tag_groups(&cfg().tag_groups)) {
             Ok(_) => {
-                println!("SQLite DB Initialized");
+                info!("SQLite DB Initialized");
                 Ok(rocket)
             }
             Err(e) => {
-                println!("Database initialization failed: {e}");
+                error!("Database initialization failed: {e}");
                 Err(rocket)
             }
         }
@@ -40,10 +45,10 @@ impl Fairing for DbInit {
 }
 
 fn open_db() -> Result<Connection, String> {
-    if fs::exists("database.db").is_err() {
-        if let Err(e) = fs::File::create("database.db") {
-            eprintln!("{e}")
-        }
+    if fs::exists("database.db").is_err()
+        && let Err(e) = fs::File::create("database.db")
+    {
+        error!("Failed to create database.db: {e}")
     }
 
     let connection =
@@ -59,10 +64,20 @@ fn open_db() -> Result<Connection, String> {
             ",
         )
         .map_err(|e| format!("Failed to assert pragma: {e}"))?;
+    connection.trace_v2(TraceEventCodes::SQLITE_TRACE_PROFILE, Some(profile_statement));
 
     Ok(connection)
 }
 
+fn profile_statement(event: TraceEvent<'_>) {
+    if let TraceEvent::Profile(stmt, duration) = event {
+        // By leading keyword only; full statements would make a series per query.
+        let sql = stmt.sql();
+        let kind = sql.split_whitespace().next().unwrap_or("other").to_lowercase();
+        metrics::observe("db_statement_duration_seconds", &[("statement", &kind)], duration);
+    }
+}
+
 pub fn ensure_sqlite() -> Result<(), String> {
     if fs::exists("database.db").is_err() {
         fs::File::create("database.db").map_err(|e| format!("Failed to create file: {e}"))?;
@@ -77,7 +92,101 @@ pub fn ensure_sqlite() -> Result<(), String> {
     Ok(())
 }
 
-pub fn set_account(account_id: i32, name: &str, mut blacklisted_tags: &str) -> Result<(), String> {
+/// Latest applied and latest embedded migration versions.
+pub fn migration_versions() -> Result<(Option<i64>, i64), String> {
+    let mut conn = open_db()?;
+    let runner = embedded::migrations::runner();
+    let applied = runner
+        .get_last_applied_migration(&mut conn)
+        .map_err(|e| format!("Failed to read applied migrations: {e}"))?
+        .map(|m| i64::from(m.version()));
+    let latest = runner
+        .get_migrations()
+        .iter()
+        .map(|m| i64::from(m.version()))
+        .max()
+        .unwrap_or_default();
+    Ok((applied, latest))
+}
+
+/// Takes the write lock and lets go of it without changing anything.
+pub fn check_writable() -> Result<(), String> {
+    open_db()?
+        .execute_batch("BEGIN IMMEDIATE; ROLLBACK;")
+        .map_err(|e| format!("Database is not writable: {e}"))
+}
+
+/// Brings stored tags in line with the configured `tag_groups`: tags of groups that were
+/// dropped from the list are deleted, groups that were added only fill in as accounts are
+/// processed again.
+pub fn sync_tag_groups(groups: &[String]) -> Result<(), String> {
+    let mut connection = open_db()?;
+
+    let configured: Vec<&str> = groups
+        .iter()
+        .map(String::as_str)
+        .filter(|g| {
+            let known = TAG_GROUPS.contains(g);
+            if !known {
+                warn!("Ignoring unknown tag group '{g}' in tag_groups");
+            }
+            known
+        })
+        .collect();
+
+    let stored: String = connection
+        .query_row("SELECT value FROM settings WHERE key = 'tag_groups'", [], |row| row.get(0))
+        .map_err(|e| format!("Failed to get stored tag groups: {e}"))?;
+    let stored: Vec<&str> = stored.split(',').filter(|g| !g.is_empty()).collect();
+
+    let removed: Vec<&str> = stored.iter().copied().filter(|g| !configured.contains(g)).collect();
+    let added: Vec<&str> = configured.iter().copied().filter(|g| !stored.contains(g)).collect();
+    if removed.is_empty() && added.is_empty() {
+        return Ok(());
+    }
+
+    let tx = connection
+        .transaction()
+        .map_err(|e| format!("Failed to get transaction: {e}"))?;
+
+    for group in &removed {
+        tx.execute("DELETE FROM tags WHERE group_type = ?1", [group])
+            .map_err(|e| format!("Failed to delete {group} tags: {e}"))?;
+        tx.execute("DELETE FROM account_tag_counts WHERE group_type = ?1", [group])
+            .map_err(|e| format!("Failed to delete {group} tag counts: {e}"))?;
+    }
+
+    let mut sorted = configured.clone();
+    sorted.sort_unstable();
+    tx.execute(
+        "INSERT INTO settings (key, value) VALUES ('tag_groups', ?1)
+        ON CONFLICT(key) DO UPDATE SET value = excluded.value",
+        [sorted.join(",")],
+    )
+    .map_err(|e| format!("Failed to save tag groups: {e}"))?;
+
+    tx.commit()
+        .map_err(|e| format!("Failed to commit transaction: {e}"))?;
+
+    if !removed.is_empty() {
+        info!("Removed stored tags of group(s): {}", removed.join(", "));
+    }
+    if !added.is_empty() {
+        info!(
+            "Tag group(s) {} added; process accounts again to store them",
+            added.join(", ")
+        );
+    }
+    Ok(())
+}
+
+/// Stores the account; `owner_id` only applies to accounts that have no owner yet.
+pub fn set_account(
+    account_id: i32,
+    name: &str,
+    mut blacklisted_tags: &str,
+    owner_id: Option<i64>,
+) -> Result<(), String> {
     if blacklisted_tags.is_empty() {
         blacklisted_tags = "
 gore
@@ -88,17 +197,16 @@ loli
 shota";
     }
 
-    eprint!("{blacklisted_tags:?}");
-
     open_db()?
         .execute(
             "
-            INSERT INTO accounts (id, name, blacklisted_tags) 
-            VALUES (?1, ?2, ?3)
+            INSERT INTO accounts (id, name, blacklisted_tags, owner_id) 
+            VALUES (?1, ?2, ?3, ?4)
             ON CONFLICT(id) DO UPDATE SET
             name = excluded.name,
-            blacklisted_tags = excluded.blacklisted_tags",
-            params![account_id, name, blacklisted_tags],
+            blacklisted_tags = excluded.blacklisted_tags,
+            owner_id = COALESCE(accounts.owner_id, excluded.owner_id)",
+            params![account_id, name, blacklisted_tags, owner_id],
         )
         .map_err(|e| format!("Failed to execute transaction: {e}"))?;
 
@@ -169,6 +277,93 @@ pub fn get_account_by_id(id: i32) -> Result<TruncatedAccount, String> {
     }
 }
 
+pub fn get_account_scoring(account_id: i32) -> Result<AccountScoring, String> {
+    let (profile, overrides) = open_db()?
+        .query_row(
+            "SELECT scoring_profile, scoring_overrides FROM accounts WHERE id = ?",
+            [account_id],
+            |row| Ok((row.get::<_, Option<String>>(0)?, row.get::<_, Option<String>>(1)?)),
+        )
+        .map_err(|e| match e {
+            rusqlite::Error::QueryReturnedNoRows => "No account found".to_string(),
+            e => format!("Failed to get account scoring: {e}"),
+        })?;
+
+    let overrides = match overrides {
+        Some(json) => rocket::serde::json::from_str(&json)
+            .map_err(|e| format!("Failed to parse scoring overrides: {e}"))?,
+        None => Default::default(),
+    };
+
+    Ok(AccountScoring { profile, overrides })
+}
+
+pub fn set_account_scoring(account_id: i32, scoring: &AccountScoring) -> Result<(), String> {
+    let overrides = rocket::serde::json::to_string(&scoring.overrides)
+        .map_err(|e| format!("Failed to serialize scoring overrides: {e}"))?;
+
+    let updated = open_db()?
+        .execute(
+            "UPDATE accounts SET scoring_profile = ?2, scoring_overrides = ?3 WHERE id = ?1",
+            params![account_id, scoring.profile, overrides],
+        )
+        .map_err(|e| format!("Failed to execute transaction: {e}"))?;
+
+    if updated == 0 {
+        return Err("No account found".to_string());
+    }
+    Ok(())
+}
+
+pub fn get_account_dislikes(account_id: i32) -> Result<Vec<String>, String> {
+    let conn = open_db()?;
+
+    let mut stmt = conn
+        .prepare("SELECT tag_name FROM account_dislikes WHERE account_id = ? ORDER BY tag_name")
+        .map_err(|e| format!("Failed to construct query: {e}"))?;
+
+    let tags = stmt
+        .query_map([account_id], |row| row.get(0))
+        .map_err(|e| format!("Failed to get dislikes: {e}"))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Failed to enumerate dislikes: {e}"))?;
+
+    Ok(tags)
+}
+
+pub fn set_account_dislikes(account_id: i32, tags: &[String]) -> Result<(), String> {
+    let mut connection = open_db()?;
+    let tx = connection
+        .transaction()
+        .map_err(|e| format!("Failed to get transaction: {e}"))?;
+
+    {
+        tx.execute("DELETE FROM account_dislikes WHERE account_id = ?1", params![account_id])
+            .map_err(|e| format!("Failed to delete dislikes: {e}"))?;
+
+        let mut insert = tx
+            .prepare_cached(
+                "INSERT OR IGNORE INTO account_dislikes (account_id, tag_name) VALUES (?1, ?2)",
+            )
+            .map_err(|e| format!("Failed to prepare transaction: {e}"))?;
+
+        for tag in tags {
+            let tag = tag.trim().to_lowercase();
+            if tag.is_empty() {
+                continue;
+            }
+            insert
+                .execute(params![account_id, tag])
+                .map_err(|e| format!("Failed to execute transaction: {e}"))?;
+        }
+    }
+
+    tx.commit()
+        .map_err(|e| format!("Failed to commit transaction: {e}"))?;
+
+    Ok(())
+}
+
 pub fn drop_account_posts(account_id: i32) -> Result<(), String> {
     let mut connection = open_db()?;
 
@@ -191,7 +386,7 @@ pub fn drop_account_posts(account_id: i32) -> Result<(), String> {
     Ok(())
 }
 
-pub fn save_posts(posts: &[Post], account_id: i32) -> Result<(), String> {
+pub fn save_posts(posts: &[Post], account_id: i32, first_position: i64) -> Result<(), String> {
     let mut connection = open_db()?;
 
     let tx = connection
@@ -213,11 +408,15 @@ pub fn save_posts(posts: &[Post], account_id: i32) -> Result<(), String> {
             .map_err(|e| format!("Failed to prepare transaction: {e}"))?;
         let mut insert_account = tx
             .prepare_cached(
-                "INSERT OR IGNORE INTO accounts_post (account_id, post_id) VALUES (?1, ?2);",
+                "
+            INSERT INTO accounts_post (account_id, post_id, fav_position)
+            VALUES (?1, ?2, ?3)
+            ON CONFLICT(post_id, account_id) DO UPDATE SET
+            fav_position = excluded.fav_position;",
             )
             .map_err(|e| format!("Failed to prepare transaction: {e}"))?;
 
-        for post in posts {
+        for (offset, post) in posts.iter().enumerate() {
             insert_post
                 .execute(params![
                     post.id,
@@ -230,7 +429,7 @@ pub fn save_posts(posts: &[Post], account_id: i32) -> Result<(), String> {
                 .map_err(|e| format!("Failed to execute transaction: {e}"))?;
 
             insert_account
-                .execute(params![account_id, post.id])
+                .execute(params![account_id, post.id, first_position + offset as i64])
                 .map_err(|e| format!("Failed to execute transaction: {e}"))?;
         }
     }
@@ -241,37 +440,63 @@ pub fn save_posts(posts: &[Post], account_id: i32) -> Result<(), String> {
     Ok(())
 }
 
-pub fn set_tag_counts(account_id: i32) -> Result<(), String> {
-    let mut counts: Vec<TagCount> = Vec::new();
+pub fn set_favorite_times(account_id: i32) -> Result<(), String> {
+    open_db()?
+        .execute(
+            "
+            WITH estimated AS (
+                SELECT ap.post_id,
+                       MAX(p.created_at) OVER (
+                           ORDER BY ap.fav_position DESC
+                           ROWS BETWEEN UNBOUNDED PRECEDING AND CURRENT ROW
+                       ) AS favorited_at
+                FROM accounts_post ap
+                INNER JOIN posts p ON p.id = ap.post_id
+                WHERE ap.account_id = ?1 AND ap.fav_position IS NOT NULL
+            )
+            UPDATE accounts_post
+            SET favorited_at = (SELECT e.favorited_at FROM estimated e WHERE e.post_id = accounts_post.post_id)
+            WHERE account_id = ?1",
+            params![account_id],
+        )
+        .map_err(|e| format!("Failed to estimate favorite times: {e}"))?;
+
+    Ok(())
+}
+
+pub fn set_tag_counts(account_id: i32, decay_tau_days: Option<f32>) -> Result<(), String> {
     let mut connection = open_db()?;
 
-    {
+    let rows: Vec<(String, String, Option<String>)> = {
         let mut stmt = connection
             .prepare(
                 r#"
-        SELECT t.name, t.group_type, COUNT(*) as count
+        SELECT t.name, t.group_type, ap.favorited_at
         FROM tags t
         INNER JOIN tags_posts tp ON t.id = tp.tag_id
         INNER JOIN accounts_post ap ON tp.post_id = ap.post_id
         WHERE ap.account_id = ?
-        GROUP BY t.name, t.group_type
-        ORDER BY count DESC
         "#,
             )
             .map_err(|e| format!("Failed to construct query: {e}"))?;
 
-        counts = stmt
-            .query_map([account_id], |row| {
-                Ok(TagCount {
-                    name: row.get(0)?,
-                    group_type: row.get(1)?,
-                    count: row.get(2)?,
-                })
-            })
-            .map_err(|e| format!("Failed to get accounts: {e}"))?
+        stmt.query_map([account_id], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+            .map_err(|e| format!("Failed to get tags: {e}"))?
             .collect::<Result<Vec<_>, _>>()
-            .map_err(|e| format!("Failed to enumerate accounts: {e}"))?;
-    }
+            .map_err(|e| format!("Failed to enumerate tags: {e}"))?
+    };
+
+    let counts = aggregate_tag_counts(
+        rows.iter().map(|(name, group_type, favorited_at)| {
+            (
+                name.as_str(),
+                group_type.as_str(),
+                favorited_at.as_deref().and_then(parse_db_time),
+            )
+        }),
+        decay_tau_days,
+        Utc::now(),
+    );
 
     let tx = connection
         .transaction()
@@ -287,10 +512,11 @@ pub fn set_tag_counts(account_id: i32) -> Result<(), String> {
         let mut insert_calc = tx
             .prepare_cached(
                 "
-        INSERT INTO account_tag_counts (account_id, tag_name, group_type, count) 
-        VALUES (?1, ?2, ?3, ?4)
+        INSERT INTO account_tag_counts (account_id, tag_name, group_type, count, weight) 
+        VALUES (?1, ?2, ?3, ?4, ?5)
         ON CONFLICT(account_id, tag_name, group_type) DO UPDATE SET
-        count = excluded.count;
+        count = excluded.count,
+        weight = excluded.weight;
         ",
             )
             .map_err(|e| format!("Failed to prepare transaction: {e}"))?;
@@ -301,7 +527,8 @@ pub fn set_tag_counts(account_id: i32) -> Result<(), String> {
                     account_id,
                     entry.name,
                     entry.group_type,
-                    entry.count
+                    entry.count,
+                    entry.weight
                 ])
                 .map_err(|e| format!("Failed to execute transaction: {e}"))?;
         }
@@ -317,15 +544,18 @@ pub fn get_tag_counts(account_id: i32) -> Result<Vec<TagCount>, String> {
     let conn = open_db()?;
 
     let mut stmt = conn
-        .prepare("SELECT * FROM account_tag_counts WHERE account_id = ?")
+        .prepare(
+            "SELECT tag_name, group_type, count, weight FROM account_tag_counts WHERE account_id = ? ORDER BY count DESC",
+        )
         .map_err(|e| format!("Failed to construct query: {e}"))?;
 
     let counts = stmt
         .query_map([account_id], |row| {
             Ok(TagCount {
-                name: row.get(1)?,
-                group_type: row.get(2)?,
-                count: row.get(3)?,
+                name: row.get(0)?,
+                group_type: row.get(1)?,
+                count: row.get(2)?,
+                weight: row.get(3)?,
             })
         })
         .map_err(|e| format!("Failed to get accounts: {e}"))?
@@ -335,7 +565,11 @@ pub fn get_tag_counts(account_id: i32) -> Result<Vec<TagCount>, String> {
     Ok(counts)
 }
 
-pub fn save_posts_tags_batch(posts: &[Post], blacklist: &HashSet<String>) -> Result<(), String> {
+pub fn save_posts_tags_batch(
+    posts: &[Post],
+    blacklist: &HashSet<String>,
+    groups: &[String],
+) -> Result<(), String> {
     if posts.is_empty() {
         return Ok(());
     }
@@ -358,34 +592,25 @@ pub fn save_posts_tags_batch(posts: &[Post], blacklist: &HashSet<String>) -> Res
             .map_err(|e| format!("prep df: {e}"))?;
 
         for post in posts {
-            for (group, tags) in [
-                ("artist", &post.tags.artist),
-                ("character", &post.tags.character),
-                ("copyright", &post.tags.copyright),
-                ("general", &post.tags.general),
-                ("lore", &post.tags.lore),
-                ("species", &post.tags.species),
-            ] {
-                let pid = post.id;
-                for tag in tags {
-                    if tag.is_empty() || blacklist.contains(tag) {
-                        continue;
-                    }
-
-                    insert_tag
-                        .execute(params![&tag, group])
-                        .map_err(|e| format!("ins tag: {e}"))?;
-
-                    let tag_id: i64 = select_id
-                        .query_row(params![&tag, group], |r| r.get(0))
-                        .map_err(|e| format!("get id {tag}:{group}: {e}"))?;
-
-                    link.execute(params![tag_id, pid])
-                        .map_err(|e| format!("link tag_id={tag_id} post_id={pid}: {e}"))?;
-
-                    df.execute(params![tag_id])
-                        .map_err(|e| format!("insert tag df: {e}"))?;
+            let pid = post.id;
+            for (group, tag) in post.tags.in_groups(groups) {
+                if blacklist.contains(tag) {
+                    continue;
                 }
+
+                insert_tag
+                    .execute(params![&tag, group])
+                    .map_err(|e| format!("ins tag: {e}"))?;
+
+                let tag_id: i64 = select_id
+                    .query_row(params![&tag, group], |r| r.get(0))
+                    .map_err(|e| format!("get id {tag}:{group}: {e}"))?;
+
+                link.execute(params![tag_id, pid])
+                    .map_err(|e| format!("link tag_id={tag_id} post_id={pid}: {e}"))?;
+
+                df.execute(params![tag_id])
+                    .map_err(|e| format!("insert tag df: {e}"))?;
             }
         }
     }
@@ -417,3 +642,1017 @@ pub fn get_tags_df() -> Result<HashMap<String, i64>> {
 
     Ok(map)
 }
+
+pub fn parse_db_time(s: &str) -> Option<DateTime<Utc>> {
+    s.parse::<DateTime<Utc>>().ok()
+}
+
+pub fn get_favorites_per_period(
+    account_id: i32,
+    period: TimelinePeriod,
+) -> Result<Vec<(String, i64)>, String> {
+    let conn = open_db()?;
+
+    let mut stmt = conn
+        .prepare(&format!(
+            r#"
+        SELECT {key} AS period, COUNT(*)
+        FROM accounts_post ap
+        WHERE ap.account_id = ? AND ap.favorited_at IS NOT NULL
+        GROUP BY period
+        ORDER BY period
+        "#,
+            key = period.sql_key()
+        ))
+        .map_err(|e| format!("Failed to construct query: {e}"))?;
+
+    let rows = stmt
+        .query_map([account_id], |row| Ok((row.get(0)?, row.get(1)?)))
+        .map_err(|e| format!("Failed to get favorites per period: {e}"))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Failed to enumerate favorites per period: {e}"))?;
+
+    Ok(rows)
+}
+
+pub fn get_tag_period_counts(
+    account_id: i32,
+    period: TimelinePeriod,
+) -> Result<Vec<(String, String, String, i64)>, String> {
+    let conn = open_db()?;
+
+    let mut stmt = conn
+        .prepare(&format!(
+            r#"
+        SELECT {key} AS period, t.name, t.group_type, COUNT(*)
+        FROM tags t
+        INNER JOIN tags_posts tp ON t.id = tp.tag_id
+        INNER JOIN accounts_post ap ON tp.post_id = ap.post_id
+        WHERE ap.account_id = ? AND ap.favorited_at IS NOT NULL
+        GROUP BY period, t.name, t.group_type
+        ORDER BY period
+        "#,
+            key = period.sql_key()
+        ))
+        .map_err(|e| format!("Failed to construct query: {e}"))?;
+
+    let rows = stmt
+        .query_map([account_id], |row| {
+            Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?))
+        })
+        .map_err(|e| format!("Failed to get tag period counts: {e}"))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Failed to enumerate tag period counts: {e}"))?;
+
+    Ok(rows)
+}
+
+pub fn get_shared_posts(account_a: i32, account_b: i32) -> Result<Vec<i64>, String> {
+    let conn = open_db()?;
+
+    let mut stmt = conn
+        .prepare(
+            r#"
+        SELECT a.post_id
+        FROM accounts_post a
+        INNER JOIN accounts_post b ON a.post_id = b.post_id
+        WHERE a.account_id = ?1 AND b.account_id = ?2
+        ORDER BY a.fav_position
+        "#,
+        )
+        .map_err(|e| format!("Failed to construct query: {e}"))?;
+
+    let ids = stmt
+        .query_map(params![account_a, account_b], |row| row.get(0))
+        .map_err(|e| format!("Failed to get shared posts: {e}"))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Failed to enumerate shared posts: {e}"))?;
+
+    Ok(ids)
+}
+
+pub fn get_all_favorites() -> Result<Vec<(i32, i64)>, String> {
+    let conn = open_db()?;
+
+    let mut stmt = conn
+        .prepare("SELECT account_id, post_id FROM accounts_post")
+        .map_err(|e| format!("Failed to construct query: {e}"))?;
+
+    let rows = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+        .map_err(|e| format!("Failed to get favorites: {e}"))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Failed to enumerate favorites: {e}"))?;
+
+    Ok(rows)
+}
+
+pub type FavoriteRow = (i64, Option<DateTime<Utc>>);
+
+pub fn get_account_favorites(account_id: i32) -> Result<Vec<FavoriteRow>, String> {
+    let conn = open_db()?;
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT post_id, favorited_at FROM accounts_post WHERE account_id = ? ORDER BY fav_position",
+        )
+        .map_err(|e| format!("Failed to construct query: {e}"))?;
+
+    let rows = stmt
+        .query_map([account_id], |row| {
+            Ok((row.get::<_, i64>(0)?, row.get::<_, Option<String>>(1)?))
+        })
+        .map_err(|e| format!("Failed to get favorites: {e}"))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Failed to enumerate favorites: {e}"))?;
+
+    Ok(rows
+        .into_iter()
+        .map(|(id, at)| (id, at.as_deref().and_then(parse_db_time)))
+        .collect())
+}
+
+pub fn get_non_favorite_post_ids(account_id: i32) -> Result<Vec<i64>, String> {
+    let conn = open_db()?;
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT id FROM posts WHERE id NOT IN (SELECT post_id FROM accounts_post WHERE account_id = ?) ORDER BY id",
+        )
+        .map_err(|e| format!("Failed to construct query: {e}"))?;
+
+    let ids = stmt
+        .query_map([account_id], |row| row.get(0))
+        .map_err(|e| format!("Failed to get posts: {e}"))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Failed to enumerate posts: {e}"))?;
+
+    Ok(ids)
+}
+
+pub fn get_stored_posts(ids: &[i64]) -> Result<Vec<Post>, String> {
+    let conn = open_db()?;
+
+    let mut select_post = conn
+        .prepare_cached(
+            "SELECT id, created_at, score_total, fav_count, rating FROM posts WHERE id = ?1",
+        )
+        .map_err(|e| format!("Failed to construct query: {e}"))?;
+    let mut select_tags = conn
+        .prepare_cached(
+            "
+            SELECT t.name, t.group_type
+            FROM tags t
+            INNER JOIN tags_posts tp ON t.id = tp.tag_id
+            WHERE tp.post_id = ?1",
+        )
+        .map_err(|e| format!("Failed to construct query: {e}"))?;
+
+    let mut posts = Vec::with_capacity(ids.len());
+    for &id in ids {
+        let row = select_post.query_row([id], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, i64>(2)?,
+                row.get::<_, i64>(3)?,
+                row.get::<_, String>(4)?,
+            ))
+        });
+        let (id, created_at, score_total, fav_count, rating) = match row {
+            Ok(r) => r,
+            Err(rusqlite::Error::QueryReturnedNoRows) => continue,
+            Err(e) => return Err(format!("Failed to get post {id}: {e}")),
+        };
+
+        let mut tags = Tags::default();
+        let tag_rows = select_tags
+            .query_map([id], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))
+            .map_err(|e| format!("Failed to get tags for post {id}: {e}"))?;
+        for tag in tag_rows {
+            let (name, group_type) =
+                tag.map_err(|e| format!("Failed to enumerate tags for post {id}: {e}"))?;
+            tags.push(&group_type, name);
+        }
+
+        posts.push(Post::stored(
+            id,
+            parse_db_time(&created_at).unwrap_or_default(),
+            score_total,
+            fav_count,
+            rating.parse()?,
+            tags,
+        ));
+    }
+
+    Ok(posts)
+}
+
+/// Cached pools among `ids` fetched after `fresh_after`; missing or stale ones are left out.
+pub fn get_cached_pools(ids: &[i64], fresh_after: DateTime<Utc>) -> Result<Vec<Pool>, String> {
+    let conn = open_db()?;
+
+    let mut stmt = conn
+        .prepare_cached(
+            "
+            SELECT id, name, description, category, is_active, post_ids, updated_at, fetched_at
+            FROM pools WHERE id = ?1",
+        )
+        .map_err(|e| format!("Failed to construct query: {e}"))?;
+
+    let mut pools = Vec::with_capacity(ids.len());
+    for &id in ids {
+        let row = stmt.query_row([id], |row| {
+            Ok((
+                Pool {
+                    id: row.get(0)?,
+                    name: row.get(1)?,
+                    description: row.get(2)?,
+                    category: row.get(3)?,
+                    is_active: row.get::<_, i64>(4)? != 0,
+                    post_ids: Vec::new(),
+                    updated_at: DateTime::<Utc>::default(),
+                },
+                row.get::<_, String>(5)?,
+                row.get::<_, String>(6)?,
+                row.get::<_, String>(7)?,
+            ))
+        });
+        let (mut pool, post_ids, updated_at, fetched_at) = match row {
+            Ok(r) => r,
+            Err(rusqlite::Error::QueryReturnedNoRows) => continue,
+            Err(e) => return Err(format!("Failed to get pool {id}: {e}")),
+        };
+        if parse_db_time(&fetched_at).is_none_or(|t| t < fresh_after) {
+            continue;
+        }
+
+        pool.post_ids = rocket::serde::json::from_str(&post_ids)
+            .map_err(|e| format!("Failed to parse post ids of pool {id}: {e}"))?;
+        pool.updated_at = parse_db_time(&updated_at).unwrap_or_default();
+        pools.push(pool);
+    }
+
+    Ok(pools)
+}
+
+pub fn save_pools(pools: &[Pool]) -> Result<(), String> {
+    let mut connection = open_db()?;
+    let tx = connection
+        .transaction()
+        .map_err(|e| format!("Failed to get transaction: {e}"))?;
+
+    {
+        let mut insert = tx
+            .prepare_cached(
+                "
+            INSERT INTO pools (id, name, description, category, is_active, post_ids, updated_at, fetched_at)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+            ON CONFLICT(id) DO UPDATE SET
+            name        = excluded.name,
+            description = excluded.description,
+            category    = excluded.category,
+            is_active   = excluded.is_active,
+            post_ids    = excluded.post_ids,
+            updated_at  = excluded.updated_at,
+            fetched_at  = excluded.fetched_at;",
+            )
+            .map_err(|e| format!("Failed to prepare transaction: {e}"))?;
+
+        let now = Utc::now().to_string();
+        for pool in pools {
+            let post_ids = rocket::serde::json::to_string(&pool.post_ids)
+                .map_err(|e| format!("Failed to serialize post ids of pool {}: {e}", pool.id))?;
+            insert
+                .execute(params![
+                    pool.id,
+                    pool.name,
+                    pool.description,
+                    pool.category,
+                    pool.is_active,
+                    post_ids,
+                    pool.updated_at.to_string(),
+                    now
+                ])
+                .map_err(|e| format!("Failed to execute transaction: {e}"))?;
+        }
+    }
+
+    tx.commit()
+        .map_err(|e| format!("Failed to commit transaction: {e}"))?;
+
+    Ok(())
+}
+
+/// Favorited artists of the account, most favorited first; `recent` is left empty.
+pub fn get_account_artists(account_id: i32, limit: usize) -> Result<Vec<ArtistSummary>, String> {
+    let conn = open_db()?;
+
+    let mut stmt = conn
+        .prepare(
+            "
+            SELECT t.name, COUNT(*), MIN(ap.favorited_at), MAX(ap.favorited_at), v.visited_at
+            FROM accounts_post ap
+            INNER JOIN tags_posts tp ON tp.post_id = ap.post_id
+            INNER JOIN tags t ON t.id = tp.tag_id AND t.group_type = 'artist'
+            LEFT JOIN artist_visits v ON v.account_id = ap.account_id AND v.artist = t.name
+            WHERE ap.account_id = ?1
+            GROUP BY t.name
+            ORDER BY COUNT(*) DESC, t.name",
+        )
+        .map_err(|e| format!("Failed to construct query: {e}"))?;
+
+    let rows = stmt
+        .query_map([account_id], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, i64>(1)?,
+                row.get::<_, Option<String>>(2)?,
+                row.get::<_, Option<String>>(3)?,
+                row.get::<_, Option<String>>(4)?,
+            ))
+        })
+        .map_err(|e| format!("Failed to get artists: {e}"))?;
+
+    let mut artists = Vec::new();
+    for row in rows {
+        let (name, favorites, first, last, visit) =
+            row.map_err(|e| format!("Failed to enumerate artists: {e}"))?;
+        if NON_ARTIST_TAGS.contains(&name.as_str()) {
+            continue;
+        }
+        artists.push(ArtistSummary {
+            name,
+            favorites,
+            first_favorited: first.as_deref().and_then(parse_db_time),
+            last_favorited: last.as_deref().and_then(parse_db_time),
+            last_visit: visit.as_deref().and_then(parse_db_time),
+            new_since_visit: 0,
+            recent: Vec::new(),
+        });
+        if artists.len() >= limit {
+            break;
+        }
+    }
+
+    Ok(artists)
+}
+
+pub fn set_artist_visit(account_id: i32, artist: &str) -> Result<(), String> {
+    open_db()?
+        .execute(
+            "
+            INSERT INTO artist_visits (account_id, artist, visited_at) VALUES (?1, ?2, ?3)
+            ON CONFLICT(account_id, artist) DO UPDATE SET visited_at = excluded.visited_at",
+            params![account_id, artist, Utc::now().to_string()],
+        )
+        .map_err(|e| format!("Failed to save artist visit: {e}"))?;
+
+    Ok(())
+}
+
+/// Scope size, posts carrying `tag` and co-occurrence counts of every other tag seen with
+/// it at least `min_together` times. The scope is the account's favorites, or every stored
+/// post when `account_id` is `None`.
+pub fn get_tag_cooccurrence(
+    tag: &str,
+    group: Option<&str>,
+    account_id: Option<i32>,
+    min_together: i64,
+) -> Result<(i64, i64, Vec<CoCount>), String> {
+    let conn = open_db()?;
+
+    // `?{p}` is the account id, NULL for the whole corpus.
+    let scope = |p: usize| match account_id {
+        Some(_) => format!("SELECT post_id FROM accounts_post WHERE account_id = ?{p}"),
+        None => format!("SELECT id AS post_id FROM posts WHERE ?{p} IS NULL"),
+    };
+    let tag = tag.trim().to_lowercase();
+
+    let scope_posts: i64 = conn
+        .query_row(
+            &format!("SELECT COUNT(*) FROM ({})", scope(1)),
+            params![account_id],
+            |row| row.get(0),
+        )
+        .map_err(|e| format!("Failed to count posts: {e}"))?;
+
+    let with_tag = format!(
+        "
+        WITH scope AS ({}),
+        target AS (
+            SELECT id FROM tags WHERE name = ?1 AND (?2 IS NULL OR group_type = ?2)
+        ),
+        with_tag AS (
+            SELECT DISTINCT tp.post_id
+            FROM tags_posts tp
+            INNER JOIN scope s ON s.post_id = tp.post_id
+            WHERE tp.tag_id IN (SELECT id FROM target)
+        )",
+        scope(3)
+    );
+
+    let tag_posts: i64 = conn
+        .query_row(
+            &format!("{with_tag} SELECT COUNT(*) FROM with_tag"),
+            params![tag, group, account_id],
+            |row| row.get(0),
+        )
+        .map_err(|e| format!("Failed to count tagged posts: {e}"))?;
+
+    let mut stmt = conn
+        .prepare(&format!(
+            "{with_tag},
+            co AS (
+                SELECT tp.tag_id, COUNT(*) AS together
+                FROM tags_posts tp
+                INNER JOIN with_tag w ON w.post_id = tp.post_id
+                WHERE tp.tag_id NOT IN (SELECT id FROM target)
+                GROUP BY tp.tag_id
+                HAVING COUNT(*) >= ?4
+            )
+            SELECT t.name, t.group_type, co.together,
+                   (SELECT COUNT(*) FROM tags_posts tp2
+                    INNER JOIN scope s2 ON s2.post_id = tp2.post_id
+                    WHERE tp2.tag_id = co.tag_id)
+            FROM co
+            INNER JOIN tags t ON t.id = co.tag_id"
+        ))
+        .map_err(|e| format!("Failed to construct query: {e}"))?;
+
+    let counts = stmt
+        .query_map(params![tag, group, account_id, min_together], |row| {
+            Ok(CoCount {
+                name: row.get(0)?,
+                group_type: row.get(1)?,
+                together: row.get(2)?,
+                count: row.get(3)?,
+            })
+        })
+        .map_err(|e| format!("Failed to get co-occurrences: {e}"))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Failed to enumerate co-occurrences: {e}"))?;
+
+    Ok((scope_posts, tag_posts, counts))
+}
+
+/// Account id and its last favorites sync, if any.
+pub type SyncTime = (i32, Option<DateTime<Utc>>);
+
+pub fn get_sync_times() -> Result<Vec<SyncTime>, String> {
+    let conn = open_db()?;
+
+    let mut stmt = conn
+        .prepare("SELECT id, synced_at FROM accounts ORDER BY id")
+        .map_err(|e| format!("Failed to construct query: {e}"))?;
+
+    stmt.query_map([], |row| {
+        let synced: Option<String> = row.get(1)?;
+        Ok((row.get(0)?, synced.as_deref().and_then(parse_db_time)))
+    })
+    .map_err(|e| format!("Failed to get accounts: {e}"))?
+    .collect::<Result<Vec<_>, _>>()
+    .map_err(|e| format!("Failed to enumerate accounts: {e}"))
+}
+
+pub fn set_synced_at(account_id: i32) -> Result<(), String> {
+    open_db()?
+        .execute(
+            "UPDATE accounts SET synced_at = ?2 WHERE id = ?1",
+            params![account_id, Utc::now().to_string()],
+        )
+        .map_err(|e| format!("Failed to save sync time: {e}"))?;
+
+    Ok(())
+}
+
+/// Newest digests of the account, newest first.
+pub fn get_digests(account_id: i32, limit: usize) -> Result<Vec<Digest>, String> {
+    let conn = open_db()?;
+
+    let mut stmt = conn
+        .prepare(
+            "
+            SELECT created_at, since, posts FROM digests
+            WHERE account_id = ?1
+            ORDER BY id DESC
+            LIMIT ?2",
+        )
+        .map_err(|e| format!("Failed to construct query: {e}"))?;
+
+    let rows = stmt
+        .query_map(params![account_id, limit as i64], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, Option<String>>(1)?,
+                row.get::<_, String>(2)?,
+            ))
+        })
+        .map_err(|e| format!("Failed to get digests: {e}"))?;
+
+    let mut digests = Vec::new();
+    for row in rows {
+        let (created_at, since, posts) = row.map_err(|e| format!("Failed to enumerate digests: {e}"))?;
+        digests.push(Digest {
+            account_id,
+            created_at: parse_db_time(&created_at).unwrap_or_default(),
+            since: since.as_deref().and_then(parse_db_time),
+            posts: rocket::serde::json::from_str(&posts)
+                .map_err(|e| format!("Failed to parse digest posts: {e}"))?,
+        });
+    }
+
+    Ok(digests)
+}
+
+/// Stores a digest and deletes all but the newest `keep` of the account.
+pub fn save_digest(digest: &Digest, keep: usize) -> Result<(), String> {
+    let mut connection = open_db()?;
+    let tx = connection
+        .transaction()
+        .map_err(|e| format!("Failed to get transaction: {e}"))?;
+
+    let posts = rocket::serde::json::to_string(&digest.posts)
+        .map_err(|e| format!("Failed to serialize digest posts: {e}"))?;
+    tx.execute(
+        "INSERT INTO digests (account_id, created_at, since, posts) VALUES (?1, ?2, ?3, ?4)",
+        params![
+            digest.account_id,
+            digest.created_at.to_string(),
+            digest.since.map(|s| s.to_string()),
+            posts
+        ],
+    )
+    .map_err(|e| format!("Failed to save digest: {e}"))?;
+
+    tx.execute(
+        "
+        DELETE FROM digests
+        WHERE account_id = ?1
+          AND id NOT IN (SELECT id FROM digests WHERE account_id = ?1 ORDER BY id DESC LIMIT ?2)",
+        params![digest.account_id, keep.max(1) as i64],
+    )
+    .map_err(|e| format!("Failed to prune digests: {e}"))?;
+
+    tx.commit()
+        .map_err(|e| format!("Failed to commit transaction: {e}"))?;
+
+    Ok(())
+}
+
+fn webhook_from_row(row: &rusqlite::Row) -> rusqlite::Result<Webhook> {
+    let ratings: String = row.get(4)?;
+    let secret: Option<String> = row.get(5)?;
+    let created_at: String = row.get(6)?;
+    Ok(Webhook {
+        id: row.get(0)?,
+        account_id: row.get(1)?,
+        url: row.get(2)?,
+        min_score: row.get::<_, f64>(3)? as f32,
+        ratings: ratings.split(',').filter_map(|r| r.parse().ok()).collect(),
+        has_secret: secret.is_some(),
+        secret,
+        created_at: parse_db_time(&created_at).unwrap_or_default(),
+    })
+}
+
+const WEBHOOK_COLUMNS: &str = "id, account_id, url, min_score, ratings, secret, created_at";
+
+pub fn get_webhooks(account_id: i32) -> Result<Vec<Webhook>, String> {
+    let conn = open_db()?;
+
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT {WEBHOOK_COLUMNS} FROM webhooks WHERE account_id = ?1 ORDER BY id"
+        ))
+        .map_err(|e| format!("Failed to construct query: {e}"))?;
+
+    stmt.query_map([account_id], webhook_from_row)
+        .map_err(|e| format!("Failed to get webhooks: {e}"))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Failed to enumerate webhooks: {e}"))
+}
+
+pub fn get_webhook(account_id: i32, webhook_id: i64) -> Result<Option<Webhook>, String> {
+    let conn = open_db()?;
+
+    conn.query_row(
+        &format!("SELECT {WEBHOOK_COLUMNS} FROM webhooks WHERE account_id = ?1 AND id = ?2"),
+        params![account_id, webhook_id],
+        webhook_from_row,
+    )
+    .optional()
+    .map_err(|e| format!("Failed to get webhook: {e}"))
+}
+
+pub fn create_webhook(account_id: i32, webhook: &NewWebhook) -> Result<Webhook, String> {
+    let conn = open_db()?;
+
+    let ratings = webhook
+        .ratings
+        .iter()
+        .map(|r| r.to_string())
+        .collect::<Vec<_>>()
+        .join(",");
+    let secret = webhook.secret.as_deref().filter(|s| !s.is_empty());
+    conn.execute(
+        "
+        INSERT INTO webhooks (account_id, url, min_score, ratings, secret, created_at)
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        params![
+            account_id,
+            webhook.url.trim(),
+            webhook.min_score as f64,
+            ratings,
+            secret,
+            Utc::now().to_string()
+        ],
+    )
+    .map_err(|e| format!("Failed to save webhook: {e}"))?;
+
+    get_webhook(account_id, conn.last_insert_rowid())?
+        .ok_or_else(|| "Saved webhook disappeared".to_string())
+}
+
+/// `false` when the account has no such webhook.
+pub fn delete_webhook(account_id: i32, webhook_id: i64) -> Result<bool, String> {
+    let deleted = open_db()?
+        .execute(
+            "DELETE FROM webhooks WHERE account_id = ?1 AND id = ?2",
+            params![account_id, webhook_id],
+        )
+        .map_err(|e| format!("Failed to delete webhook: {e}"))?;
+
+    Ok(deleted > 0)
+}
+
+/// Posts already delivered successfully to the webhook.
+pub fn get_delivered_posts(webhook_id: i64) -> Result<HashSet<i64>, String> {
+    let conn = open_db()?;
+
+    let mut stmt = conn
+        .prepare(
+            "
+            SELECT post_id FROM webhook_deliveries
+            WHERE webhook_id = ?1 AND post_id IS NOT NULL AND status BETWEEN 200 AND 299",
+        )
+        .map_err(|e| format!("Failed to construct query: {e}"))?;
+
+    stmt.query_map([webhook_id], |row| row.get(0))
+        .map_err(|e| format!("Failed to get deliveries: {e}"))?
+        .collect::<Result<HashSet<_>, _>>()
+        .map_err(|e| format!("Failed to enumerate deliveries: {e}"))
+}
+
+/// Newest deliveries of the webhook, newest first.
+pub fn get_webhook_deliveries(webhook_id: i64, limit: usize) -> Result<Vec<WebhookDelivery>, String> {
+    let conn = open_db()?;
+
+    let mut stmt = conn
+        .prepare(
+            "
+            SELECT id, webhook_id, event, post_id, attempt, status, error, delivered_at
+            FROM webhook_deliveries
+            WHERE webhook_id = ?1
+            ORDER BY id DESC
+            LIMIT ?2",
+        )
+        .map_err(|e| format!("Failed to construct query: {e}"))?;
+
+    stmt.query_map(params![webhook_id, limit as i64], |row| {
+        let delivered_at: String = row.get(7)?;
+        Ok(WebhookDelivery {
+            id: row.get(0)?,
+            webhook_id: row.get(1)?,
+            event: row.get(2)?,
+            post_id: row.get(3)?,
+            attempt: row.get(4)?,
+            status: row.get(5)?,
+            error: row.get(6)?,
+            delivered_at: parse_db_time(&delivered_at).unwrap_or_default(),
+        })
+    })
+    .map_err(|e| format!("Failed to get deliveries: {e}"))?
+    .collect::<Result<Vec<_>, _>>()
+    .map_err(|e| format!("Failed to enumerate deliveries: {e}"))
+}
+
+/// Logs a delivery and returns its id.
+pub fn save_webhook_delivery(delivery: &WebhookDelivery) -> Result<i64, String> {
+    let conn = open_db()?;
+
+    conn.execute(
+        "
+        INSERT INTO webhook_deliveries (webhook_id, event, post_id, attempt, status, error, delivered_at)
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        params![
+            delivery.webhook_id,
+            delivery.event,
+            delivery.post_id,
+            delivery.attempt,
+            delivery.status,
+            delivery.error,
+            delivery.delivered_at.to_string()
+        ],
+    )
+    .map_err(|e| format!("Failed to save delivery: {e}"))?;
+
+    Ok(conn.last_insert_rowid())
+}
+
+/// Deletes logged deliveries from before `cutoff`.
+pub fn prune_webhook_deliveries(cutoff: DateTime<Utc>) -> Result<usize, String> {
+    open_db()?
+        .execute(
+            "DELETE FROM webhook_deliveries WHERE delivered_at < ?1",
+            [cutoff.to_string()],
+        )
+        .map_err(|e| format!("Failed to prune deliveries: {e}"))
+}
+
+/// Queues a matched post for the webhook; `false` when it is already queued.
+pub fn queue_webhook_post(webhook_id: i64, post: &ScoredPost) -> Result<bool, String> {
+    let json = rocket::serde::json::to_string(post)
+        .map_err(|e| format!("Failed to serialize queued post: {e}"))?;
+    let inserted = open_db()?
+        .execute(
+            "
+            INSERT OR IGNORE INTO webhook_queue (webhook_id, post_id, post, queued_at)
+            VALUES (?1, ?2, ?3, ?4)",
+            params![webhook_id, post.post.id, json, Utc::now().to_string()],
+        )
+        .map_err(|e| format!("Failed to queue post: {e}"))?;
+
+    Ok(inserted > 0)
+}
+
+/// Oldest queued posts of the webhook.
+pub fn get_webhook_queue(webhook_id: i64, limit: usize) -> Result<Vec<ScoredPost>, String> {
+    let conn = open_db()?;
+
+    let mut stmt = conn
+        .prepare(
+            "
+            SELECT post FROM webhook_queue
+            WHERE webhook_id = ?1
+            ORDER BY queued_at, post_id
+            LIMIT ?2",
+        )
+        .map_err(|e| format!("Failed to construct query: {e}"))?;
+
+    let rows = stmt
+        .query_map(params![webhook_id, limit as i64], |row| row.get::<_, String>(0))
+        .map_err(|e| format!("Failed to get queued posts: {e}"))?;
+
+    let mut posts = Vec::new();
+    for row in rows {
+        let json = row.map_err(|e| format!("Failed to enumerate queued posts: {e}"))?;
+        posts.push(
+            rocket::serde::json::from_str(&json)
+                .map_err(|e| format!("Failed to parse queued post: {e}"))?,
+        );
+    }
+
+    Ok(posts)
+}
+
+pub fn dequeue_webhook_post(webhook_id: i64, post_id: i64) -> Result<(), String> {
+    open_db()?
+        .execute(
+            "DELETE FROM webhook_queue WHERE webhook_id = ?1 AND post_id = ?2",
+            params![webhook_id, post_id],
+        )
+        .map_err(|e| format!("Failed to dequeue post: {e}"))?;
+
+    Ok(())
+}
+
+/// Counts a failed run for a queued post and drops it after `max_runs`; `true` if dropped.
+pub fn fail_webhook_post(webhook_id: i64, post_id: i64, max_runs: u32) -> Result<bool, String> {
+    let conn = open_db()?;
+    conn.execute(
+        "UPDATE webhook_queue SET runs = runs + 1 WHERE webhook_id = ?1 AND post_id = ?2",
+        params![webhook_id, post_id],
+    )
+    .map_err(|e| format!("Failed to update queued post: {e}"))?;
+    let dropped = conn
+        .execute(
+            "DELETE FROM webhook_queue WHERE webhook_id = ?1 AND post_id = ?2 AND runs >= ?3",
+            params![webhook_id, post_id, max_runs],
+        )
+        .map_err(|e| format!("Failed to drop queued post: {e}"))?;
+
+    Ok(dropped > 0)
+}
+
+fn user_from_row(row: &rusqlite::Row) -> rusqlite::Result<User> {
+    let role: String = row.get(2)?;
+    let created_at: String = row.get(3)?;
+    Ok(User {
+        id: row.get(0)?,
+        username: row.get(1)?,
+        role: role.parse().unwrap_or(Role::User),
+        created_at: parse_db_time(&created_at).unwrap_or_default(),
+    })
+}
+
+pub fn get_users() -> Result<Vec<User>, String> {
+    let conn = open_db()?;
+
+    let mut stmt = conn
+        .prepare("SELECT id, username, role, created_at FROM users ORDER BY id")
+        .map_err(|e| format!("Failed to construct query: {e}"))?;
+
+    stmt.query_map([], user_from_row)
+        .map_err(|e| format!("Failed to get users: {e}"))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Failed to enumerate users: {e}"))
+}
+
+/// The user and their password hash.
+pub fn get_user_login(username: &str) -> Result<Option<(User, String)>, String> {
+    open_db()?
+        .query_row(
+            "SELECT id, username, role, created_at, password_hash FROM users WHERE username = ?1",
+            [username],
+            |row| Ok((user_from_row(row)?, row.get(4)?)),
+        )
+        .optional()
+        .map_err(|e| format!("Failed to get user: {e}"))
+}
+
+pub fn create_user(username: &str, password_hash: &str, role: Role) -> Result<User, String> {
+    let conn = open_db()?;
+
+    conn.execute(
+        "INSERT INTO users (username, password_hash, role, created_at) VALUES (?1, ?2, ?3, ?4)",
+        params![username, password_hash, role.to_string(), Utc::now().to_string()],
+    )
+    .map_err(|e| format!("Failed to save user: {e}"))?;
+
+    conn.query_row(
+        "SELECT id, username, role, created_at FROM users WHERE id = ?1",
+        [conn.last_insert_rowid()],
+        user_from_row,
+    )
+    .map_err(|e| format!("Failed to get user: {e}"))
+}
+
+pub fn set_user_password(username: &str, password_hash: &str) -> Result<bool, String> {
+    let updated = open_db()?
+        .execute(
+            "UPDATE users SET password_hash = ?2 WHERE username = ?1",
+            params![username, password_hash],
+        )
+        .map_err(|e| format!("Failed to save password: {e}"))?;
+
+    Ok(updated > 0)
+}
+
+/// `false` when there is no such user.
+pub fn delete_user(user_id: i64) -> Result<bool, String> {
+    let deleted = open_db()?
+        .execute("DELETE FROM users WHERE id = ?1", [user_id])
+        .map_err(|e| format!("Failed to delete user: {e}"))?;
+
+    Ok(deleted > 0)
+}
+
+fn api_token_from_row(row: &rusqlite::Row) -> rusqlite::Result<ApiToken> {
+    let created_at: String = row.get(3)?;
+    let expires_at: Option<String> = row.get(4)?;
+    let last_used_at: Option<String> = row.get(5)?;
+    Ok(ApiToken {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        kind: row.get(2)?,
+        created_at: parse_db_time(&created_at).unwrap_or_default(),
+        expires_at: expires_at.as_deref().and_then(parse_db_time),
+        last_used_at: last_used_at.as_deref().and_then(parse_db_time),
+    })
+}
+
+pub fn create_api_token(
+    user_id: i64,
+    token_hash: &str,
+    name: &str,
+    kind: &str,
+    expires_at: Option<DateTime<Utc>>,
+) -> Result<ApiToken, String> {
+    let conn = open_db()?;
+
+    conn.execute(
+        "
+        INSERT INTO api_tokens (user_id, token_hash, name, kind, created_at, expires_at)
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        params![
+            user_id,
+            token_hash,
+            name,
+            kind,
+            Utc::now().to_string(),
+            expires_at.map(|t| t.to_string())
+        ],
+    )
+    .map_err(|e| format!("Failed to save token: {e}"))?;
+
+    conn.query_row(
+        "SELECT id, name, kind, created_at, expires_at, last_used_at FROM api_tokens WHERE id = ?1",
+        [conn.last_insert_rowid()],
+        api_token_from_row,
+    )
+    .map_err(|e| format!("Failed to get token: {e}"))
+}
+
+pub fn get_api_tokens(user_id: i64) -> Result<Vec<ApiToken>, String> {
+    let conn = open_db()?;
+
+    let mut stmt = conn
+        .prepare(
+            "
+            SELECT id, name, kind, created_at, expires_at, last_used_at
+            FROM api_tokens WHERE user_id = ?1 ORDER BY id",
+        )
+        .map_err(|e| format!("Failed to construct query: {e}"))?;
+
+    stmt.query_map([user_id], api_token_from_row)
+        .map_err(|e| format!("Failed to get tokens: {e}"))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Failed to enumerate tokens: {e}"))
+}
+
+/// The user owning an unexpired token and the token id; marks the token as used.
+pub fn get_token_user(token_hash: &str) -> Result<Option<(User, i64)>, String> {
+    let conn = open_db()?;
+    let now = Utc::now();
+
+    let found = conn
+        .query_row(
+            "
+            SELECT u.id, u.username, u.role, u.created_at, t.id, t.expires_at
+            FROM api_tokens t
+            INNER JOIN users u ON u.id = t.user_id
+            WHERE t.token_hash = ?1",
+            [token_hash],
+            |row| {
+                let expires_at: Option<String> = row.get(5)?;
+                Ok((user_from_row(row)?, row.get::<_, i64>(4)?, expires_at))
+            },
+        )
+        .optional()
+        .map_err(|e| format!("Failed to get token: {e}"))?;
+
+    let Some((user, token_id, expires_at)) = found else {
+        return Ok(None);
+    };
+    if expires_at
+        .as_deref()
+        .and_then(parse_db_time)
+        .is_some_and(|t| t <= now)
+    {
+        return Ok(None);
+    }
+
+    conn.execute(
+        "UPDATE api_tokens SET last_used_at = ?2 WHERE id = ?1",
+        params![token_id, now.to_string()],
+    )
+    .map_err(|e| format!("Failed to update token: {e}"))?;
+
+    Ok(Some((user, token_id)))
+}
+
+/// `false` when the user has no such token.
+pub fn delete_api_token(user_id: i64, token_id: i64) -> Result<bool, String> {
+    let deleted = open_db()?
+        .execute(
+            "DELETE FROM api_tokens WHERE user_id = ?1 AND id = ?2",
+            params![user_id, token_id],
+        )
+        .map_err(|e| format!("Failed to delete token: {e}"))?;
+
+    Ok(deleted > 0)
+}
+
+/// Owner of a stored account; the outer `None` means there is no such account.
+pub fn get_account_owner(account_id: i32) -> Result<Option<Option<i64>>, String> {
+    open_db()?
+        .query_row(
+            "SELECT owner_id FROM accounts WHERE id = ?1",
+            [account_id],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| format!("Failed to get account owner: {e}"))
+}
+
+pub fn set_account_owner(account_id: i32, owner_id: Option<i64>) -> Result<bool, String> {
+    let updated = open_db()?
+        .execute(
+            "UPDATE accounts SET owner_id = ?2 WHERE id = ?1",
+            params![account_id, owner_id],
+        )
+        .map_err(|e| format!("Failed to set account owner: {e}"))?;
+
+    Ok(updated > 0)
+}
@@ -0,0 +1,239 @@
+use chrono::{DateTime, Utc};
+use std::collections::{HashMap, HashSet};
+
+use crate::db::{self, FavoriteRow};
+use crate::models::{Config, Post, cfg};
+use crate::utils::{
+    self, CoFavoriteIndex, IdfIndex, NegativeProfile, RankingMetrics, SplitMix64, aggregate_tag_counts,
+};
+
+#[derive(Debug, Clone)]
+pub struct EvaluationOptions {
+    pub holdout: f32,
+    pub k: usize,
+    pub negatives: Option<usize>,
+    pub seed: u64,
+}
+
+impl Default for EvaluationOptions {
+    fn default() -> Self {
+        Self {
+            holdout: 0.2,
+            k: 20,
+            negatives: None,
+            seed: 42,
+        }
+    }
+}
+
+pub struct EvaluationData {
+    pub account_id: i32,
+    pub favorites: usize,
+    train: Vec<(Post, Option<DateTime<Utc>>)>,
+    candidates: Vec<Post>,
+    relevant: HashSet<i64>,
+    collab: HashMap<i64, f32>,
+    blacklist: String,
+    dislikes: Vec<String>,
+    df: HashMap<String, i64>,
+    n_posts: i64,
+    now: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone)]
+pub struct EvaluationReport {
+    pub account_id: i32,
+    pub favorites: usize,
+    pub held_out: usize,
+    pub negatives: usize,
+    pub k: usize,
+    pub metrics: RankingMetrics,
+}
+
+/// The account's stored favorites in random order; evaluating needs at least `min`.
+fn shuffled_favorites(account_id: i32, min: usize, rng: &mut SplitMix64) -> Result<Vec<FavoriteRow>, String> {
+    let mut favorites = db::get_account_favorites(account_id)?;
+    if favorites.len() < min {
+        return Err(format!(
+            "Account {account_id} needs at least {min} stored favorites, found {}",
+            favorites.len()
+        ));
+    }
+    rng.shuffle(&mut favorites);
+    Ok(favorites)
+}
+
+impl EvaluationData {
+    pub fn prepare(account_id: i32, opts: &EvaluationOptions) -> Result<Self, String> {
+        let mut rng = SplitMix64::new(opts.seed);
+        let favorites = shuffled_favorites(account_id, 2, &mut rng)?;
+
+        let held_out = ((favorites.len() as f32 * opts.holdout.clamp(0.0, 1.0)).round() as usize)
+            .clamp(1, favorites.len() - 1);
+        let (held, kept) = favorites.split_at(held_out);
+        Self::from_split(account_id, favorites.len(), held, kept, &[], &mut rng, opts)
+    }
+
+    /// A validation split to pick settings on and a test split to report them on. Both
+    /// hold out `holdout` of the favorites; the test ones are hidden from the validation
+    /// split entirely (training posts, co-favorites and document frequencies), so
+    /// choosing settings never sees them.
+    pub fn prepare_tuning(account_id: i32, opts: &EvaluationOptions) -> Result<(Self, Self), String> {
+        let mut rng = SplitMix64::new(opts.seed);
+        let favorites = shuffled_favorites(account_id, 3, &mut rng)?;
+
+        let held_out = ((favorites.len() as f32 * opts.holdout.clamp(0.0, 1.0)).round() as usize)
+            .clamp(1, (favorites.len() - 1) / 2);
+        let (test, rest) = favorites.split_at(held_out);
+        let (validation, train) = rest.split_at(held_out);
+        Ok((
+            Self::from_split(
+                account_id,
+                favorites.len(),
+                validation,
+                train,
+                test,
+                &mut rng,
+                opts,
+            )?,
+            Self::from_split(account_id, favorites.len(), test, rest, &[], &mut rng, opts)?,
+        ))
+    }
+
+    /// Ranks `held` back among stored posts the account never favorited, learning its
+    /// taste from `kept` only. `hidden` favorites are neither ranked nor learned from.
+    fn from_split(
+        account_id: i32,
+        favorites: usize,
+        held: &[FavoriteRow],
+        kept: &[FavoriteRow],
+        hidden: &[FavoriteRow],
+        rng: &mut SplitMix64,
+        opts: &EvaluationOptions,
+    ) -> Result<Self, String> {
+        let relevant: HashSet<i64> = held.iter().map(|(id, _)| *id).collect();
+
+        let mut negative_ids = db::get_non_favorite_post_ids(account_id)?;
+        if negative_ids.is_empty() {
+            warn!(
+                "Account {account_id}: no stored posts outside its favorites, so every candidate is \
+                 relevant and the metrics are meaningless; store a second account to get negatives"
+            );
+        }
+        rng.shuffle(&mut negative_ids);
+        negative_ids.truncate(opts.negatives.unwrap_or(held.len() * 4));
+
+        let fav_times: HashMap<i64, Option<DateTime<Utc>>> = kept.iter().copied().collect();
+        let train = db::get_stored_posts(&kept.iter().map(|(id, _)| *id).collect::<Vec<_>>())?
+            .into_iter()
+            .map(|p| {
+                let at = fav_times.get(&p.id).copied().flatten();
+                (p, at)
+            })
+            .collect();
+
+        let mut candidate_ids: Vec<i64> = relevant.iter().copied().chain(negative_ids).collect();
+        rng.shuffle(&mut candidate_ids);
+        let candidates = db::get_stored_posts(&candidate_ids)?;
+
+        // Favorites this split must not know about: the ones it ranks and the hidden ones.
+        let unseen: HashSet<i64> = relevant
+            .iter()
+            .copied()
+            .chain(hidden.iter().map(|(id, _)| *id))
+            .collect();
+        let all_favorites = db::get_all_favorites()?;
+        let others: HashSet<i64> = all_favorites
+            .iter()
+            .filter(|(acc, _)| *acc != account_id)
+            .map(|(_, post)| *post)
+            .collect();
+        let collab_rows: Vec<(i32, i64)> = all_favorites
+            .into_iter()
+            .filter(|(acc, post)| !(*acc == account_id && unseen.contains(post)))
+            .collect();
+        let collab = CoFavoriteIndex::from_rows(&collab_rows).scores_for(account_id);
+
+        // Unseen posts only stored for this account would not be in the database when
+        // they come up as recommendations, so their tags leave the document frequencies.
+        let mut df = db::get_tags_df().map_err(|e| format!("Failed to get tag df: {e}"))?;
+        let mut n_posts = db::post_count();
+        let groups = cfg().tag_groups.clone();
+        let only_mine: Vec<i64> = unseen
+            .iter()
+            .copied()
+            .filter(|id| !others.contains(id))
+            .collect();
+        for post in db::get_stored_posts(&only_mine)? {
+            n_posts -= 1;
+            let tags: HashSet<&String> = post.tags.in_groups(&groups).map(|(_, t)| t).collect();
+            for tag in tags {
+                if let Some(count) = df.get_mut(tag.as_str()) {
+                    *count = (*count - 1).max(0);
+                }
+            }
+        }
+
+        Ok(Self {
+            account_id,
+            favorites,
+            train,
+            candidates,
+            relevant,
+            collab,
+            blacklist: db::get_account_by_id(account_id)?.blacklist,
+            dislikes: db::get_account_dislikes(account_id)?,
+            df,
+            n_posts,
+            now: Utc::now(),
+        })
+    }
+
+    pub fn evaluate(&self, cfg: &Config, k: usize) -> EvaluationReport {
+        let mut priors = cfg.priors.clone();
+        priors.now = self.now;
+
+        let idf = IdfIndex::from_df(&self.df, self.n_posts, self.now, cfg.df_floor, cfg.idf_max);
+
+        let counts = aggregate_tag_counts(
+            self.train.iter().flat_map(|(post, at)| {
+                post.tags
+                    .in_groups(&cfg.tag_groups)
+                    .map(move |(group, t)| (t.as_str(), group, *at))
+            }),
+            cfg.fav_decay_tau_days,
+            self.now,
+        );
+
+        let negative = NegativeProfile::build(
+            &cfg.negative,
+            &cfg.tag_groups,
+            &self.blacklist,
+            &self.dislikes,
+            &counts,
+            &idf,
+        );
+        let scorer = utils::build_scorer(cfg.scoring_model, &counts, cfg, &priors, &idf, &negative);
+
+        let mut ranked: Vec<(f32, bool)> = self
+            .candidates
+            .iter()
+            .map(|post| {
+                let score = scorer.score(post, *self.collab.get(&post.id).unwrap_or(&0.0));
+                (score, self.relevant.contains(&post.id))
+            })
+            .collect();
+        ranked.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+        let relevance: Vec<bool> = ranked.into_iter().map(|(_, r)| r).collect();
+
+        EvaluationReport {
+            account_id: self.account_id,
+            favorites: self.favorites,
+            held_out: self.relevant.len(),
+            negatives: self.candidates.len().saturating_sub(self.relevant.len()),
+            k,
+            metrics: utils::ranking_metrics(&relevance, self.relevant.len(), k),
+        }
+    }
+}
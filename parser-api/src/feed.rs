@@ -0,0 +1,351 @@
+use chrono::{DateTime, SubsecRound, Utc};
+use rocket::http::{ContentType, Status};
+use rocket::request::{self, FromRequest, Request};
+use rocket::response::{self, Responder, Response};
+use std::collections::{HashMap, HashSet};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::io::Cursor;
+use std::sync::{LazyLock, Mutex};
+
+use crate::db;
+use crate::models::{FeedFormat, Post, ScoredPost, TAG_GROUPS, cfg};
+use crate::recommend::{self, RecommendOptions};
+
+#[derive(Clone)]
+pub struct RenderedFeed {
+    pub format: FeedFormat,
+    pub body: String,
+    pub etag: String,
+    pub last_modified: DateTime<Utc>,
+    generated_at: DateTime<Utc>,
+}
+
+/// Account, format, score threshold (rounded, so it can be hashed) and digest source.
+type FeedKey = (i32, FeedFormat, String, bool);
+
+/// At this many cached feeds, expired ones are dropped before adding another, then the
+/// oldest if that was not enough. Clients pick `min_score`, so keys are not bounded
+/// otherwise; expired feeds are kept until then so `Last-Modified` can stay put.
+const MAX_FEEDS: usize = 1_000;
+
+static FEED_CACHE: LazyLock<Mutex<HashMap<FeedKey, RenderedFeed>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Builds (or reuses) the feed for an account, from live recommendations or, with
+/// `digest`, from the stored scheduler digests. `Last-Modified` only moves when the
+/// set of entries actually changes, so polling readers get 304s between updates.
+pub async fn build(
+    account_id: i32,
+    format: FeedFormat,
+    min_score: Option<f32>,
+    digest: bool,
+) -> Result<RenderedFeed, std::io::Error> {
+    let settings = cfg().feed.clone();
+    let min_score = min_score.unwrap_or(settings.min_score);
+    let key = (account_id, format, format!("{min_score:.3}"), digest);
+    let now = Utc::now();
+    let ttl = chrono::Duration::milliseconds((settings.cache_minutes * 60_000.0) as i64);
+
+    let previous = FEED_CACHE.lock().unwrap().get(&key).cloned();
+    if let Some(feed) = &previous
+        && now - feed.generated_at < ttl
+    {
+        return Ok(feed.clone());
+    }
+
+    let account = db::get_account_by_id(account_id).map_err(std::io::Error::other)?;
+    let mut entries = if digest {
+        digest_entries(account_id, min_score)?
+    } else {
+        let opts = RecommendOptions {
+            affinity_threshold: Some(min_score),
+            ..Default::default()
+        };
+        recommend::recommend(account_id, &opts).await?
+    };
+    entries.truncate(settings.limit);
+
+    let etag = entity_tag(&entries);
+    let last_modified = match &previous {
+        Some(feed) if feed.etag == etag => feed.last_modified,
+        _ => now.trunc_subsecs(0),
+    };
+    let title = format!("Recommendations for {}", account.name);
+    let body = match format {
+        FeedFormat::Atom => render_atom(&title, account_id, last_modified, &entries),
+        FeedFormat::Rss => render_rss(&title, account_id, last_modified, &entries),
+    };
+
+    let feed = RenderedFeed {
+        format,
+        body,
+        etag,
+        last_modified,
+        generated_at: now,
+    };
+    let mut cache = FEED_CACHE.lock().unwrap();
+    if cache.len() >= MAX_FEEDS && !cache.contains_key(&key) {
+        cache.retain(|_, f| now - f.generated_at < ttl);
+        if cache.len() >= MAX_FEEDS
+            && let Some(oldest) = cache
+                .iter()
+                .min_by_key(|(_, f)| f.generated_at)
+                .map(|(k, _)| k.clone())
+        {
+            cache.remove(&oldest);
+        }
+    }
+    cache.insert(key, feed.clone());
+    Ok(feed)
+}
+
+/// Posts of all kept digests, newest digest first, each post once.
+fn digest_entries(account_id: i32, min_score: f32) -> Result<Vec<ScoredPost>, std::io::Error> {
+    let digests = db::get_digests(account_id, cfg().schedule.keep_digests.max(1))
+        .map_err(std::io::Error::other)?;
+    let mut seen = HashSet::new();
+    Ok(digests
+        .into_iter()
+        .flat_map(|d| d.posts)
+        .filter(|sp| sp.score >= min_score && seen.insert(sp.post.id))
+        .collect())
+}
+
+fn entity_tag(entries: &[ScoredPost]) -> String {
+    let mut hasher = DefaultHasher::new();
+    for sp in entries {
+        sp.post.id.hash(&mut hasher);
+        sp.post.updated_at.timestamp().hash(&mut hasher);
+        format!("{:.3}", sp.score).hash(&mut hasher);
+    }
+    format!("W/\"{:x}\"", hasher.finish())
+}
+
+fn render_atom(title: &str, account_id: i32, updated: DateTime<Utc>, entries: &[ScoredPost]) -> String {
+    let domain = cfg().posts_domain.clone();
+    let mut out = String::from("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n");
+    out.push_str("<feed xmlns=\"http://www.w3.org/2005/Atom\">\n");
+    out.push_str(&format!("  <id>urn:e621-account-parser:feed:{account_id}</id>\n"));
+    out.push_str(&format!("  <title>{}</title>\n", escape(title)));
+    out.push_str(&format!("  <updated>{}</updated>\n", updated.to_rfc3339()));
+    out.push_str(&format!("  <link href=\"{}\"/>\n", escape(&domain)));
+
+    for sp in entries {
+        let post = &sp.post;
+        let link = format!("{domain}/posts/{}", post.id);
+        out.push_str("  <entry>\n");
+        out.push_str(&format!("    <id>{}</id>\n", escape(&link)));
+        out.push_str(&format!("    <title>{}</title>\n", escape(&entry_title(sp))));
+        out.push_str(&format!("    <link href=\"{}\"/>\n", escape(&link)));
+        out.push_str(&format!("    <published>{}</published>\n", post.created_at.to_rfc3339()));
+        out.push_str(&format!("    <updated>{}</updated>\n", post.updated_at.to_rfc3339()));
+        for artist in &post.tags.artist {
+            out.push_str(&format!("    <author><name>{}</name></author>\n", escape(artist)));
+        }
+        if post.tags.artist.is_empty() {
+            out.push_str("    <author><name>unknown</name></author>\n");
+        }
+        if let Some((url, mime)) = enclosure(post) {
+            out.push_str(&format!(
+                "    <link rel=\"enclosure\" type=\"{mime}\" href=\"{}\"/>\n",
+                escape(&url)
+            ));
+        }
+        for tag in post.tags.general.iter() {
+            out.push_str(&format!("    <category term=\"{}\"/>\n", escape(tag)));
+        }
+        out.push_str(&format!(
+            "    <content type=\"html\">{}</content>\n",
+            escape(&entry_html(sp, &link))
+        ));
+        out.push_str("  </entry>\n");
+    }
+    out.push_str("</feed>\n");
+    out
+}
+
+fn render_rss(title: &str, account_id: i32, updated: DateTime<Utc>, entries: &[ScoredPost]) -> String {
+    let domain = cfg().posts_domain.clone();
+    let mut out = String::from("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n");
+    out.push_str("<rss version=\"2.0\">\n<channel>\n");
+    out.push_str(&format!("  <title>{}</title>\n", escape(title)));
+    out.push_str(&format!("  <link>{}</link>\n", escape(&domain)));
+    out.push_str(&format!(
+        "  <description>Recommended posts for account {account_id}</description>\n"
+    ));
+    out.push_str(&format!("  <lastBuildDate>{}</lastBuildDate>\n", updated.to_rfc2822()));
+
+    for sp in entries {
+        let post = &sp.post;
+        let link = format!("{domain}/posts/{}", post.id);
+        out.push_str("  <item>\n");
+        out.push_str(&format!("    <guid isPermaLink=\"true\">{}</guid>\n", escape(&link)));
+        out.push_str(&format!("    <title>{}</title>\n", escape(&entry_title(sp))));
+        out.push_str(&format!("    <link>{}</link>\n", escape(&link)));
+        out.push_str(&format!("    <pubDate>{}</pubDate>\n", post.created_at.to_rfc2822()));
+        if let Some((url, mime)) = enclosure(post) {
+            // Upstream doesn't report preview sizes; 0 is the accepted "unknown".
+            out.push_str(&format!(
+                "    <enclosure url=\"{}\" type=\"{mime}\" length=\"0\"/>\n",
+                escape(&url)
+            ));
+        }
+        for tag in post.tags.general.iter() {
+            out.push_str(&format!("    <category>{}</category>\n", escape(tag)));
+        }
+        out.push_str(&format!(
+            "    <description>{}</description>\n",
+            escape(&entry_html(sp, &link))
+        ));
+        out.push_str("  </item>\n");
+    }
+    out.push_str("</channel>\n</rss>\n");
+    out
+}
+
+fn entry_title(sp: &ScoredPost) -> String {
+    let artists = if sp.post.tags.artist.is_empty() {
+        "unknown artist".to_string()
+    } else {
+        sp.post.tags.artist.join(", ")
+    };
+    format!("#{} by {artists} ({:.2})", sp.post.id, sp.score)
+}
+
+fn entry_html(sp: &ScoredPost, link: &str) -> String {
+    let post = &sp.post;
+    let mut html = String::new();
+    if let Some((url, _)) = enclosure(post) {
+        html.push_str(&format!(
+            "<p><a href=\"{}\"><img src=\"{}\" alt=\"#{}\"/></a></p>",
+            escape(link),
+            escape(&url),
+            post.id
+        ));
+    }
+    html.push_str(&format!(
+        "<p>Score {:.2} · rating {} · {} favorites</p>",
+        sp.score, post.rating, post.fav_count
+    ));
+    for group in TAG_GROUPS {
+        if let Some(tags) = post.tags.group(group)
+            && !tags.is_empty()
+        {
+            let list: Vec<String> = tags.iter().map(|t| escape(t)).collect();
+            html.push_str(&format!("<p><b>{group}:</b> {}</p>", list.join(", ")));
+        }
+    }
+    if !sp.related.is_empty() {
+        let ids: Vec<String> = sp.related.iter().map(|r| format!("#{}", r.post.id)).collect();
+        html.push_str(&format!("<p>Related: {}</p>", ids.join(", ")));
+    }
+    html
+}
+
+/// Preview image used as the entry's enclosure, falling back to the sample.
+fn enclosure(post: &Post) -> Option<(String, &'static str)> {
+    let url = post
+        .preview
+        .as_ref()
+        .and_then(|p| p.url.clone())
+        .or_else(|| post.sample.as_ref().and_then(|s| s.url.clone()))?;
+    let ext = url.rsplit('.').next().unwrap_or_default().to_lowercase();
+    let mime = match ext.as_str() {
+        "png" => "image/png",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        _ => "image/jpeg",
+    };
+    Some((url, mime))
+}
+
+fn escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// `If-None-Match` / `If-Modified-Since` from the request, if any.
+pub struct ConditionalGet {
+    if_none_match: Option<String>,
+    if_modified_since: Option<DateTime<Utc>>,
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for ConditionalGet {
+    type Error = ();
+
+    async fn from_request(req: &'r Request<'_>) -> request::Outcome<Self, Self::Error> {
+        let headers = req.headers();
+        request::Outcome::Success(ConditionalGet {
+            if_none_match: headers.get_one("If-None-Match").map(str::to_string),
+            if_modified_since: headers
+                .get_one("If-Modified-Since")
+                .and_then(|v| DateTime::parse_from_rfc2822(v).ok())
+                .map(|d| d.with_timezone(&Utc)),
+        })
+    }
+}
+
+impl ConditionalGet {
+    fn is_fresh(&self, feed: &RenderedFeed) -> bool {
+        // If-None-Match wins over If-Modified-Since when both are sent (RFC 9110).
+        if let Some(tags) = &self.if_none_match {
+            return tags
+                .split(',')
+                .map(str::trim)
+                .any(|t| t == "*" || t.trim_start_matches("W/") == feed.etag.trim_start_matches("W/"));
+        }
+        self.if_modified_since
+            .is_some_and(|since| feed.last_modified <= since)
+    }
+}
+
+pub struct FeedResponse {
+    feed: RenderedFeed,
+    not_modified: bool,
+}
+
+impl FeedResponse {
+    pub fn new(feed: RenderedFeed, conditional: &ConditionalGet) -> Self {
+        let not_modified = conditional.is_fresh(&feed);
+        FeedResponse { feed, not_modified }
+    }
+}
+
+impl<'r> Responder<'r, 'static> for FeedResponse {
+    fn respond_to(self, _: &'r Request<'_>) -> response::Result<'static> {
+        let max_age = (cfg().feed.cache_minutes * 60.0).max(0.0) as u64;
+        let mut builder = Response::build();
+        builder
+            .raw_header("ETag", self.feed.etag)
+            .raw_header(
+                "Last-Modified",
+                self.feed
+                    .last_modified
+                    .format("%a, %d %b %Y %H:%M:%S GMT")
+                    .to_string(),
+            )
+            .raw_header("Cache-Control", format!("public, max-age={max_age}"));
+
+        if self.not_modified {
+            builder.status(Status::NotModified);
+        } else {
+            let (top, sub) = self.feed.format.content_type();
+            builder
+                .header(ContentType::new(top, sub).with_params(("charset", "utf-8")))
+                .sized_body(self.feed.body.len(), Cursor::new(self.feed.body));
+        }
+        builder.ok()
+    }
+}
@@ -0,0 +1,79 @@
+use std::collections::BTreeMap;
+use std::sync::{LazyLock, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::models::{Check, Liveness, Readiness, cfg, config_status};
+use crate::{api, db};
+
+static STARTED: LazyLock<Instant> = LazyLock::new(Instant::now);
+
+/// Last upstream probe; reused for `health.probe_cache_seconds`.
+static PROBE: LazyLock<Mutex<Option<(Instant, Check)>>> = LazyLock::new(|| Mutex::new(None));
+
+/// Starts the uptime clock; called once while building the server.
+pub fn mark_started() {
+    LazyLock::force(&STARTED);
+}
+
+pub fn liveness() -> Liveness {
+    Liveness {
+        status: "ok",
+        version: env!("CARGO_PKG_VERSION"),
+        uptime_seconds: STARTED.elapsed().as_secs(),
+    }
+}
+
+fn check_migrations() -> Result<String, String> {
+    match db::migration_versions()? {
+        (Some(applied), latest) if applied >= latest => Ok(format!("at V{applied}")),
+        (Some(applied), latest) => Err(format!("at V{applied}, V{latest} is pending")),
+        (None, _) => Err("no migrations applied".to_string()),
+    }
+}
+
+fn check_database() -> Result<String, String> {
+    db::check_writable().map(|_| "writable".to_string())
+}
+
+/// The active config, valid by construction; a rejected edit of the file is only
+/// mentioned, since the previous config keeps serving.
+fn check_config() -> Result<String, String> {
+    let status = config_status();
+    let mut detail = format!("{} loaded at {}", &status.hash[..12], status.loaded_at.to_rfc3339());
+    if let Some(error) = status.last_error {
+        detail.push_str(&format!("; last reload rejected: {error}"));
+    }
+    Ok(detail)
+}
+
+async fn check_upstream() -> Check {
+    let max_age = Duration::from_secs_f32(cfg().health.probe_cache_seconds.max(0.0));
+    if let Some((at, check)) = &*PROBE.lock().unwrap()
+        && at.elapsed() < max_age
+    {
+        return check.clone();
+    }
+
+    let check = Check::from_result(match api::probe().await {
+        Ok(status) if status.is_success() => Ok(format!("answered {status}")),
+        Ok(status) => Err(format!("answered {status}")),
+        Err(e) => Err(e),
+    });
+    *PROBE.lock().unwrap() = Some((Instant::now(), check.clone()));
+    check
+}
+
+pub async fn readiness() -> Readiness {
+    let mut checks = BTreeMap::new();
+    checks.insert("migrations", Check::from_result(check_migrations()));
+    checks.insert("database", Check::from_result(check_database()));
+    checks.insert("config", Check::from_result(check_config()));
+    if cfg().health.probe_upstream {
+        checks.insert("upstream", check_upstream().await);
+    }
+
+    Readiness {
+        ready: checks.values().all(|c| c.ok),
+        checks,
+    }
+}
@@ -0,0 +1,230 @@
+use chrono::{SecondsFormat, Utc};
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use rocket::fairing::{Fairing, Info, Kind};
+use rocket::http::Header;
+use rocket::route::{Handler, Outcome, Route};
+use rocket::serde::json::serde_json;
+use rocket::{Data, Request, Response};
+use std::future::Future;
+use std::io::Write;
+use std::str::FromStr;
+use std::sync::{LazyLock, RwLock};
+
+use crate::models::{Config, LogFormat};
+
+const REDACTED: &str = "[redacted]";
+/// Keys whose values are masked, as in `access_token=…`, `"password":"…"` or `secret: "…"`.
+const SECRET_KEYS: [&str; 6] = ["access_token", "admin_api", "api_key", "password", "secret", "token"];
+/// Shorter configured secrets are not replaced literally; they would mask ordinary words.
+const MIN_SECRET_LEN: usize = 6;
+/// Longest `X-Request-Id` taken from a client; anything else gets a fresh id.
+const MAX_ID_LEN: usize = 64;
+
+/// What the logger needs from `[log]`, replaced by `configure` on every config reload.
+struct State {
+    format: LogFormat,
+    level: LevelFilter,
+    secrets: Vec<String>,
+}
+
+static STATE: LazyLock<RwLock<State>> = LazyLock::new(|| {
+    RwLock::new(State {
+        format: LogFormat::Text,
+        level: LevelFilter::Info,
+        secrets: Vec::new(),
+    })
+});
+
+tokio::task_local! {
+    /// Id of the API request or background job the current task works for.
+    static REQUEST_ID: String;
+}
+
+/// Installs the logger. Must run before Rocket starts, which only installs its own
+/// logger when none is set; Rocket's messages then go through this one as well.
+pub fn init() {
+    if log::set_boxed_logger(Box::new(Logger)).is_ok() {
+        log::set_max_level(STATE.read().unwrap().level);
+    }
+}
+
+/// Applies the `[log]` settings of a newly loaded config.
+pub fn configure(config: &Config) {
+    let level = LevelFilter::from_str(&config.log.level);
+    let mut secrets: Vec<String> = config.log.redact.clone();
+    secrets.push(config.admin_api.clone());
+    secrets.retain(|s| s.trim().len() >= MIN_SECRET_LEN);
+
+    let mut state = STATE.write().unwrap();
+    state.format = config.log.format;
+    state.level = *level.as_ref().unwrap_or(&LevelFilter::Info);
+    state.secrets = secrets;
+    log::set_max_level(state.level);
+    drop(state);
+
+    if level.is_err() {
+        warn!("Unknown log level '{}', using info", config.log.level);
+    }
+}
+
+/// The id of the request or job being handled, if any.
+pub fn current_id() -> Option<String> {
+    REQUEST_ID.try_with(Clone::clone).ok()
+}
+
+/// Runs `f` with `id` attached to everything it logs; used for background jobs.
+pub async fn scoped<F: Future>(id: String, f: F) -> F::Output {
+    REQUEST_ID.scope(id, f).await
+}
+
+/// Masks configured secrets, bearer tokens and the values of secret-looking keys.
+pub fn redact(msg: &str) -> String {
+    let mut out = msg.to_string();
+    for secret in &STATE.read().unwrap().secrets {
+        out = out.replace(secret.as_str(), REDACTED);
+    }
+    out = mask_after(&out, "bearer ");
+    for key in SECRET_KEYS {
+        out = mask_after(&out, &format!("{key}="));
+        out = mask_after(&out, &format!("\"{key}\":\""));
+        out = mask_after(&out, &format!("{key}: \""));
+    }
+    out
+}
+
+/// Replaces the value after each case-insensitive `marker`, up to the next delimiter.
+fn mask_after(s: &str, marker: &str) -> String {
+    // ASCII lowercasing keeps byte offsets, so positions found here apply to `s`.
+    let lower = s.to_ascii_lowercase();
+    let mut out = String::with_capacity(s.len());
+    let mut pos = 0;
+    while let Some(found) = lower[pos..].find(marker) {
+        let start = pos + found + marker.len();
+        let len = s[start..]
+            .find(|c: char| c.is_whitespace() || "&\"',;)".contains(c))
+            .unwrap_or(s.len() - start);
+        out.push_str(&s[pos..start]);
+        if len > 0 {
+            out.push_str(REDACTED);
+        }
+        pos = start + len;
+    }
+    out.push_str(&s[pos..]);
+    out
+}
+
+struct Logger;
+
+/// Rocket logs its launch banner as warnings so it shows at every level.
+fn is_launch(meta: &Metadata<'_>) -> bool {
+    meta.target().contains("rocket::launch")
+}
+
+impl Log for Logger {
+    fn enabled(&self, meta: &Metadata<'_>) -> bool {
+        meta.level() <= STATE.read().unwrap().level || is_launch(meta)
+    }
+
+    fn log(&self, record: &Record<'_>) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        let state = STATE.read().unwrap();
+        // Connection-level chatter from the HTTP stack, hidden by Rocket's logger too.
+        let from = |path| record.module_path().is_some_and(|m| m.starts_with(path));
+        if state.level < LevelFilter::Debug && (from("hyper") || from("rustls") || from("r2d2")) {
+            return;
+        }
+        let format = state.format;
+        drop(state);
+
+        let level = if is_launch(record.metadata()) { Level::Info } else { record.level() };
+        // Rocket marks lines that belong to the one before with a trailing `_`.
+        let indented = record.target().ends_with('_');
+        let msg = redact(&record.args().to_string());
+        let ts = Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true);
+        let id = current_id();
+
+        let line = match format {
+            LogFormat::Text => {
+                let id = id.map(|id| format!(" [{id}]")).unwrap_or_default();
+                let indent = if indented { "   >> " } else { " " };
+                format!("{ts} {level:<5}{id}{indent}{msg}")
+            }
+            LogFormat::Json => serde_json::json!({
+                "ts": ts,
+                "level": level.as_str(),
+                "target": record.target().trim_end_matches('_'),
+                "request_id": id,
+                "msg": msg,
+            })
+            .to_string(),
+        };
+        // Logs go to stderr so CLI commands keep stdout for their output.
+        let _ = writeln!(std::io::stderr().lock(), "{line}");
+    }
+
+    fn flush(&self) {
+        let _ = std::io::stderr().flush();
+    }
+}
+
+/// Request id kept in the request's local cache.
+struct RequestId(String);
+
+fn request_id<'a>(req: &'a Request<'_>) -> &'a str {
+    &req.local_cache(|| {
+        let incoming = req.headers().get_one("X-Request-Id").filter(|id| {
+            !id.is_empty()
+                && id.len() <= MAX_ID_LEN
+                && id.chars().all(|c| c.is_ascii_alphanumeric() || "-_.".contains(c))
+        });
+        RequestId(incoming.map_or_else(|| format!("{:016x}", rand::random::<u64>()), str::to_string))
+    })
+    .0
+}
+
+/// Gives every request an id, taken from `X-Request-Id` when the client or a proxy
+/// sent a usable one, and returns it in the response's `X-Request-Id`.
+pub struct RequestIds;
+
+#[rocket::async_trait]
+impl Fairing for RequestIds {
+    fn info(&self) -> Info {
+        Info {
+            name: "Request ids",
+            kind: Kind::Request | Kind::Response,
+        }
+    }
+
+    async fn on_request(&self, req: &mut Request<'_>, _: &mut Data<'_>) {
+        request_id(req);
+    }
+
+    async fn on_response<'r>(&self, req: &'r Request<'_>, res: &mut Response<'r>) {
+        res.set_header(Header::new("X-Request-Id", request_id(req).to_string()));
+    }
+}
+
+/// Runs a route's handler with the request id in scope.
+#[derive(Clone)]
+struct WithRequestId(Box<dyn Handler>);
+
+#[rocket::async_trait]
+impl Handler for WithRequestId {
+    async fn handle<'r>(&self, req: &'r Request<'_>, data: Data<'r>) -> Outcome<'r> {
+        REQUEST_ID.scope(request_id(req).to_string(), self.0.handle(req, data)).await
+    }
+}
+
+/// Wraps the handlers of `routes` so everything logged while handling a request,
+/// including upstream calls and database errors, carries its id.
+pub fn with_request_ids(routes: Vec<Route>) -> Vec<Route> {
+    routes
+        .into_iter()
+        .map(|mut route| {
+            route.handler = Box::new(WithRequestId(route.handler));
+            route
+        })
+        .collect()
+}
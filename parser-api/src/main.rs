@@ -2,178 +2,869 @@
 extern crate rocket;
 
 use chrono::Utc;
+use rocket::http::{ContentType, Status};
+use rocket::response::status::Custom;
 use rocket::{State, get};
 use rocket::{futures::lock::Mutex, serde::json::Json};
 use rusqlite::Result;
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use rocket_cors::AllowedOrigins;
 
 use crate::models::{
-    ScoredPost, UserApiResponse, cfg, default_path, reload_from, start_config_watcher,
+    AccountComparison, AccountScoring, ApiToken, ArtistSummary, ConfigStatus, Digest, FeedFormat, IssuedToken, Liveness, LoginRequest, NewApiToken, NewUser, Pool, PoolPage, PoolRecommendation, Readiness, RelatedTags, ScoredPost, ScoringProfile, Topic, TagTimeline, TimelinePeriod, NewWebhook, Role, User, Webhook, WebhookDelivery, cfg, config_status, default_path, load_config, reload_from,
+    start_config_watcher,
 };
 use crate::{
     db::{
-        DbInit, get_account_by_id, get_account_by_name, get_tag_counts, set_account, set_tag_counts,
+        DbInit, get_account_by_id, get_account_by_name, get_tag_counts, set_account,
     },
     models::{Post, TagCount, TruncatedAccount},
     rocket::serde::json
 };
 use rocket_okapi::okapi::openapi3::OpenApi;
 use rocket_okapi::{openapi, openapi_get_routes_spec, settings::OpenApiSettings, swagger_ui::*};
-use crate::utils::IdfIndex;
+use crate::auth::{AccountAccess, AdminUser, AuthUser, FeedUser};
+use crate::recommend::{RecommendOptions, ScoringContext};
+use crate::logging::{RequestIds, with_request_ids};
+use crate::metrics::RequestMetrics;
+use crate::quota::Quotas;
+use crate::scheduler::Scheduler;
+use crate::utils::{IdfIndex, RelatedSort};
 
 mod api;
+mod auth;
+mod cli;
 mod db;
+mod evaluation;
+mod feed;
+mod health;
+mod logging;
+mod metrics;
 mod models;
+mod quota;
+mod recommend;
+mod scheduler;
+mod sync;
+mod tuning;
 mod utils;
+mod webhooks;
 
 #[openapi(tag = "Processing")]
 #[post("/process/<account_id>")]
-async fn process_posts(account_id: i32) -> Result<String, String> {
-    let cfg = cfg();
-    let blacklist: HashSet<String> = cfg
-        .tag_blacklist
-        .iter()
-        .map(|s| s.to_lowercase())
-        .collect();
-    let account = get_account_by_id(account_id).map_err(|e| e.to_string())?;
-    let user = api::get_account(&account).await;
-    let favcount = match user {
-        UserApiResponse::FullCurrentUser(u) => u.favorite_count,
-        UserApiResponse::FullUser(u) => u.favorite_count,
-    };
-    let pages = (favcount / cfg.posts_limit) + (if favcount % cfg.posts_limit > 0 { 1 } else { 0 });
-
-    db::drop_account_posts(account_id).map_err(|e| format!("Failed to drop account posts: {e}"))?;
-
-    for i in 1..=pages {
-        let raw_posts = api::get_favorites(&account, i).await;
-        let posts: Vec<Post> = raw_posts
-            .into_iter()
-            .map(|p| strip_blacklisted_tags(p, &blacklist))
-            .collect();
-        info!("{} post(s) found on page {}", posts.len(), i);
-
-        db::save_posts(&posts, account.id).map_err(|e| format!("Failed to save posts: {e}"))?;
-
-        db::save_posts_tags_batch(&posts, &blacklist)
-            .map_err(|e| format!("Failed to save tags for page {i}: {e}"))?;
-    }
-
-    set_tag_counts(account_id).map_err(|e| format!("Failed to set account tag counts: {e}"))?;
+async fn process_posts(_access: AccountAccess, account_id: i32) -> Result<String, String> {
+    sync::sync_account(account_id).await?;
     Ok(json::to_string(&"okay :3").unwrap())
 }
 
-fn strip_blacklisted_tags(mut p: Post, blacklist: &HashSet<String>) -> Post {
-    let filter = |v: &mut Vec<String>| {
-        v.retain(|t| !blacklist.contains(&t.to_lowercase().trim().to_string()));
-    };
-    filter(&mut p.tags.artist);
-    filter(&mut p.tags.character);
-    filter(&mut p.tags.copyright);
-    filter(&mut p.tags.general);
-    filter(&mut p.tags.lore);
-    filter(&mut p.tags.meta);
-    filter(&mut p.tags.species);
-    p
-}
-
 #[openapi(tag = "Accounts")]
 #[get("/account/<account_id>/tag_counts")]
-async fn get_account_tag_counts(account_id: i32) -> Result<Json<Vec<TagCount>>, String> {
+async fn get_account_tag_counts(
+    _access: AccountAccess,
+    account_id: i32,
+) -> Result<Json<Vec<TagCount>>, String> {
     match get_tag_counts(account_id) {
         Ok(counts) => Ok(Json(counts.to_vec())),
         Err(e) => {
             let error_msg = format!("Failed to get tag counts: {e}");
-            eprintln!("{error_msg}");
+            error!("{error_msg}");
             Err(error_msg)
         }
     }
 }
 
+#[openapi(tag = "Accounts")]
+#[get("/account/<account_id>/tag_timeline?<period>&<recent>&<limit>")]
+async fn get_account_tag_timeline(
+    _access: AccountAccess,
+    account_id: i32,
+    period: Option<String>,
+    recent: Option<usize>,
+    limit: Option<usize>,
+) -> Result<Json<TagTimeline>, String> {
+    let period = TimelinePeriod::parse(period.as_deref())?;
+    let timeline = db::get_favorites_per_period(account_id, period).and_then(|favorites| {
+        let rows = db::get_tag_period_counts(account_id, period)?;
+        Ok(utils::build_timeline(
+            period,
+            &favorites,
+            &rows,
+            recent.unwrap_or(3),
+            limit.unwrap_or(10),
+        ))
+    });
+
+    match timeline {
+        Ok(timeline) => Ok(Json(timeline)),
+        Err(e) => {
+            let error_msg = format!("Failed to get tag timeline: {e}");
+            error!("{error_msg}");
+            Err(error_msg)
+        }
+    }
+}
+
+/// `None` for accounts the caller may not see.
 #[openapi(tag = "Users")]
 #[get("/user/name/<name>")]
-async fn get_account_name(name: &str) -> Result<Json<TruncatedAccount>, String> {
-    match get_account_by_name(name.to_string()) {
-        Ok(account) => Ok(Json(account)),
+async fn get_account_name(
+    auth: AuthUser,
+    name: &str,
+) -> Result<Option<Json<TruncatedAccount>>, String> {
+    let result = get_account_by_name(name.to_string())
+        .and_then(|account| Ok(auth.can_access(account.id)?.then_some(account)));
+
+    match result {
+        Ok(account) => Ok(account.map(Json)),
         Err(e) => {
             let error_msg = format!("Failed to get account: {e}");
-            eprintln!("{error_msg}");
+            error!("{error_msg}");
             Err(error_msg)
         }
     }
 }
 
 #[openapi(tag = "Users")]
-#[get("/user/id/<id>")]
-async fn get_account_id(id: i32) -> Result<Json<TruncatedAccount>, String> {
-    match get_account_by_id(id) {
+#[get("/user/id/<account_id>")]
+async fn get_account_id(
+    _access: AccountAccess,
+    account_id: i32,
+) -> Result<Json<TruncatedAccount>, String> {
+    match get_account_by_id(account_id) {
         Ok(account) => Ok(Json(account)),
         Err(e) => {
             let error_msg = format!("Failed to get account: {e}");
-            eprintln!("{error_msg}");
+            error!("{error_msg}");
             Err(error_msg)
         }
     }
 }
 
+/// Stores an account owned by the caller; others' accounts can only be changed by admins.
 #[openapi(tag = "Accounts")]
 #[post("/account", data = "<account>")]
-async fn create_account(account: Json<TruncatedAccount>) -> Result<(), String> {
-    match set_account(account.id, &account.name, &account.blacklist) {
+async fn create_account(
+    auth: AuthUser,
+    account: Json<TruncatedAccount>,
+) -> Result<(), Custom<String>> {
+    let owner = db::get_account_owner(account.id).map_err(|e| Custom(Status::InternalServerError, e))?;
+    if !auth.is_admin() && owner.is_some_and(|o| o != Some(auth.user.id)) {
+        return Err(Custom(Status::Forbidden, format!("Account {} belongs to another user", account.id)));
+    }
+
+    match set_account(account.id, &account.name, &account.blacklist, auth.owner_id()) {
         Ok(_) => Ok(()),
         Err(e) => {
             let error_msg = format!("Failed to get account: {e}");
-            eprintln!("{error_msg}");
+            error!("{error_msg}");
+            Err(Custom(Status::InternalServerError, error_msg))
+        }
+    }
+}
+
+#[openapi(tag = "Accounts")]
+#[get("/account/<account_id>/scoring")]
+async fn get_account_scoring(
+    _access: AccountAccess,
+    account_id: i32,
+) -> Result<Json<AccountScoring>, String> {
+    match db::get_account_scoring(account_id) {
+        Ok(scoring) => Ok(Json(scoring)),
+        Err(e) => {
+            let error_msg = format!("Failed to get account scoring: {e}");
+            error!("{error_msg}");
+            Err(error_msg)
+        }
+    }
+}
+
+#[openapi(tag = "Accounts")]
+#[put("/account/<account_id>/scoring", data = "<scoring>")]
+async fn set_account_scoring(
+    _access: AccountAccess,
+    account_id: i32,
+    scoring: Json<AccountScoring>,
+) -> Result<(), Custom<String>> {
+    let mut scoring = scoring.into_inner();
+    scoring.profile = scoring.profile.filter(|p| !p.trim().is_empty());
+
+    if let Err(e) = cfg().validate_scoring(&scoring) {
+        return Err(Custom(Status::BadRequest, format!("Invalid account scoring: {e}")));
+    }
+    match db::set_account_scoring(account_id, &scoring) {
+        Ok(_) => Ok(()),
+        Err(e) => {
+            let error_msg = format!("Failed to set account scoring: {e}");
+            error!("{error_msg}");
+            Err(Custom(Status::InternalServerError, error_msg))
+        }
+    }
+}
+
+#[openapi(tag = "Accounts")]
+#[get("/account/<account_id>/dislikes")]
+async fn get_account_dislikes(
+    _access: AccountAccess,
+    account_id: i32,
+) -> Result<Json<Vec<String>>, String> {
+    match db::get_account_dislikes(account_id) {
+        Ok(tags) => Ok(Json(tags)),
+        Err(e) => {
+            let error_msg = format!("Failed to get dislikes: {e}");
+            error!("{error_msg}");
+            Err(error_msg)
+        }
+    }
+}
+
+#[openapi(tag = "Accounts")]
+#[put("/account/<account_id>/dislikes", data = "<tags>")]
+async fn set_account_dislikes(
+    _access: AccountAccess,
+    account_id: i32,
+    tags: Json<Vec<String>>,
+) -> Result<(), String> {
+    match db::set_account_dislikes(account_id, &tags) {
+        Ok(_) => Ok(()),
+        Err(e) => {
+            let error_msg = format!("Failed to set dislikes: {e}");
+            error!("{error_msg}");
+            Err(error_msg)
+        }
+    }
+}
+
+// Artists OR-ed into a single upstream search, well below its per-query tag limit.
+const ARTIST_BATCH: usize = 10;
+
+#[openapi(tag = "Accounts")]
+#[get("/account/<account_id>/artists?<limit>&<recent>")]
+async fn get_account_artists(
+    _access: AccountAccess,
+    account_id: i32,
+    limit: Option<usize>,
+    recent: Option<usize>,
+) -> Result<Json<Vec<ArtistSummary>>, String> {
+    let account = get_account_by_id(account_id).map_err(|e| format!("Failed to get account: {e}"))?;
+    let mut artists = db::get_account_artists(account_id, limit.unwrap_or(20))
+        .map_err(|e| format!("Failed to get artists: {e}"))?;
+    let favorited: HashSet<i64> = db::get_account_favorites(account_id)
+        .map_err(|e| format!("Failed to get favorites: {e}"))?
+        .into_iter()
+        .map(|(id, _)| id)
+        .collect();
+
+    let recent = recent.unwrap_or(6);
+    if recent > 0 {
+        for batch in artists.chunks_mut(ARTIST_BATCH) {
+            let query = if let [artist] = batch {
+                artist.name.clone()
+            } else {
+                batch.iter().map(|a| format!("~{}", a.name)).collect::<Vec<_>>().join(" ")
+            };
+            let posts = api::search_posts(&account, &query, (recent * batch.len() * 2).min(320)).await;
+
+            for post in posts.iter().filter(|p| !favorited.contains(&p.id)) {
+                for artist in batch.iter_mut() {
+                    if artist.recent.len() < recent && post.tags.artist.contains(&artist.name) {
+                        artist.recent.push(post.clone());
+                    }
+                }
+            }
+        }
+    }
+
+    for artist in &mut artists {
+        let since = artist.last_visit.or(artist.last_favorited);
+        artist.new_since_visit = artist
+            .recent
+            .iter()
+            .filter(|p| since.is_none_or(|t| p.created_at > t))
+            .count();
+    }
+
+    Ok(Json(artists))
+}
+
+#[openapi(tag = "Accounts")]
+#[put("/account/<account_id>/artists/<name>/visit")]
+async fn visit_artist(_access: AccountAccess, account_id: i32, name: &str) -> Result<(), String> {
+    match db::set_artist_visit(account_id, name) {
+        Ok(()) => Ok(()),
+        Err(e) => {
+            let error_msg = format!("Failed to save artist visit: {e}");
+            error!("{error_msg}");
+            Err(error_msg)
+        }
+    }
+}
+
+/// Latest digest written by the background scheduler, if any.
+#[openapi(tag = "Accounts")]
+#[get("/account/<account_id>/digest")]
+async fn get_account_digest(
+    _access: AccountAccess,
+    account_id: i32,
+) -> Result<Option<Json<Digest>>, String> {
+    match db::get_digests(account_id, 1) {
+        Ok(digests) => Ok(digests.into_iter().next().map(Json)),
+        Err(e) => {
+            let error_msg = format!("Failed to get digest: {e}");
+            error!("{error_msg}");
+            Err(error_msg)
+        }
+    }
+}
+
+/// Runs a scheduled sync for one account right away.
+#[openapi(tag = "Processing")]
+#[post("/account/<account_id>/digest")]
+async fn refresh_account_digest(
+    _access: AccountAccess,
+    account_id: i32,
+) -> Result<Json<Digest>, String> {
+    scheduler::refresh(account_id).await.map(Json)
+}
+
+#[openapi(tag = "Webhooks")]
+#[get("/account/<account_id>/webhooks")]
+async fn get_account_webhooks(
+    _access: AccountAccess,
+    account_id: i32,
+) -> Result<Json<Vec<Webhook>>, String> {
+    db::get_webhooks(account_id)
+        .map(Json)
+        .map_err(|e| format!("Failed to get webhooks: {e}"))
+}
+
+#[openapi(tag = "Webhooks")]
+#[post("/account/<account_id>/webhooks", data = "<webhook>")]
+async fn create_account_webhook(
+    _access: AccountAccess,
+    account_id: i32,
+    webhook: Json<NewWebhook>,
+) -> Result<Json<Webhook>, String> {
+    let result = match webhooks::validate_url(&webhook.url).await {
+        Ok(()) => db::create_webhook(account_id, &webhook),
+        Err(e) => Err(e),
+    };
+
+    match result {
+        Ok(created) => Ok(Json(created)),
+        Err(e) => {
+            let error_msg = format!("Failed to create webhook: {e}");
+            error!("{error_msg}");
+            Err(error_msg)
+        }
+    }
+}
+
+#[openapi(tag = "Webhooks")]
+#[delete("/account/<account_id>/webhooks/<webhook_id>")]
+async fn delete_account_webhook(
+    _access: AccountAccess,
+    account_id: i32,
+    webhook_id: i64,
+) -> Result<Option<()>, String> {
+    db::delete_webhook(account_id, webhook_id)
+        .map(|deleted| deleted.then_some(()))
+        .map_err(|e| format!("Failed to delete webhook: {e}"))
+}
+
+/// Sends a sample payload to the webhook and returns the logged delivery.
+#[openapi(tag = "Webhooks")]
+#[post("/account/<account_id>/webhooks/<webhook_id>/test")]
+async fn test_account_webhook(
+    _access: AccountAccess,
+    account_id: i32,
+    webhook_id: i64,
+) -> Result<Option<Json<WebhookDelivery>>, String> {
+    let Some(webhook) = db::get_webhook(account_id, webhook_id)? else {
+        return Ok(None);
+    };
+    webhooks::send_test(&webhook).await.map(|d| Some(Json(d)))
+}
+
+#[openapi(tag = "Webhooks")]
+#[get("/account/<account_id>/webhooks/<webhook_id>/deliveries?<limit>")]
+async fn get_webhook_deliveries(
+    _access: AccountAccess,
+    account_id: i32,
+    webhook_id: i64,
+    limit: Option<usize>,
+) -> Result<Option<Json<Vec<WebhookDelivery>>>, String> {
+    if db::get_webhook(account_id, webhook_id)?.is_none() {
+        return Ok(None);
+    }
+    db::get_webhook_deliveries(webhook_id, limit.unwrap_or(50))
+        .map(|d| Some(Json(d)))
+        .map_err(|e| format!("Failed to get deliveries: {e}"))
+}
+
+#[openapi(tag = "Tags")]
+#[get("/tags/related?<tag>&<group>&<account_id>&<sort>&<limit>&<min_count>")]
+async fn get_related_tags(
+    _access: AccountAccess,
+    tag: &str,
+    group: Option<&str>,
+    account_id: Option<i32>,
+    sort: Option<&str>,
+    limit: Option<usize>,
+    min_count: Option<i64>,
+) -> Result<Json<RelatedTags>, String> {
+    let sort: RelatedSort = sort.map(str::parse).transpose()?.unwrap_or_default();
+
+    match db::get_tag_cooccurrence(tag, group, account_id, min_count.unwrap_or(2)) {
+        Ok((scope_posts, tag_posts, counts)) => Ok(Json(utils::related_tags(
+            tag,
+            scope_posts,
+            tag_posts,
+            counts,
+            sort,
+            limit.unwrap_or(25),
+        ))),
+        Err(e) => {
+            let error_msg = format!("Failed to get related tags: {e}");
+            error!("{error_msg}");
+            Err(error_msg)
+        }
+    }
+}
+
+#[openapi(tag = "Accounts")]
+#[get("/account/<account_id>/topics?<k>")]
+async fn get_account_topics(
+    _access: AccountAccess,
+    account_id: i32,
+    k: Option<usize>,
+) -> Result<Json<Vec<Topic>>, std::io::Error> {
+    let ctx = ScoringContext::load(account_id, None, None)?;
+    let mut settings = ctx.cfg.topics.clone();
+    if let Some(k) = k {
+        settings.k = k;
+    }
+    Ok(Json(ctx.topics(account_id, &settings)?.topics.clone()))
+}
+
+#[openapi(tag = "Recommendations")]
+#[get("/tag_groups")]
+async fn get_tag_groups() -> Json<Vec<String>> {
+    Json(cfg().tag_groups.clone())
+}
+
+#[openapi(tag = "Recommendations")]
+#[get("/profiles")]
+async fn get_scoring_profiles() -> Json<BTreeMap<String, ScoringProfile>> {
+    Json(cfg().profiles.clone().into_iter().collect())
+}
+
+#[openapi(tag = "Config")]
+#[get("/config/status")]
+async fn get_config_status(_admin: AdminUser) -> Json<ConfigStatus> {
+    Json(config_status())
+}
+
+fn build_comparison(
+    account_a: i32,
+    account_b: i32,
+    limit: usize,
+) -> Result<AccountComparison, String> {
+    let cfg = cfg();
+
+    let mut priors = cfg.priors.clone();
+    priors.now = Utc::now();
+
+    let a = get_account_by_id(account_a)?;
+    let b = get_account_by_id(account_b)?;
+    let a_counts = get_tag_counts(account_a)?;
+    let b_counts = get_tag_counts(account_b)?;
+    let idf = IdfIndex::from_db(
+        db::get_tags_df,
+        db::post_count,
+        priors.now,
+        cfg.df_floor,
+        cfg.idf_max,
+    )
+    .map_err(|e| format!("Failed to build IDF index: {e}"))?;
+
+    let a_vec = utils::user_vector(&a_counts, &cfg.group_weights, &priors, &idf);
+    let b_vec = utils::user_vector(&b_counts, &cfg.group_weights, &priors, &idf);
+    let (similarity, shared_tags, unique_a, unique_b) =
+        utils::compare_tag_profiles(&a_counts, &b_counts, &a_vec, &b_vec, limit);
+
+    let shared_posts = db::get_shared_posts(account_a, account_b)?;
+
+    Ok(AccountComparison {
+        account_a: a,
+        account_b: b,
+        similarity,
+        shared_tags,
+        unique_a,
+        unique_b,
+        shared_post_count: shared_posts.len(),
+        shared_post_ids: shared_posts.into_iter().take(limit).collect(),
+    })
+}
+
+#[openapi(tag = "Accounts")]
+#[get("/compare/<account_a>/<account_b>?<limit>")]
+async fn compare_accounts(
+    _access: AccountAccess,
+    account_a: i32,
+    account_b: i32,
+    limit: Option<usize>,
+) -> Result<Json<AccountComparison>, String> {
+    match build_comparison(account_a, account_b, limit.unwrap_or(25)) {
+        Ok(comparison) => Ok(Json(comparison)),
+        Err(e) => {
+            let error_msg = format!("Failed to compare accounts: {e}");
+            error!("{error_msg}");
             Err(error_msg)
         }
     }
 }
 
+// Each query parameter is a handler argument.
+#[allow(clippy::too_many_arguments)]
 #[openapi(tag = "Recommendations")]
-#[get("/recommendations/<account_id>?<page>&<affinity_threshold>")]
+#[get("/recommendations/<account_id>?<page>&<affinity_threshold>&<profile>&<model>&<group>&<topic_quota>")]
 async fn get_recommendations(
+    _access: AccountAccess,
     account_id: i32,
     page: Option<i32>,
     affinity_threshold: Option<f32>,
+    profile: Option<String>,
+    model: Option<String>,
+    group: Option<bool>,
+    topic_quota: Option<usize>,
 ) -> Result<Json<Vec<ScoredPost>>, std::io::Error> {
-    let cfg = cfg();
+    let opts = RecommendOptions {
+        page,
+        affinity_threshold,
+        profile,
+        model,
+        group: group.unwrap_or(true),
+        topic_quota,
+    };
+    recommend::recommend(account_id, &opts).await.map(Json)
+}
 
-    let mut priors = cfg.priors.clone();
-    priors.now = Utc::now();
+// The upstream `id:` search takes at most this many ids per request.
+const UPSTREAM_ID_BATCH: usize = 100;
+const POOL_PAGE_SIZE: usize = 24;
 
-    let tags: Vec<TagCount> = get_tag_counts(account_id)
-        .map_err(|e| std::io::Error::other(format!("Failed to get tag counts: {e}")))?
-        .to_vec();
+async fn posts_by_ids(account: &TruncatedAccount, ids: &[i64]) -> Vec<Post> {
+    let mut posts = Vec::with_capacity(ids.len());
+    for chunk in ids.chunks(UPSTREAM_ID_BATCH) {
+        posts.extend(api::get_posts_by_ids(account, chunk).await);
+    }
+    posts
+}
 
-    let account = get_account_by_id(account_id)
-        .map_err(|e| std::io::Error::other(format!("Failed to get account: {e}")))?;
-    let posts: Vec<Post> = api::get_posts(&account, page).await;
+/// Pools in the order of `ids`, fetching those missing from the cache or older than
+/// `pools.cache_hours`.
+async fn load_pools(ids: &[i64]) -> Result<Vec<Pool>, String> {
+    let max_age = chrono::Duration::seconds((cfg().pools.cache_hours * 3600.0) as i64);
+    let mut pools: HashMap<i64, Pool> = db::get_cached_pools(ids, Utc::now() - max_age)?
+        .into_iter()
+        .map(|p| (p.id, p))
+        .collect();
 
-    let mut scored: Vec<ScoredPost> = Vec::with_capacity(posts.len());
-    for post in posts {
-        let tmp_post = post.clone();
+    let missing: Vec<i64> = ids.iter().copied().filter(|id| !pools.contains_key(id)).collect();
+    for chunk in missing.chunks(UPSTREAM_ID_BATCH) {
+        let fetched = api::get_pools(chunk).await;
+        db::save_pools(&fetched)?;
+        pools.extend(fetched.into_iter().map(|p| (p.id, p)));
+    }
 
-        let idf = IdfIndex::from_db(db::get_tags_df, db::post_count, priors.now).unwrap();
-        let s = utils::post_affinity(
-            &tags,
-            &post,
-            &cfg.group_weights,
-            &priors,
-            &idf
-        );
+    Ok(ids.iter().filter_map(|id| pools.remove(id)).collect())
+}
 
-        scored.push(ScoredPost {
-            post: tmp_post,
-            score: s,
-        });
+// Up to `n` member ids spread evenly over the pool, so long series are not judged
+// by their first pages alone.
+fn sample_members(post_ids: &[i64], n: usize) -> Vec<i64> {
+    if post_ids.len() <= n {
+        return post_ids.to_vec();
     }
+    (0..n).map(|i| post_ids[i * post_ids.len() / n]).collect()
+}
+
+#[openapi(tag = "Recommendations")]
+#[get("/recommendations/<account_id>/pools?<page>&<affinity_threshold>&<profile>&<model>")]
+async fn get_pool_recommendations(
+    _access: AccountAccess,
+    account_id: i32,
+    page: Option<i32>,
+    affinity_threshold: Option<f32>,
+    profile: Option<String>,
+    model: Option<String>,
+) -> Result<Json<Vec<PoolRecommendation>>, std::io::Error> {
+    let ctx = ScoringContext::load(account_id, profile.as_deref(), model.as_deref())?;
+    let candidates: Vec<Post> = api::get_posts(&ctx.account, page).await;
+
+    let mut seen = HashSet::new();
+    let pool_ids: Vec<i64> = candidates
+        .iter()
+        .flat_map(|p| p.pools.iter().copied())
+        .filter(|id| seen.insert(*id))
+        .collect();
+    let pools = load_pools(&pool_ids)
+        .await
+        .map_err(|e| std::io::Error::other(format!("Failed to load pools: {e}")))?;
+
+    let samples: Vec<Vec<i64>> = pools
+        .iter()
+        .map(|p| sample_members(&p.post_ids, ctx.cfg.pools.sample_size))
+        .collect();
+    let mut members: HashMap<i64, Post> = candidates.into_iter().map(|p| (p.id, p)).collect();
+    let missing: Vec<i64> = samples
+        .iter()
+        .flatten()
+        .copied()
+        .filter(|id| !members.contains_key(id))
+        .collect::<HashSet<_>>()
+        .into_iter()
+        .collect();
+    members.extend(
+        posts_by_ids(&ctx.account, &missing)
+            .await
+            .into_iter()
+            .map(|p| (p.id, p)),
+    );
+
+    let collab = recommend::cofavorites()?.scores_for(account_id);
+    let scorer = ctx.scorer();
+
+    let mut recommendations: Vec<PoolRecommendation> = pools
+        .into_iter()
+        .zip(samples)
+        .filter_map(|(pool, sample)| {
+            let mut scored: Vec<ScoredPost> = sample
+                .iter()
+                .filter_map(|id| members.get(id))
+                .map(|post| ScoredPost {
+                    post: post.clone(),
+                    score: scorer.score(post, *collab.get(&post.id).unwrap_or(&0.0)),
+                    related: Vec::new(),
+                })
+                .collect();
+            if scored.is_empty() {
+                return None;
+            }
+            scored.sort_by(|a, b| b.score.total_cmp(&a.score));
+
+            let score = scored.iter().map(|sp| sp.score).sum::<f32>() / scored.len() as f32;
+            Some(PoolRecommendation {
+                pool,
+                score,
+                best: scored[0].score,
+                scored_posts: scored.len(),
+                cover: scored.into_iter().next(),
+            })
+        })
+        .collect();
 
     if let Some(threshold) = affinity_threshold {
-        scored.retain(|sp| sp.score >= threshold);
+        recommendations.retain(|r| r.score >= threshold);
     }
+    recommendations.sort_by(|a, b| b.score.total_cmp(&a.score));
 
-    Ok(Json(scored))
+    Ok(Json(recommendations))
+}
+
+#[openapi(tag = "Recommendations")]
+#[get("/pool/<pool_id>?<account_id>&<page>")]
+async fn get_pool(
+    _access: AccountAccess,
+    pool_id: i64,
+    account_id: i32,
+    page: Option<usize>,
+) -> Result<Json<PoolPage>, std::io::Error> {
+    let ctx = ScoringContext::load(account_id, None, None)?;
+    let pool = load_pools(&[pool_id])
+        .await
+        .map_err(|e| std::io::Error::other(format!("Failed to load pool: {e}")))?
+        .pop()
+        .ok_or_else(|| std::io::Error::other(format!("No pool {pool_id} found")))?;
+
+    let pages = pool.post_ids.len().div_ceil(POOL_PAGE_SIZE).max(1);
+    let page = page.unwrap_or(1).clamp(1, pages);
+    let ids: Vec<i64> = pool
+        .post_ids
+        .iter()
+        .skip((page - 1) * POOL_PAGE_SIZE)
+        .take(POOL_PAGE_SIZE)
+        .copied()
+        .collect();
+
+    let mut fetched: HashMap<i64, Post> = posts_by_ids(&ctx.account, &ids)
+        .await
+        .into_iter()
+        .map(|p| (p.id, p))
+        .collect();
+    let scorer = ctx.scorer();
+    let posts = ids
+        .iter()
+        .filter_map(|id| fetched.remove(id))
+        .map(|post| ScoredPost {
+            score: scorer.score(&post, 0.0),
+            post,
+            related: Vec::new(),
+        })
+        .collect();
+
+    Ok(Json(PoolPage { pool, page, pages, posts }))
+}
+
+/// `<account_id>.atom` or `<account_id>.rss`; feed readers want the extension in the URL
+/// and pass their token as `?access_token=`.
+#[get("/feed/<file>?<min_score>&<digest>")]
+async fn get_feed(
+    file: &str,
+    min_score: Option<f32>,
+    digest: Option<bool>,
+    conditional: feed::ConditionalGet,
+    feed_user: FeedUser,
+) -> Result<feed::FeedResponse, Custom<String>> {
+    let auth = feed_user.0;
+    let not_found = || Custom(Status::NotFound, format!("No feed named {file}"));
+    let (id, ext) = file.rsplit_once('.').ok_or_else(not_found)?;
+    let account_id: i32 = id.parse().map_err(|_| not_found())?;
+    let format = FeedFormat::from_extension(ext).ok_or_else(not_found)?;
+    if !auth.can_access(account_id).map_err(|e| Custom(Status::InternalServerError, e))? {
+        return Err(Custom(Status::Forbidden, format!("No access to account {account_id}")));
+    }
+
+    let rendered = feed::build(account_id, format, min_score, digest.unwrap_or(false))
+        .await
+        .map_err(|e| Custom(Status::InternalServerError, e.to_string()))?;
+    Ok(feed::FeedResponse::new(rendered, &conditional))
+}
+
+#[openapi(tag = "Auth")]
+#[post("/auth/login", data = "<login>")]
+async fn login(login: Json<LoginRequest>) -> Result<Json<IssuedToken>, Custom<String>> {
+    match auth::login(&login.username, &login.password) {
+        Ok(Some(issued)) => Ok(Json(issued)),
+        Ok(None) => Err(Custom(Status::Unauthorized, "Wrong username or password".to_string())),
+        Err(e) => {
+            let error_msg = format!("Failed to log in: {e}");
+            error!("{error_msg}");
+            Err(Custom(Status::InternalServerError, error_msg))
+        }
+    }
+}
+
+/// Revokes the token the request was made with.
+#[openapi(tag = "Auth")]
+#[post("/auth/logout")]
+async fn logout(auth: AuthUser) -> Result<(), String> {
+    if let Some(token_id) = auth.token_id {
+        db::delete_api_token(auth.user.id, token_id)?;
+    }
+    Ok(())
+}
+
+#[openapi(tag = "Auth")]
+#[get("/auth/me")]
+async fn get_me(auth: AuthUser) -> Json<User> {
+    Json(auth.user)
+}
+
+#[openapi(tag = "Auth")]
+#[get("/auth/tokens")]
+async fn get_tokens(auth: AuthUser) -> Result<Json<Vec<ApiToken>>, String> {
+    db::get_api_tokens(auth.user.id)
+        .map(Json)
+        .map_err(|e| format!("Failed to get tokens: {e}"))
+}
+
+/// Creates a token that doesn't expire, e.g. for scripts or feed readers.
+#[openapi(tag = "Auth")]
+#[post("/auth/tokens", data = "<token>")]
+async fn create_token(auth: AuthUser, token: Json<NewApiToken>) -> Result<Json<IssuedToken>, String> {
+    if auth.token_id.is_none() {
+        return Err("Tokens need [auth] enabled and a logged in user".to_string());
+    }
+    match auth::issue_token(auth.user, token.name.trim(), auth::API_TOKEN) {
+        Ok(issued) => Ok(Json(issued)),
+        Err(e) => {
+            let error_msg = format!("Failed to create token: {e}");
+            error!("{error_msg}");
+            Err(error_msg)
+        }
+    }
+}
+
+#[openapi(tag = "Auth")]
+#[delete("/auth/tokens/<token_id>")]
+async fn delete_token(auth: AuthUser, token_id: i64) -> Result<Option<()>, String> {
+    db::delete_api_token(auth.user.id, token_id)
+        .map(|deleted| deleted.then_some(()))
+        .map_err(|e| format!("Failed to delete token: {e}"))
+}
+
+#[openapi(tag = "Auth")]
+#[get("/users")]
+async fn get_users(_admin: AdminUser) -> Result<Json<Vec<User>>, String> {
+    db::get_users()
+        .map(Json)
+        .map_err(|e| format!("Failed to get users: {e}"))
+}
+
+#[openapi(tag = "Auth")]
+#[post("/users", data = "<user>")]
+async fn create_user(_admin: AdminUser, user: Json<NewUser>) -> Result<Json<User>, String> {
+    match auth::create_user(&user.username, &user.password, user.role.unwrap_or(Role::User)) {
+        Ok(created) => Ok(Json(created)),
+        Err(e) => {
+            let error_msg = format!("Failed to create user: {e}");
+            error!("{error_msg}");
+            Err(error_msg)
+        }
+    }
+}
+
+#[openapi(tag = "Auth")]
+#[delete("/users/<user_id>")]
+async fn delete_user(_admin: AdminUser, user_id: i64) -> Result<Option<()>, String> {
+    db::delete_user(user_id)
+        .map(|deleted| deleted.then_some(()))
+        .map_err(|e| format!("Failed to delete user: {e}"))
+}
+
+/// Hands a stored account to another user; without `user_id` only admins see it.
+#[openapi(tag = "Auth")]
+#[put("/account/<account_id>/owner?<user_id>")]
+async fn set_account_owner(
+    _admin: AdminUser,
+    account_id: i32,
+    user_id: Option<i64>,
+) -> Result<Option<()>, String> {
+    db::set_account_owner(account_id, user_id)
+        .map(|updated| updated.then_some(()))
+        .map_err(|e| format!("Failed to set account owner: {e}"))
+}
+
+/// Liveness: answers as long as the server runs.
+#[get("/health")]
+fn get_health() -> Json<Liveness> {
+    Json(health::liveness())
+}
+
+/// Readiness: 503 until migrations, the database and, when `health.probe_upstream`
+/// is set, the posts domain all check out.
+#[get("/ready")]
+async fn get_ready() -> Custom<Json<Readiness>> {
+    let readiness = health::readiness().await;
+    let status = if readiness.ready { Status::Ok } else { Status::ServiceUnavailable };
+    Custom(status, Json(readiness))
+}
+
+/// Prometheus text format, mounted at the root like other exporters.
+#[get("/metrics")]
+fn get_metrics() -> (ContentType, String) {
+    let content_type = ContentType::new("text", "plain").with_params(("version", "0.0.4"));
+    (content_type, metrics::render())
 }
 
 #[get("/openapi.json")]
@@ -195,9 +886,28 @@ fn attach_cors(rocket: rocket::Rocket<rocket::Build>) -> rocket::Rocket<rocket::
     rocket
 }
 
-#[launch]
-async fn rocket() -> _ {
+#[rocket::main]
+async fn main() {
+    logging::init();
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if let Some(code) = cli::run(&args) {
+        std::process::exit(code);
+    }
+
+    if let Err(e) = rocket().launch().await {
+        error!("Rocket failed to launch: {e}");
+        std::process::exit(1);
+    }
+}
+
+fn rocket() -> rocket::Rocket<rocket::Build> {
+    health::mark_started();
     let path = default_path().unwrap();
+    // Reloads keep the previous config when the file is invalid; at startup there is none.
+    if let Err(e) = load_config(&path) {
+        error!("Not starting: {e:#}");
+        std::process::exit(1);
+    }
     let _ = reload_from(&path);
     let watcher = start_config_watcher(path).unwrap();
 
@@ -206,17 +916,50 @@ async fn rocket() -> _ {
         settings:
         process_posts,
         get_account_tag_counts,
+        get_account_tag_timeline,
         get_account_id,
         get_account_name,
         create_account,
-        get_recommendations
+        get_account_scoring,
+        set_account_scoring,
+        get_account_dislikes,
+        set_account_dislikes,
+        get_account_artists,
+        visit_artist,
+        get_account_digest,
+        refresh_account_digest,
+        get_account_webhooks,
+        create_account_webhook,
+        delete_account_webhook,
+        test_account_webhook,
+        get_webhook_deliveries,
+        get_scoring_profiles,
+        get_config_status,
+        get_tag_groups,
+        get_related_tags,
+        get_account_topics,
+        compare_accounts,
+        get_recommendations,
+        get_pool_recommendations,
+        get_pool,
+        login,
+        logout,
+        get_me,
+        get_tokens,
+        create_token,
+        delete_token,
+        get_users,
+        create_user,
+        delete_user,
+        set_account_owner
     ];
 
     let r = rocket::build()
         .manage(Mutex::new(watcher))
         .manage(spec)
-        .mount("/api", api_routes)
-        .mount("/api", routes![openapi_json])
+        .mount("/api", with_request_ids(api_routes))
+        .mount("/api", with_request_ids(routes![openapi_json, get_feed]))
+        .mount("/", routes![get_health, get_ready, get_metrics])
         .mount(
             "/api/swagger-ui",
             make_swagger_ui(&SwaggerUIConfig {
@@ -224,7 +967,11 @@ async fn rocket() -> _ {
                 ..Default::default()
             }),
         )
-        .attach(DbInit);
+        .attach(RequestIds)
+        .attach(RequestMetrics)
+        .attach(DbInit)
+        .attach(Quotas)
+        .attach(Scheduler);
 
     attach_cors(r)
 }
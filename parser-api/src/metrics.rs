@@ -0,0 +1,162 @@
+use rocket::fairing::{Fairing, Info, Kind};
+use rocket::{Data, Request, Response};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::{LazyLock, Mutex};
+use std::time::{Duration, Instant};
+
+// Upper bounds in seconds, from a fast DB query to a full account sync.
+const BUCKETS: [f64; 14] = [
+    0.001, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 120.0,
+];
+
+/// Every exported metric with its type and help text; names not listed here are not exported.
+const METRICS: [(&str, &str, &str); 12] = [
+    ("http_requests_total", "counter", "API requests by route, method and status."),
+    ("http_request_duration_seconds", "histogram", "API request latency by route."),
+    ("upstream_requests_total", "counter", "Upstream responses by status, `error` for failed connections."),
+    ("upstream_retries_total", "counter", "Upstream requests retried by send_with_retry."),
+    ("webhook_requests_total", "counter", "Webhook responses by status, `error` for failed connections."),
+    ("webhook_retries_total", "counter", "Webhook requests retried within one delivery."),
+    ("job_duration_seconds", "histogram", "Processing jobs by kind and outcome."),
+    ("db_statement_duration_seconds", "histogram", "SQLite statements by kind."),
+    ("idf_index_tags", "gauge", "Tags in the last built IDF index."),
+    ("idf_index_posts", "gauge", "Posts the last built IDF index was computed over."),
+    ("config_reloads_total", "counter", "Config reloads by result."),
+    ("process_start_time_seconds", "gauge", "Unix time the API started."),
+];
+
+struct Histogram {
+    counts: [u64; BUCKETS.len()],
+    count: u64,
+    sum: f64,
+}
+
+/// Metric name and its rendered labels, e.g. `{route="get_feed"}`.
+type SeriesKey = (&'static str, String);
+
+#[derive(Default)]
+struct Registry {
+    values: BTreeMap<SeriesKey, f64>,
+    histograms: BTreeMap<SeriesKey, Histogram>,
+}
+
+static REGISTRY: LazyLock<Mutex<Registry>> = LazyLock::new(|| {
+    let mut registry = Registry::default();
+    let started = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default();
+    registry
+        .values
+        .insert(("process_start_time_seconds", String::new()), started.as_secs_f64());
+    Mutex::new(registry)
+});
+
+fn labels(pairs: &[(&str, &str)]) -> String {
+    if pairs.is_empty() {
+        return String::new();
+    }
+    let inner: Vec<String> = pairs
+        .iter()
+        .map(|(k, v)| format!("{k}=\"{}\"", v.replace('\\', "\\\\").replace('"', "\\\"")))
+        .collect();
+    format!("{{{}}}", inner.join(","))
+}
+
+pub fn inc(name: &'static str, pairs: &[(&str, &str)]) {
+    *REGISTRY.lock().unwrap().values.entry((name, labels(pairs))).or_default() += 1.0;
+}
+
+pub fn set(name: &'static str, pairs: &[(&str, &str)], value: f64) {
+    REGISTRY.lock().unwrap().values.insert((name, labels(pairs)), value);
+}
+
+pub fn observe(name: &'static str, pairs: &[(&str, &str)], duration: Duration) {
+    let seconds = duration.as_secs_f64();
+    let mut registry = REGISTRY.lock().unwrap();
+    let histogram = registry
+        .histograms
+        .entry((name, labels(pairs)))
+        .or_insert(Histogram { counts: [0; BUCKETS.len()], count: 0, sum: 0.0 });
+    for (bound, count) in BUCKETS.iter().zip(histogram.counts.iter_mut()) {
+        if seconds <= *bound {
+            *count += 1;
+        }
+    }
+    histogram.count += 1;
+    histogram.sum += seconds;
+}
+
+/// Records how long a processing job took and whether it succeeded.
+pub fn observe_job(job: &str, started: Instant, ok: bool) {
+    let outcome = if ok { "ok" } else { "error" };
+    observe("job_duration_seconds", &[("job", job), ("outcome", outcome)], started.elapsed());
+}
+
+/// Adds `le` to already rendered labels.
+fn with_bound(labels: &str, bound: &str) -> String {
+    match labels.strip_suffix('}') {
+        Some(inner) => format!("{inner},le=\"{bound}\"}}"),
+        None => format!("{{le=\"{bound}\"}}"),
+    }
+}
+
+/// All metrics in the Prometheus text exposition format.
+pub fn render() -> String {
+    let registry = REGISTRY.lock().unwrap();
+    let mut out = String::new();
+
+    for (name, kind, help) in METRICS {
+        let _ = writeln!(out, "# HELP {name} {help}");
+        let _ = writeln!(out, "# TYPE {name} {kind}");
+
+        let first = (name, String::new());
+        let values = registry.values.range(first.clone()..).take_while(|((n, _), _)| *n == name);
+        for ((_, labels), value) in values {
+            let _ = writeln!(out, "{name}{labels} {value}");
+        }
+        let histograms = registry.histograms.range(first..).take_while(|((n, _), _)| *n == name);
+        for ((_, labels), h) in histograms {
+            for (bound, count) in BUCKETS.iter().zip(h.counts) {
+                let _ = writeln!(out, "{name}_bucket{} {count}", with_bound(labels, &bound.to_string()));
+            }
+            let _ = writeln!(out, "{name}_bucket{} {}", with_bound(labels, "+Inf"), h.count);
+            let _ = writeln!(out, "{name}_sum{labels} {}", h.sum);
+            let _ = writeln!(out, "{name}_count{labels} {}", h.count);
+        }
+    }
+    out
+}
+
+/// Counts and times every API request by the name of the route that handled it.
+pub struct RequestMetrics;
+
+#[rocket::async_trait]
+impl Fairing for RequestMetrics {
+    fn info(&self) -> Info {
+        Info {
+            name: "Request metrics",
+            kind: Kind::Request | Kind::Response,
+        }
+    }
+
+    async fn on_request(&self, req: &mut Request<'_>, _: &mut Data<'_>) {
+        req.local_cache(Instant::now);
+    }
+
+    async fn on_response<'r>(&self, req: &'r Request<'_>, res: &mut Response<'r>) {
+        let started = req.local_cache(Instant::now);
+        // Unmatched paths share one label so scanners can't add series.
+        let route = req
+            .route()
+            .and_then(|r| r.name.as_deref())
+            .unwrap_or("unmatched");
+        let status = res.status().code.to_string();
+
+        inc(
+            "http_requests_total",
+            &[("route", route), ("method", req.method().as_str()), ("status", &status)],
+        );
+        observe("http_request_duration_seconds", &[("route", route)], started.elapsed());
+    }
+}
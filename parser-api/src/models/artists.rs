@@ -0,0 +1,22 @@
+use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use crate::models::Post;
+
+#[derive(Serialize, Clone, JsonSchema)]
+pub struct ArtistSummary {
+    pub name: String,
+    pub favorites: i64,
+    #[schemars(with = "Option<String>", description = "RFC3339 timestamp")]
+    pub first_favorited: Option<DateTime<Utc>>,
+    #[schemars(with = "Option<String>", description = "RFC3339 timestamp")]
+    pub last_favorited: Option<DateTime<Utc>>,
+    #[schemars(with = "Option<String>", description = "RFC3339 timestamp")]
+    pub last_visit: Option<DateTime<Utc>>,
+    /// Recent posts uploaded after the last visit, or after the last favorite if the
+    /// artist was never visited.
+    pub new_since_visit: usize,
+    /// Newest upstream posts by the artist the account has not favorited.
+    pub recent: Vec<Post>,
+}
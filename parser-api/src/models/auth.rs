@@ -0,0 +1,101 @@
+use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct AuthSettings {
+    /// Without it every request acts as an admin, as before users existed.
+    pub enabled: bool,
+    /// Web logins expire after this many days.
+    pub session_days: f32,
+}
+
+impl Default for AuthSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            session_days: 30.0,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    Admin,
+    User,
+}
+
+impl Display for Role {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Role::Admin => write!(f, "admin"),
+            Role::User => write!(f, "user"),
+        }
+    }
+}
+
+impl FromStr for Role {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "admin" => Ok(Role::Admin),
+            "user" => Ok(Role::User),
+            other => Err(format!("unknown role '{other}'")),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Clone, JsonSchema)]
+pub struct User {
+    pub id: i64,
+    pub username: String,
+    pub role: Role,
+    #[schemars(with = "String", description = "RFC3339 timestamp")]
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Deserialize, JsonSchema)]
+pub struct NewUser {
+    pub username: String,
+    pub password: String,
+    #[serde(default)]
+    pub role: Option<Role>,
+}
+
+#[derive(Deserialize, JsonSchema)]
+pub struct LoginRequest {
+    pub username: String,
+    pub password: String,
+}
+
+/// A freshly issued token; the raw value is only ever shown here.
+#[derive(Serialize, JsonSchema)]
+pub struct IssuedToken {
+    pub token: String,
+    pub user: User,
+    pub info: ApiToken,
+}
+
+#[derive(Serialize, Clone, JsonSchema)]
+pub struct ApiToken {
+    pub id: i64,
+    pub name: String,
+    /// `session` for web logins, `api` for tokens made on purpose.
+    pub kind: String,
+    #[schemars(with = "String", description = "RFC3339 timestamp")]
+    pub created_at: DateTime<Utc>,
+    #[schemars(with = "Option<String>", description = "RFC3339 timestamp")]
+    pub expires_at: Option<DateTime<Utc>>,
+    #[schemars(with = "Option<String>", description = "RFC3339 timestamp")]
+    pub last_used_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Deserialize, JsonSchema)]
+pub struct NewApiToken {
+    pub name: String,
+}
@@ -0,0 +1,25 @@
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use crate::models::{TagCount, TruncatedAccount};
+
+#[derive(Debug, Serialize, Clone, JsonSchema)]
+pub struct SharedTag {
+    pub name: String,
+    pub group_type: String,
+    pub count_a: i64,
+    pub count_b: i64,
+    pub contribution: f32,
+}
+
+#[derive(Debug, Serialize, Clone, JsonSchema)]
+pub struct AccountComparison {
+    pub account_a: TruncatedAccount,
+    pub account_b: TruncatedAccount,
+    pub similarity: f32,
+    pub shared_tags: Vec<SharedTag>,
+    pub unique_a: Vec<TagCount>,
+    pub unique_b: Vec<TagCount>,
+    pub shared_post_count: usize,
+    pub shared_post_ids: Vec<i64>,
+}
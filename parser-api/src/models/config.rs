@@ -1,15 +1,21 @@
 use anyhow::Context;
 use arc_swap::ArcSwap;
+use chrono::{DateTime, Utc};
 use notify::{Event, RecommendedWatcher, RecursiveMode, Watcher};
-use rocket::serde::Deserialize;
+use rocket::serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use sha2::{Digest, Sha256};
 use std::path::{Path, PathBuf};
+use std::str::FromStr;
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::{Arc, LazyLock};
+use std::sync::{Arc, LazyLock, Mutex};
 use std::thread::JoinHandle;
 use std::time::{Duration, SystemTime};
 use std::{fs, thread};
 use std::collections::HashMap;
-use crate::utils::{Priors};
+use crate::models::{AccountScoring, AuthSettings, FeedSettings, HealthSettings, LogSettings, PoolSettings, QuotaSettings, ScheduleSettings, ScoringProfile, TAG_GROUPS, WebhookSettings, default_tag_groups};
+use crate::{db, logging, metrics};
+use crate::utils::{Diversity, NegativeSignals, Priors, ScoringModel, TopicSettings};
 
 #[derive(Debug, Clone, Deserialize)]
 pub struct Config {
@@ -20,10 +26,193 @@ pub struct Config {
     pub posts_limit: i32,
     pub rps_delay_ms: u64,
     pub max_retries: u64,
+    /// Tag groups that are stored and scored; see `db::sync_tag_groups` for changes.
+    #[serde(default = "default_tag_groups")]
+    pub tag_groups: Vec<String>,
     pub group_weights: HashMap<String, f32>,
     pub priors: Priors,
     pub df_floor: f32,
-    pub idf_max: f32
+    pub idf_max: f32,
+    pub fav_decay_tau_days: Option<f32>,
+    pub collab_candidates: Option<usize>,
+    #[serde(default)]
+    pub diversity: Diversity,
+    #[serde(default)]
+    pub negative: NegativeSignals,
+    #[serde(default)]
+    pub scoring_model: ScoringModel,
+    #[serde(default)]
+    pub profiles: HashMap<String, ScoringProfile>,
+    #[serde(default)]
+    pub pools: PoolSettings,
+    #[serde(default)]
+    pub topics: TopicSettings,
+    #[serde(default)]
+    pub feed: FeedSettings,
+    #[serde(default)]
+    pub schedule: ScheduleSettings,
+    #[serde(default)]
+    pub webhooks: WebhookSettings,
+    #[serde(default)]
+    pub auth: AuthSettings,
+    #[serde(default)]
+    pub quotas: QuotaSettings,
+    #[serde(default)]
+    pub health: HealthSettings,
+    #[serde(default)]
+    pub log: LogSettings,
+}
+
+/// Everything wrong with a config, collected so one failed reload reports all of it.
+#[derive(Default)]
+pub struct Problems(Vec<String>);
+
+impl Problems {
+    pub fn check(&mut self, ok: bool, problem: impl FnOnce() -> String) {
+        if !ok {
+            self.0.push(problem());
+        }
+    }
+
+    /// `value` is finite and in `min..=max`.
+    pub fn range(&mut self, field: &str, value: f32, min: f32, max: f32) {
+        self.check(value.is_finite() && (min..=max).contains(&value), || {
+            format!("{field} must be between {min} and {max}, got {value}")
+        });
+    }
+
+    pub fn at_least(&mut self, field: &str, value: f32, min: f32) {
+        self.check(value.is_finite() && value >= min, || {
+            format!("{field} must be at least {min}, got {value}")
+        });
+    }
+
+    pub fn positive(&mut self, field: &str, value: f32) {
+        self.check(value.is_finite() && value > 0.0, || {
+            format!("{field} must be positive, got {value}")
+        });
+    }
+
+    fn weights<'a>(&mut self, field: &str, weights: impl Iterator<Item = (&'a String, &'a f32)>) {
+        for (group, weight) in weights {
+            self.check(TAG_GROUPS.contains(&group.to_lowercase().as_str()), || {
+                format!("{field} has unknown tag group '{group}'")
+            });
+            self.at_least(&format!("{field}.{group}"), *weight, 0.0);
+        }
+    }
+}
+
+impl Config {
+    /// Checks for values that parse but would break requests or scoring.
+    pub fn validate(&self) -> anyhow::Result<()> {
+        let mut p = Problems::default();
+
+        p.check(!self.admin_user.trim().is_empty(), || "admin_user must not be empty".into());
+        let url = reqwest::Url::parse(&self.posts_domain);
+        p.check(
+            url.is_ok_and(|u| matches!(u.scheme(), "http" | "https") && u.has_host()),
+            || format!("posts_domain must be an http(s) URL, got '{}'", self.posts_domain),
+        );
+        // e621 answers at most 320 posts per page.
+        p.check((1..=320).contains(&self.posts_limit), || {
+            format!("posts_limit must be between 1 and 320, got {}", self.posts_limit)
+        });
+
+        p.check(!self.tag_groups.is_empty(), || "tag_groups must not be empty".into());
+        for group in &self.tag_groups {
+            p.check(TAG_GROUPS.contains(&group.as_str()), || {
+                format!("tag_groups has unknown group '{group}', expected one of {}", TAG_GROUPS.join(", "))
+            });
+        }
+        p.weights("group_weights", self.group_weights.iter());
+        self.priors.validate("priors", &mut p);
+        p.at_least("df_floor", self.df_floor, 0.0);
+        p.positive("idf_max", self.idf_max);
+        if let Some(tau) = self.fav_decay_tau_days {
+            p.positive("fav_decay_tau_days", tau);
+        }
+
+        for (name, profile) in &self.profiles {
+            self.check_layer(&format!("profiles.{name}"), profile, &mut p);
+        }
+
+        p.range("diversity.strength", self.diversity.strength, 0.0, 1.0);
+        for (field, cap) in [
+            ("diversity.max_per_artist", self.diversity.max_per_artist),
+            ("diversity.max_per_copyright", self.diversity.max_per_copyright),
+            ("topics.quota", self.topics.quota),
+        ] {
+            p.check(cap != Some(0), || format!("{field} must be at least 1 or left out"));
+        }
+        p.range("negative.penalty", self.negative.penalty, 0.0, 1.0);
+        p.at_least("negative.blacklist_weight", self.negative.blacklist_weight, 0.0);
+        p.at_least("negative.dislike_weight", self.negative.dislike_weight, 0.0);
+        p.at_least("negative.common_weight", self.negative.common_weight, 0.0);
+        p.range("negative.common_min_share", self.negative.common_min_share, 0.0, 1.0);
+        p.check(self.topics.k > 0, || "topics.k must be at least 1".into());
+        p.check(self.topics.max_iter > 0, || "topics.max_iter must be at least 1".into());
+
+        p.at_least("pools.cache_hours", self.pools.cache_hours, 0.0);
+        p.check(self.pools.sample_size > 0, || "pools.sample_size must be at least 1".into());
+        p.check(self.feed.limit > 0, || "feed.limit must be at least 1".into());
+        p.at_least("feed.cache_minutes", self.feed.cache_minutes, 0.0);
+        p.positive("schedule.interval_minutes", self.schedule.interval_minutes);
+        p.check(self.schedule.digest_size > 0, || "schedule.digest_size must be at least 1".into());
+        p.check(self.webhooks.attempts > 0, || "webhooks.attempts must be at least 1".into());
+        p.positive("webhooks.timeout_seconds", self.webhooks.timeout_seconds);
+        p.positive("webhooks.keep_days", self.webhooks.keep_days);
+        p.positive("auth.session_days", self.auth.session_days);
+        for pattern in self.quotas.limited.iter().chain(&self.quotas.jobs) {
+            let path = pattern.trim().rsplit(' ').next().unwrap_or_default();
+            p.check(path.starts_with('/'), || {
+                format!("quotas pattern '{pattern}' must be a path, optionally after a method")
+            });
+        }
+        p.at_least("health.probe_cache_seconds", self.health.probe_cache_seconds, 0.0);
+        p.check(log::LevelFilter::from_str(&self.log.level).is_ok(), || {
+            format!("log.level must be error, warn, info, debug or trace, got '{}'", self.log.level)
+        });
+
+        if !p.0.is_empty() {
+            anyhow::bail!("{}", p.0.join("; "));
+        }
+        Ok(())
+    }
+
+    /// Checks `layer` applied over these settings, naming fields under `prefix`.
+    fn check_layer(&self, prefix: &str, layer: &ScoringProfile, p: &mut Problems) {
+        p.weights(&format!("{prefix}.group_weights"), layer.group_weights.iter());
+        let mut layered = self.clone();
+        layer.apply(&mut layered);
+        layered.priors.validate(&format!("{prefix}.priors"), p);
+        p.at_least(&format!("{prefix}.df_floor"), layered.df_floor, 0.0);
+        p.positive(&format!("{prefix}.idf_max"), layered.idf_max);
+    }
+
+    /// Checks an account's profile and overrides the way `validate` checks `[profiles]`.
+    pub fn validate_scoring(&self, scoring: &AccountScoring) -> Result<(), String> {
+        let profile = self.scoring_for(scoring.profile.as_deref(), None)?;
+        let mut p = Problems::default();
+        profile.check_layer("overrides", &scoring.overrides, &mut p);
+        if !p.0.is_empty() {
+            return Err(p.0.join("; "));
+        }
+        Ok(())
+    }
+}
+
+/// The config in use and how the last reload went.
+#[derive(Serialize, Clone, JsonSchema)]
+pub struct ConfigStatus {
+    pub path: String,
+    /// SHA-256 of the file contents the active config was loaded from.
+    pub hash: String,
+    pub loaded_at: DateTime<Utc>,
+    /// Last time the file was read again, whether or not that worked.
+    pub last_reload_at: Option<DateTime<Utc>>,
+    /// Why the last reload was rejected; cleared by the next one that succeeds.
+    pub last_error: Option<String>,
 }
 
 pub struct ConfigWatcher {
@@ -39,9 +228,16 @@ impl Drop for ConfigWatcher {
     }
 }
 
-pub fn load_config(p: &Path) -> anyhow::Result<Config> {
+/// A valid config from `p`, with the hash of the file it came from.
+fn read_config(p: &Path) -> anyhow::Result<(Config, String)> {
     let s = fs::read_to_string(p).with_context(|| format!("reading {}", p.display()))?;
-    toml::from_str(&s).context("parsing config.toml")
+    let config: Config = toml::from_str(&s).context("parsing config.toml")?;
+    config.validate().context("invalid config.toml")?;
+    Ok((config, hex::encode(Sha256::digest(s.as_bytes()))))
+}
+
+pub fn load_config(p: &Path) -> anyhow::Result<Config> {
+    read_config(p).map(|(config, _)| config)
 }
 
 pub fn default_path() -> anyhow::Result<PathBuf> {
@@ -80,22 +276,30 @@ pub fn start_config_watcher(path: PathBuf) -> anyhow::Result<ConfigWatcher> {
                     {
                         thread::sleep(Duration::from_millis(120));
 
-                        if let Ok(mtime) = file_mtime(&path) {
-                            if last_mtime.is_none_or(|old| old < mtime) {
-                                match reload_from(&path) {
-                                    Ok(_) => {
-                                        last_mtime = Some(mtime);
-                                        eprintln!("[config] reloaded {}", path.display());
-                                    }
-                                    Err(e) => {
-                                        eprintln!("[config] reload failed: {e:#}");
+                        if let Ok(mtime) = file_mtime(&path)
+                            && last_mtime.is_none_or(|old| old < mtime)
+                        {
+                            // Reading the file raises events too; an invalid file is only
+                            // tried again once it changes.
+                            last_mtime = Some(mtime);
+                            match reload_from(&path) {
+                                Ok(_) => {
+                                    metrics::inc("config_reloads_total", &[("result", "success")]);
+                                    info!("Reloaded config from {}", path.display());
+                                    // Drops stored tags of groups that were just removed.
+                                    if let Err(e) = db::sync_tag_groups(&cfg().tag_groups) {
+                                        error!("Failed to apply tag_groups: {e}");
                                     }
                                 }
+                                Err(e) => {
+                                    metrics::inc("config_reloads_total", &[("result", "failure")]);
+                                    error!("Config reload rejected, keeping the previous config: {e:#}");
+                                }
                             }
                         }
                     }
                 }
-                Ok(Err(e)) => eprintln!("[config] watch error: {e}"),
+                Ok(Err(e)) => warn!("Config watch error: {e}"),
                 Err(std::sync::mpsc::RecvTimeoutError::Timeout) => continue,
                 Err(std::sync::mpsc::RecvTimeoutError::Disconnected) => break,
             }
@@ -114,18 +318,55 @@ pub fn file_mtime(p: &Path) -> std::io::Result<SystemTime> {
 
 pub static CONFIG: LazyLock<ArcSwap<Config>> = LazyLock::new(|| {
     let p = default_path().expect("config path");
-    let cfg = load_config(&p).expect("initial config");
+    let (cfg, hash) = read_config(&p).expect("initial config");
+    logging::configure(&cfg);
+    *STATUS.lock().unwrap() = Some(ConfigStatus {
+        path: p.display().to_string(),
+        hash,
+        loaded_at: Utc::now(),
+        last_reload_at: None,
+        last_error: None,
+    });
     ArcSwap::from_pointee(cfg)
 });
 
+/// Set together with `CONFIG`, which must be loaded before this is read.
+static STATUS: Mutex<Option<ConfigStatus>> = Mutex::new(None);
+
 pub fn cfg() -> Arc<Config> {
     CONFIG.load_full()
 }
 
+pub fn config_status() -> ConfigStatus {
+    LazyLock::force(&CONFIG);
+    STATUS.lock().unwrap().clone().expect("status is set with the config")
+}
+
+/// Swaps in the config from `p` if it is valid; otherwise the current one stays active
+/// and the error is kept for `config_status`.
 pub fn reload_from(p: &Path) -> anyhow::Result<()> {
-    let new = load_config(p)?;
+    // The startup config sets STATUS, so it has to be loaded before the lock is taken.
+    LazyLock::force(&CONFIG);
+    let loaded = read_config(p);
+    let mut status = STATUS.lock().unwrap();
+    let status = status.as_mut().expect("status is set with the config");
+    status.last_reload_at = Some(Utc::now());
+
+    let (new, hash) = match loaded {
+        Ok(loaded) => loaded,
+        Err(e) => {
+            status.last_error = Some(format!("{e:#}"));
+            return Err(e);
+        }
+    };
+    status.path = p.display().to_string();
+    status.hash = hash;
+    status.loaded_at = Utc::now();
+    status.last_error = None;
+
     let arc = Arc::new(new);
-    CONFIG.store(arc.clone());
-    eprintln!("[config] current value:\n{arc:#?}");
+    logging::configure(&arc);
+    debug!("Config now: {arc:?}");
+    CONFIG.store(arc);
     Ok(())
 }
@@ -0,0 +1,42 @@
+use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::models::ScoredPost;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct ScheduleSettings {
+    pub enabled: bool,
+    /// Accounts not synced for this long are synced again.
+    pub interval_minutes: f32,
+    /// Most posts kept in one digest.
+    pub digest_size: usize,
+    pub digest_min_score: f32,
+    /// Older digests of an account are deleted.
+    pub keep_digests: usize,
+}
+
+impl Default for ScheduleSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval_minutes: 360.0,
+            digest_size: 20,
+            digest_min_score: 0.0,
+            keep_digests: 10,
+        }
+    }
+}
+
+/// Best new recommendations found by one scheduled sync.
+#[derive(Serialize, Deserialize, Clone, JsonSchema)]
+pub struct Digest {
+    pub account_id: i32,
+    #[schemars(with = "String", description = "RFC3339 timestamp")]
+    pub created_at: DateTime<Utc>,
+    /// Only posts uploaded after this (the previous digest) are included.
+    #[schemars(with = "Option<String>", description = "RFC3339 timestamp")]
+    pub since: Option<DateTime<Utc>>,
+    pub posts: Vec<ScoredPost>,
+}
@@ -0,0 +1,44 @@
+use serde::Deserialize;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct FeedSettings {
+    /// Posts scoring below this are left out of Atom/RSS feeds.
+    pub min_score: f32,
+    pub limit: usize,
+    /// A generated feed is served again until it is this old.
+    pub cache_minutes: f32,
+}
+
+impl Default for FeedSettings {
+    fn default() -> Self {
+        Self {
+            min_score: 0.0,
+            limit: 50,
+            cache_minutes: 15.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FeedFormat {
+    Atom,
+    Rss,
+}
+
+impl FeedFormat {
+    pub fn from_extension(ext: &str) -> Option<Self> {
+        match ext {
+            "atom" => Some(FeedFormat::Atom),
+            "rss" | "xml" => Some(FeedFormat::Rss),
+            _ => None,
+        }
+    }
+
+    pub fn content_type(self) -> (&'static str, &'static str) {
+        match self {
+            FeedFormat::Atom => ("application", "atom+xml"),
+            FeedFormat::Rss => ("application", "rss+xml"),
+        }
+    }
+}
@@ -0,0 +1,48 @@
+use rocket::serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct HealthSettings {
+    /// Also require the posts domain to answer for `/ready`.
+    pub probe_upstream: bool,
+    /// A probe result is reused for this long, so frequent checks don't hit the upstream.
+    pub probe_cache_seconds: f32,
+}
+
+impl Default for HealthSettings {
+    fn default() -> Self {
+        Self {
+            probe_upstream: false,
+            probe_cache_seconds: 60.0,
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct Liveness {
+    pub status: &'static str,
+    pub version: &'static str,
+    pub uptime_seconds: u64,
+}
+
+#[derive(Serialize, Clone)]
+pub struct Check {
+    pub ok: bool,
+    pub detail: String,
+}
+
+impl Check {
+    pub fn from_result(result: Result<String, String>) -> Self {
+        match result {
+            Ok(detail) => Check { ok: true, detail },
+            Err(detail) => Check { ok: false, detail },
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct Readiness {
+    pub ready: bool,
+    pub checks: BTreeMap<&'static str, Check>,
+}
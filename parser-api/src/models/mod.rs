@@ -1,9 +1,35 @@
+mod artists;
+mod auth;
+mod compare;
+mod digest;
+mod feed;
+mod health;
+mod logging;
+mod pools;
 mod posts;
+mod quota;
+mod scoring;
 mod tags_info;
+mod timeline;
+mod topics;
 mod users;
+mod webhooks;
 mod config;
 
+pub use artists::*;
+pub use auth::*;
+pub use compare::*;
+pub use digest::*;
+pub use feed::*;
+pub use health::*;
+pub use logging::*;
+pub use pools::*;
 pub use posts::*;
+pub use quota::*;
+pub use scoring::*;
 pub use tags_info::*;
+pub use timeline::*;
+pub use topics::*;
 pub use users::*;
+pub use webhooks::*;
 pub use config::*;
@@ -0,0 +1,59 @@
+use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::models::ScoredPost;
+
+/// Pool metadata as returned by `/pools.json`, cached in the `pools` table.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct Pool {
+    pub id: i64,
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    /// `series` for ordered pools, `collection` for loose ones.
+    pub category: String,
+    pub is_active: bool,
+    /// Member posts in reading order.
+    pub post_ids: Vec<i64>,
+    #[schemars(with = "String", description = "RFC3339 timestamp")]
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct PoolSettings {
+    /// Cached pool metadata older than this is fetched again.
+    pub cache_hours: f32,
+    /// Member posts scored per pool, spread evenly over the pool.
+    pub sample_size: usize,
+}
+
+impl Default for PoolSettings {
+    fn default() -> Self {
+        Self {
+            cache_hours: 24.0,
+            sample_size: 30,
+        }
+    }
+}
+
+#[derive(Serialize, Clone, JsonSchema)]
+pub struct PoolRecommendation {
+    pub pool: Pool,
+    /// Mean score of the scored member posts.
+    pub score: f32,
+    pub best: f32,
+    pub scored_posts: usize,
+    /// Best scoring member, shown as the pool's cover.
+    pub cover: Option<ScoredPost>,
+}
+
+#[derive(Serialize, Clone, JsonSchema)]
+pub struct PoolPage {
+    pub pool: Pool,
+    pub page: usize,
+    pub pages: usize,
+    /// Posts of this page in pool order; blacklisted members are left out.
+    pub posts: Vec<ScoredPost>,
+}
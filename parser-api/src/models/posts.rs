@@ -1,4 +1,5 @@
 use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
 
 use chrono::{DateTime, Utc};
 use schemars::JsonSchema;
@@ -38,6 +39,43 @@ pub struct Post {
     pub duration: Option<f64>,
 }
 
+impl Post {
+    pub fn stored(
+        id: i64,
+        created_at: DateTime<Utc>,
+        score_total: i64,
+        fav_count: i64,
+        rating: Rating,
+        tags: Tags,
+    ) -> Self {
+        Self {
+            id,
+            created_at,
+            updated_at: created_at,
+            file: None,
+            preview: None,
+            sample: None,
+            score: Score { total: score_total, ..Default::default() },
+            tags,
+            locked_tags: None,
+            change_seq: 0.0,
+            flags: Flags::default(),
+            rating,
+            fav_count,
+            sources: Vec::new(),
+            pools: Vec::new(),
+            relationships: Relationships::default(),
+            approver_id: None,
+            uploader_id: 0,
+            description: None,
+            comment_count: 0,
+            is_favorited: false,
+            has_notes: false,
+            duration: None,
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, JsonSchema, Clone)]
 pub struct FileInfo {
     pub width: i64,
@@ -96,14 +134,35 @@ pub struct Samples {
     pub p720: PostSampleAlternate,
 }
 
-#[derive(Serialize, Deserialize, JsonSchema, Clone)]
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Default)]
 pub struct Score {
     pub up: i64,
     pub down: i64,
     pub total: i64,
 }
 
-#[derive(Serialize, Deserialize, JsonSchema, Clone)]
+/// Every tag group e621 reports, in the order they are stored and scored.
+pub const TAG_GROUPS: [&str; 9] = [
+    "artist",
+    "character",
+    "contributor",
+    "copyright",
+    "general",
+    "invalid",
+    "lore",
+    "meta",
+    "species",
+];
+
+/// Groups stored and scored when `tag_groups` is not set in the config.
+pub fn default_tag_groups() -> Vec<String> {
+    ["artist", "character", "copyright", "general", "lore", "species"]
+        .into_iter()
+        .map(String::from)
+        .collect()
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Default)]
 pub struct Tags {
     pub general: Vec<String>,
     pub artist: Vec<String>,
@@ -116,7 +175,59 @@ pub struct Tags {
     pub contributor: Vec<String>,
 }
 
-#[derive(Serialize, Deserialize, JsonSchema, Clone)]
+impl Tags {
+    pub fn group(&self, group_type: &str) -> Option<&Vec<String>> {
+        match group_type {
+            "general" => Some(&self.general),
+            "artist" => Some(&self.artist),
+            "copyright" => Some(&self.copyright),
+            "character" => Some(&self.character),
+            "species" => Some(&self.species),
+            "invalid" => Some(&self.invalid),
+            "meta" => Some(&self.meta),
+            "lore" => Some(&self.lore),
+            "contributor" => Some(&self.contributor),
+            _ => None,
+        }
+    }
+
+    pub fn group_mut(&mut self, group_type: &str) -> Option<&mut Vec<String>> {
+        match group_type {
+            "general" => Some(&mut self.general),
+            "artist" => Some(&mut self.artist),
+            "copyright" => Some(&mut self.copyright),
+            "character" => Some(&mut self.character),
+            "species" => Some(&mut self.species),
+            "invalid" => Some(&mut self.invalid),
+            "meta" => Some(&mut self.meta),
+            "lore" => Some(&mut self.lore),
+            "contributor" => Some(&mut self.contributor),
+            _ => None,
+        }
+    }
+
+    pub fn push(&mut self, group_type: &str, name: String) {
+        if let Some(tags) = self.group_mut(group_type) {
+            tags.push(name);
+        }
+    }
+
+    /// Non-empty tags of the given groups as `(group, tag)` pairs; unknown groups are skipped.
+    pub fn in_groups<'a>(
+        &'a self,
+        groups: &'a [String],
+    ) -> impl Iterator<Item = (&'a str, &'a String)> + 'a {
+        groups.iter().flat_map(move |g| {
+            self.group(g)
+                .into_iter()
+                .flatten()
+                .filter(|t| !t.is_empty())
+                .map(move |t| (g.as_str(), t))
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Default)]
 pub struct Flags {
     pub pending: bool,
     pub flagged: bool,
@@ -126,7 +237,7 @@ pub struct Flags {
     pub deleted: bool,
 }
 
-#[derive(Serialize, Deserialize, JsonSchema, Clone)]
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Copy, PartialEq, Eq)]
 #[serde(rename_all = "lowercase")]
 pub enum Rating {
     S,
@@ -144,7 +255,20 @@ impl Display for Rating {
     }
 }
 
-#[derive(Serialize, Deserialize, JsonSchema, Clone)]
+impl FromStr for Rating {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "s" => Ok(Rating::S),
+            "q" => Ok(Rating::Q),
+            "e" => Ok(Rating::E),
+            other => Err(format!("unknown rating '{other}'")),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Default)]
 pub struct Relationships {
     pub parent_id: Option<i64>,
     pub has_children: bool,
@@ -156,4 +280,7 @@ pub struct Relationships {
 pub struct ScoredPost {
     pub post: Post,
     pub score: f32,
+    /// Variants collapsed into this post: same parent, pool or file.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub related: Vec<ScoredPost>,
 }
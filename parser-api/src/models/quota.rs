@@ -0,0 +1,38 @@
+use rocket::serde::Deserialize;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct QuotaSettings {
+    pub enabled: bool,
+    /// Requests one client may make to `limited` endpoints per minute, with bursts up to it.
+    pub requests_per_minute: u32,
+    /// Processing jobs one client may have running at the same time.
+    pub concurrent_jobs: usize,
+    /// Endpoints counted against `requests_per_minute`, as `"METHOD /path"`; `*` matches
+    /// one path segment and longer paths match their prefix.
+    pub limited: Vec<String>,
+    /// Endpoints that also count against `concurrent_jobs` while they run.
+    pub jobs: Vec<String>,
+    /// Take the client IP from `X-Real-IP` instead of the connection. Only safe behind a
+    /// proxy that sets the header; otherwise clients pick their own bucket.
+    pub trusted_proxy: bool,
+}
+
+impl Default for QuotaSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            requests_per_minute: 30,
+            concurrent_jobs: 1,
+            limited: vec![
+                "POST /api/process/*".into(),
+                "POST /api/account/*/digest".into(),
+                "GET /api/recommendations/*".into(),
+                "GET /api/pool/*".into(),
+                "GET /api/account/*/artists".into(),
+            ],
+            jobs: vec!["POST /api/process/*".into(), "POST /api/account/*/digest".into()],
+            trusted_proxy: false,
+        }
+    }
+}
@@ -0,0 +1,126 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::models::Config;
+use crate::utils::{Priors, ScoringModel};
+
+/// Prior values a profile replaces; `None` keeps whatever the layer below set.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct PriorsOverride {
+    pub recency_tau_days: Option<f32>,
+    pub quality_a: Option<f32>,
+    pub quality_b: Option<f32>,
+    pub mix_sim: Option<f32>,
+    pub mix_quality: Option<f32>,
+    pub mix_recency: Option<f32>,
+    pub mix_collab: Option<f32>,
+    pub idf_lambda: Option<f32>,
+    pub idf_alpha: Option<f32>,
+    pub freq_alpha: Option<f32>,
+}
+
+impl PriorsOverride {
+    fn apply(&self, p: &mut Priors) {
+        let set = |dst: &mut f32, v: Option<f32>| {
+            if let Some(v) = v {
+                *dst = v;
+            }
+        };
+        set(&mut p.recency_tau_days, self.recency_tau_days);
+        set(&mut p.quality_a, self.quality_a);
+        set(&mut p.quality_b, self.quality_b);
+        set(&mut p.mix_sim, self.mix_sim);
+        set(&mut p.mix_quality, self.mix_quality);
+        set(&mut p.mix_recency, self.mix_recency);
+        set(&mut p.mix_collab, self.mix_collab);
+        set(&mut p.freq_alpha, self.freq_alpha);
+        if self.idf_lambda.is_some() {
+            p.idf_lambda = self.idf_lambda;
+        }
+        if self.idf_alpha.is_some() {
+            p.idf_alpha = self.idf_alpha;
+        }
+    }
+}
+
+/// Partial scoring settings layered over the global config: named in `[profiles.<name>]`
+/// or stored per account.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct ScoringProfile {
+    pub model: Option<ScoringModel>,
+    pub group_weights: HashMap<String, f32>,
+    pub priors: PriorsOverride,
+    pub df_floor: Option<f32>,
+    pub idf_max: Option<f32>,
+}
+
+impl ScoringProfile {
+    pub fn apply(&self, cfg: &mut Config) {
+        if let Some(model) = self.model {
+            cfg.scoring_model = model;
+        }
+        cfg.group_weights
+            .extend(self.group_weights.iter().map(|(k, v)| (k.to_lowercase(), *v)));
+        self.priors.apply(&mut cfg.priors);
+        if let Some(v) = self.df_floor {
+            cfg.df_floor = v;
+        }
+        if let Some(v) = self.idf_max {
+            cfg.idf_max = v;
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct AccountScoring {
+    /// Name of a profile from `[profiles]`, `None` for the global settings.
+    pub profile: Option<String>,
+    /// Account-specific values applied on top of the selected profile.
+    pub overrides: ScoringProfile,
+}
+
+impl Config {
+    /// Global settings with the named profile and then the account overrides applied.
+    pub fn scoring_for(
+        &self,
+        profile: Option<&str>,
+        overrides: Option<&ScoringProfile>,
+    ) -> Result<Config, String> {
+        let mut cfg = self.clone();
+        if let Some(name) = profile.filter(|n| !n.is_empty()) {
+            self.profiles
+                .get(name)
+                .ok_or_else(|| format!("Unknown scoring profile '{name}'"))?
+                .apply(&mut cfg);
+        }
+        if let Some(overrides) = overrides {
+            overrides.apply(&mut cfg);
+        }
+        Ok(cfg)
+    }
+
+    /// Settings for scoring an account: `requested` or else the account's own profile,
+    /// then its overrides. Only a requested profile must exist; a stored one that was
+    /// since removed from the config falls back to the global settings.
+    pub fn account_scoring_for(
+        &self,
+        requested: Option<&str>,
+        scoring: &AccountScoring,
+    ) -> Result<Config, String> {
+        if requested.is_none()
+            && let Some(name) = scoring.profile.as_deref().filter(|n| !n.is_empty())
+            && !self.profiles.contains_key(name)
+        {
+            warn!("Scoring profile '{name}' is no longer configured, using the global settings");
+            return self.scoring_for(None, Some(&scoring.overrides));
+        }
+        self.scoring_for(
+            requested.or(scoring.profile.as_deref()),
+            Some(&scoring.overrides),
+        )
+    }
+}
@@ -6,4 +6,27 @@ pub struct TagCount {
     pub name: String,
     pub group_type: String,
     pub count: i64,
+    pub weight: f64,
+}
+
+#[derive(Debug, Serialize, Clone, JsonSchema)]
+pub struct RelatedTag {
+    pub name: String,
+    pub group_type: String,
+    /// Posts in scope carrying both tags.
+    pub together: i64,
+    /// Posts in scope carrying this tag.
+    pub count: i64,
+    pub lift: f64,
+    pub pmi: f64,
+    pub jaccard: f64,
+}
+
+#[derive(Debug, Serialize, Clone, JsonSchema)]
+pub struct RelatedTags {
+    pub tag: String,
+    /// Posts considered: the account's favorites or every stored post.
+    pub scope_posts: i64,
+    pub tag_posts: i64,
+    pub related: Vec<RelatedTag>,
 }
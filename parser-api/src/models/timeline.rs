@@ -0,0 +1,68 @@
+use schemars::JsonSchema;
+use serde::Serialize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimelinePeriod {
+    Month,
+    Quarter,
+}
+
+impl TimelinePeriod {
+    pub fn parse(value: Option<&str>) -> Result<Self, String> {
+        match value.map(|v| v.trim().to_lowercase()).as_deref() {
+            None | Some("") | Some("month") => Ok(TimelinePeriod::Month),
+            Some("quarter") => Ok(TimelinePeriod::Quarter),
+            Some(other) => Err(format!("Unknown period '{other}', expected 'month' or 'quarter'")),
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            TimelinePeriod::Month => "month",
+            TimelinePeriod::Quarter => "quarter",
+        }
+    }
+
+    /// SQL expression turning `ap.favorited_at` into a sortable bucket key.
+    pub fn sql_key(self) -> &'static str {
+        match self {
+            TimelinePeriod::Month => "substr(ap.favorited_at, 1, 7)",
+            TimelinePeriod::Quarter => {
+                "substr(ap.favorited_at, 1, 4) || '-Q' || ((CAST(substr(ap.favorited_at, 6, 2) AS INTEGER) + 2) / 3)"
+            }
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Clone, JsonSchema)]
+pub struct PeriodTagCount {
+    pub name: String,
+    pub group_type: String,
+    pub count: i64,
+    pub share: f64,
+}
+
+#[derive(Debug, Serialize, Clone, JsonSchema)]
+pub struct TimelineBucket {
+    pub period: String,
+    pub favorites: i64,
+    pub tags: Vec<PeriodTagCount>,
+}
+
+#[derive(Debug, Serialize, Clone, JsonSchema)]
+pub struct TagTrend {
+    pub name: String,
+    pub group_type: String,
+    pub earlier_count: i64,
+    pub recent_count: i64,
+    pub earlier_share: f64,
+    pub recent_share: f64,
+}
+
+#[derive(Debug, Serialize, Clone, JsonSchema)]
+pub struct TagTimeline {
+    pub period: String,
+    pub buckets: Vec<TimelineBucket>,
+    pub new_interests: Vec<TagTrend>,
+    pub abandoned_interests: Vec<TagTrend>,
+}
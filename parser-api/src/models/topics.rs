@@ -0,0 +1,22 @@
+use schemars::JsonSchema;
+use serde::Serialize;
+
+#[derive(Debug, Serialize, Clone, JsonSchema)]
+pub struct TopicTag {
+    pub name: String,
+    pub group_type: String,
+    pub weight: f32,
+}
+
+#[derive(Debug, Serialize, Clone, JsonSchema)]
+pub struct Topic {
+    pub id: usize,
+    /// Top tags of the topic joined with " / ".
+    pub label: String,
+    pub top_tags: Vec<TopicTag>,
+    /// Favorited posts in the topic.
+    pub size: usize,
+    pub share: f32,
+    /// Posts closest to the topic's center, most typical first.
+    pub example_post_ids: Vec<i64>,
+}
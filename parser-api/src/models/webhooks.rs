@@ -0,0 +1,108 @@
+use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::models::Rating;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct WebhookSettings {
+    /// HTTP attempts per delivery, each logged; 429, 5xx and connection errors are retried.
+    pub attempts: u32,
+    pub timeout_seconds: f32,
+    /// Scheduler runs a post that could not be delivered is queued for before it is dropped.
+    pub retry_runs: u32,
+    /// The delivery log is trimmed to this many days; older posts are not sent.
+    pub keep_days: f32,
+    /// Hosts webhooks may reach although they resolve to a loopback, private or
+    /// link-local address, such as a chat server on the local network.
+    pub allowed_hosts: Vec<String>,
+}
+
+impl Default for WebhookSettings {
+    fn default() -> Self {
+        Self {
+            attempts: 3,
+            timeout_seconds: 10.0,
+            retry_runs: 5,
+            keep_days: 30.0,
+            allowed_hosts: Vec::new(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, JsonSchema)]
+pub struct NewWebhook {
+    pub url: String,
+    /// Posts scoring lower are not sent.
+    #[serde(default)]
+    pub min_score: f32,
+    /// Ratings to send; empty for all of them.
+    #[serde(default)]
+    pub ratings: Vec<Rating>,
+    /// Signs each body with HMAC-SHA256 in `X-Webhook-Signature` when set.
+    #[serde(default)]
+    pub secret: Option<String>,
+}
+
+#[derive(Serialize, Clone, JsonSchema)]
+pub struct Webhook {
+    pub id: i64,
+    pub account_id: i32,
+    pub url: String,
+    pub min_score: f32,
+    pub ratings: Vec<Rating>,
+    /// Never sent back, only whether one is set.
+    #[serde(skip)]
+    pub secret: Option<String>,
+    pub has_secret: bool,
+    #[schemars(with = "String", description = "RFC3339 timestamp")]
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Serialize, Clone, JsonSchema)]
+pub struct WebhookDelivery {
+    pub id: i64,
+    pub webhook_id: i64,
+    /// `post.matched` or `test`.
+    pub event: String,
+    pub post_id: Option<i64>,
+    /// 1 for the first request of a delivery, counting up for its retries.
+    pub attempt: u32,
+    /// Missing when no response arrived.
+    pub status: Option<u16>,
+    pub error: Option<String>,
+    #[schemars(with = "String", description = "RFC3339 timestamp")]
+    pub delivered_at: DateTime<Utc>,
+}
+
+impl WebhookDelivery {
+    pub fn succeeded(&self) -> bool {
+        self.status.is_some_and(|s| (200..300).contains(&s))
+    }
+}
+
+/// Body POSTed to a webhook. `text` and `content` carry the same line so Slack- and
+/// Discord-style incoming hooks show something without a relay.
+#[derive(Serialize)]
+pub struct WebhookPayload {
+    pub event: String,
+    pub account_id: i32,
+    pub webhook_id: i64,
+    pub text: String,
+    pub content: String,
+    pub post: Option<WebhookPost>,
+}
+
+#[derive(Serialize)]
+pub struct WebhookPost {
+    pub id: i64,
+    pub url: String,
+    pub score: f32,
+    pub rating: Rating,
+    pub created_at: DateTime<Utc>,
+    pub preview_url: Option<String>,
+    pub file_url: Option<String>,
+    pub artists: Vec<String>,
+    pub tags: Vec<String>,
+}
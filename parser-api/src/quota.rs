@@ -0,0 +1,186 @@
+use rocket::fairing::{Fairing, Info, Kind};
+use rocket::http::uri::Origin;
+use rocket::http::{ContentType, Header, Method, Status};
+use rocket::{Data, Request, Response};
+use std::collections::HashMap;
+use std::io::Cursor;
+use std::sync::{LazyLock, Mutex};
+use std::time::Instant;
+
+use crate::{auth, db};
+use crate::models::{QuotaSettings, cfg};
+
+/// Suggested wait when a client already has `concurrent_jobs` running; jobs take
+/// seconds to minutes, so there is no exact time to give.
+const JOB_RETRY_SECS: u64 = 10;
+/// Above this many tracked clients, idle ones are dropped.
+const MAX_CLIENTS: usize = 10_000;
+/// Rejected requests are routed here, where nothing is mounted, and answered in
+/// `on_response`; fairings can't answer a request themselves.
+const REJECTED_PATH: &str = "/__quota_exceeded";
+
+/// Token bucket holding up to `requests_per_minute` requests.
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+static BUCKETS: LazyLock<Mutex<HashMap<String, Bucket>>> = LazyLock::new(Default::default);
+static JOBS: LazyLock<Mutex<HashMap<String, usize>>> = LazyLock::new(Default::default);
+
+/// What the fairing decided about a request, kept in its local cache until the response.
+enum Admission {
+    Free,
+    Job(String),
+    Rejected { retry_after: u64, reason: String },
+}
+
+/// `"METHOD /path/*"` against a request; the method is optional.
+fn matches(pattern: &str, method: Method, path: &str) -> bool {
+    let (pattern_method, pattern_path) = match pattern.trim().split_once(' ') {
+        Some((m, p)) => (Some(m), p.trim()),
+        None => (None, pattern.trim()),
+    };
+    if pattern_method.is_some_and(|m| !m.eq_ignore_ascii_case(method.as_str())) {
+        return false;
+    }
+    let mut segments = path.split('/').filter(|s| !s.is_empty());
+    pattern_path
+        .split('/')
+        .filter(|s| !s.is_empty())
+        .all(|p| segments.next().is_some_and(|s| p == "*" || p == s))
+}
+
+/// Requests are counted per user when auth is on, so all of a user's tokens share one
+/// bucket, otherwise per IP. Invalid tokens fall back to the IP; the route guards turn
+/// them away before any work is done.
+fn client_key(req: &Request<'_>) -> String {
+    if cfg().auth.enabled
+        && let Some(token) = auth::bearer_token(req)
+    {
+        match db::get_token_user(&auth::token_hash(token)) {
+            Ok(Some((user, _))) => return format!("user:{}", user.id),
+            Ok(None) => {}
+            Err(e) => error!("Failed to check API token: {e}"),
+        }
+    }
+    let ip = if cfg().quotas.trusted_proxy { req.client_ip() } else { req.remote().map(|r| r.ip()) };
+    match ip {
+        Some(ip) => format!("ip:{ip}"),
+        None => "ip:unknown".to_string(),
+    }
+}
+
+/// Takes one request from the client's bucket, or the seconds until one is available.
+fn take_request(key: &str, per_minute: u32) -> Result<(), u64> {
+    let capacity = per_minute as f64;
+    let per_second = capacity / 60.0;
+    let now = Instant::now();
+
+    let mut buckets = BUCKETS.lock().unwrap();
+    if buckets.len() > MAX_CLIENTS {
+        buckets.retain(|_, b| now.duration_since(b.updated).as_secs_f64() * per_second < capacity);
+    }
+    let bucket = buckets.entry(key.to_string()).or_insert(Bucket { tokens: capacity, updated: now });
+    bucket.tokens =
+        (bucket.tokens + now.duration_since(bucket.updated).as_secs_f64() * per_second).min(capacity);
+    bucket.updated = now;
+
+    if bucket.tokens >= 1.0 {
+        bucket.tokens -= 1.0;
+        Ok(())
+    } else {
+        Err(((1.0 - bucket.tokens) / per_second).ceil().max(1.0) as u64)
+    }
+}
+
+fn start_job(key: &str, limit: usize) -> bool {
+    let mut jobs = JOBS.lock().unwrap();
+    let running = jobs.entry(key.to_string()).or_default();
+    if *running >= limit {
+        return false;
+    }
+    *running += 1;
+    true
+}
+
+fn finish_job(key: &str) {
+    let mut jobs = JOBS.lock().unwrap();
+    if let Some(running) = jobs.get_mut(key) {
+        *running = running.saturating_sub(1);
+        if *running == 0 {
+            jobs.remove(key);
+        }
+    }
+}
+
+fn admit(req: &Request<'_>, settings: &QuotaSettings) -> Admission {
+    let path = req.uri().path().as_str();
+    let method = req.method();
+    let limited = settings.limited.iter().any(|p| matches(p, method, path));
+    let job = settings.jobs.iter().any(|p| matches(p, method, path));
+    if !limited && !job {
+        return Admission::Free;
+    }
+
+    let key = client_key(req);
+    if limited
+        && settings.requests_per_minute > 0
+        && let Err(retry_after) = take_request(&key, settings.requests_per_minute)
+    {
+        return Admission::Rejected {
+            retry_after,
+            reason: format!("Rate limit of {} requests per minute reached", settings.requests_per_minute),
+        };
+    }
+    if job && settings.concurrent_jobs > 0 {
+        if !start_job(&key, settings.concurrent_jobs) {
+            return Admission::Rejected {
+                retry_after: JOB_RETRY_SECS,
+                reason: format!("Only {} processing job(s) may run at once", settings.concurrent_jobs),
+            };
+        }
+        return Admission::Job(key);
+    }
+    Admission::Free
+}
+
+/// Enforces `[quotas]`, read again for every request so config reloads apply at once.
+pub struct Quotas;
+
+#[rocket::async_trait]
+impl Fairing for Quotas {
+    fn info(&self) -> Info {
+        Info {
+            name: "Client quotas",
+            kind: Kind::Request | Kind::Response,
+        }
+    }
+
+    async fn on_request(&self, req: &mut Request<'_>, _: &mut Data<'_>) {
+        let settings = &cfg().quotas;
+        if !settings.enabled {
+            return;
+        }
+        let admission = admit(req, settings);
+        if let Admission::Rejected { reason, .. } = &admission {
+            info!("Rejected {} {}: {reason}", req.method(), req.uri());
+            req.set_method(Method::Get);
+            req.set_uri(Origin::parse(REJECTED_PATH).unwrap());
+        }
+        req.local_cache(|| admission);
+    }
+
+    async fn on_response<'r>(&self, req: &'r Request<'_>, res: &mut Response<'r>) {
+        match req.local_cache(|| Admission::Free) {
+            Admission::Free => {}
+            Admission::Job(key) => finish_job(key),
+            Admission::Rejected { retry_after, reason } => {
+                res.set_status(Status::TooManyRequests);
+                res.set_header(ContentType::Plain);
+                res.set_header(Header::new("Retry-After", retry_after.to_string()));
+                res.set_sized_body(reason.len(), Cursor::new(reason.clone()));
+            }
+        }
+    }
+}
@@ -0,0 +1,262 @@
+use chrono::Utc;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, LazyLock, Mutex};
+
+use crate::models::{Config, Post, ScoredPost, TagCount, TruncatedAccount, cfg};
+use crate::utils::{
+    self, CoFavoriteIndex, IdfIndex, NegativeProfile, Priors, Scorer, ScoringModel, TopicModel,
+    TopicSettings,
+};
+use crate::{api, db, metrics};
+
+/// Co-favorite index over the favorites of every stored account, built on first use and
+/// dropped whenever a sync finishes.
+static COFAVORITES: LazyLock<Mutex<Option<Arc<CoFavoriteIndex>>>> = LazyLock::new(Default::default);
+
+/// A fitted topic model with a fingerprint of the settings it was fitted under.
+type CachedTopics = (String, Arc<TopicModel>);
+
+/// Fitted topic models per account; cleared together with the co-favorite index.
+static TOPICS: LazyLock<Mutex<HashMap<i32, CachedTopics>>> = LazyLock::new(Default::default);
+/// Bumped on every invalidation, so a model fitted from data a sync has since replaced
+/// is not cached.
+static FAVORITES_EPOCH: AtomicU64 = AtomicU64::new(0);
+
+/// The cached co-favorite index, built from the database if there is none.
+pub fn cofavorites() -> Result<Arc<CoFavoriteIndex>, std::io::Error> {
+    // Held while building, so concurrent requests wait for one index instead of each
+    // reading every favorite.
+    let mut cached = COFAVORITES.lock().unwrap();
+    if let Some(index) = cached.as_ref() {
+        return Ok(index.clone());
+    }
+    let index = CoFavoriteIndex::from_db(db::get_all_favorites)
+        .map_err(|e| std::io::Error::other(format!("Failed to build co-favorite index: {e}")))?;
+    Ok(cached.insert(Arc::new(index)).clone())
+}
+
+/// Drops everything derived from stored favorites; called when a sync has changed them.
+pub fn invalidate_favorites() {
+    FAVORITES_EPOCH.fetch_add(1, Ordering::SeqCst);
+    *COFAVORITES.lock().unwrap() = None;
+    TOPICS.lock().unwrap().clear();
+}
+
+/// Everything an account's scorer borrows, resolved once per request.
+pub struct ScoringContext {
+    pub cfg: Config,
+    pub model: ScoringModel,
+    pub priors: Priors,
+    pub tags: Vec<TagCount>,
+    pub account: TruncatedAccount,
+    pub idf: IdfIndex,
+    pub negative: NegativeProfile,
+}
+
+impl ScoringContext {
+    pub fn load(
+        account_id: i32,
+        profile: Option<&str>,
+        model: Option<&str>,
+    ) -> Result<Self, std::io::Error> {
+        let scoring = db::get_account_scoring(account_id)
+            .map_err(|e| std::io::Error::other(format!("Failed to get account scoring: {e}")))?;
+        let cfg = cfg()
+            .account_scoring_for(profile, &scoring)
+            .map_err(std::io::Error::other)?;
+        let model = match model {
+            Some(m) => m.parse::<ScoringModel>().map_err(std::io::Error::other)?,
+            None => cfg.scoring_model,
+        };
+
+        let mut priors = cfg.priors.clone();
+        priors.now = Utc::now();
+
+        let tags: Vec<TagCount> = db::get_tag_counts(account_id)
+            .map_err(|e| std::io::Error::other(format!("Failed to get tag counts: {e}")))?;
+
+        let account = db::get_account_by_id(account_id)
+            .map_err(|e| std::io::Error::other(format!("Failed to get account: {e}")))?;
+
+        let idf = IdfIndex::from_db(
+            db::get_tags_df,
+            db::post_count,
+            priors.now,
+            cfg.df_floor,
+            cfg.idf_max,
+        )
+        .map_err(|e| std::io::Error::other(format!("Failed to build IDF index: {e}")))?;
+        debug!(
+            "IDF index: {} tags over {} posts (computed at {})",
+            idf.as_map().len(),
+            idf.n_posts,
+            idf.computed_at
+        );
+        metrics::set("idf_index_tags", &[], idf.as_map().len() as f64);
+        metrics::set("idf_index_posts", &[], idf.n_posts as f64);
+
+        let dislikes = db::get_account_dislikes(account_id)
+            .map_err(|e| std::io::Error::other(format!("Failed to get dislikes: {e}")))?;
+        let negative = NegativeProfile::build(
+            &cfg.negative,
+            &cfg.tag_groups,
+            &account.blacklist,
+            &dislikes,
+            &tags,
+            &idf,
+        );
+
+        Ok(Self { cfg, model, priors, tags, account, idf, negative })
+    }
+
+    pub fn post_vector(&self, post: &Post) -> HashMap<String, f32> {
+        utils::post_vector(post, &self.cfg.tag_groups, &self.cfg.group_weights, &self.priors, &self.idf)
+    }
+
+    /// Topics of the account's favorites, split with `settings`. Fitting runs k-means over
+    /// every favorite, so the model is cached until the next sync or a settings change.
+    pub fn topics(&self, account_id: i32, settings: &TopicSettings) -> Result<Arc<TopicModel>, std::io::Error> {
+        // Everything `post_vector` and the fit depend on besides the stored posts.
+        let weights: BTreeMap<_, _> = self.cfg.group_weights.iter().collect();
+        let fingerprint = format!(
+            "{settings:?} {:?} {weights:?} {:?} {:?} {} {}",
+            self.cfg.tag_groups,
+            self.priors.idf_lambda,
+            self.priors.idf_alpha,
+            self.cfg.df_floor,
+            self.cfg.idf_max
+        );
+        if let Some((cached, model)) = TOPICS.lock().unwrap().get(&account_id)
+            && *cached == fingerprint
+        {
+            return Ok(model.clone());
+        }
+
+        let epoch = FAVORITES_EPOCH.load(Ordering::SeqCst);
+        let ids: Vec<i64> = db::get_account_favorites(account_id)
+            .map_err(|e| std::io::Error::other(format!("Failed to get favorites: {e}")))?
+            .into_iter()
+            .map(|(id, _)| id)
+            .collect();
+        let vectors: Vec<(i64, HashMap<String, f32>)> = db::get_stored_posts(&ids)
+            .map_err(|e| std::io::Error::other(format!("Failed to get favorites: {e}")))?
+            .iter()
+            .map(|p| (p.id, self.post_vector(p)))
+            .collect();
+        let model = Arc::new(TopicModel::fit(&vectors, settings));
+
+        let mut cache = TOPICS.lock().unwrap();
+        if FAVORITES_EPOCH.load(Ordering::SeqCst) == epoch {
+            cache.insert(account_id, (fingerprint, model.clone()));
+        }
+        Ok(model)
+    }
+
+    pub fn scorer(&self) -> Box<dyn Scorer + '_> {
+        utils::build_scorer(self.model, &self.tags, &self.cfg, &self.priors, &self.idf, &self.negative)
+    }
+}
+
+pub struct RecommendOptions {
+    pub page: Option<i32>,
+    pub affinity_threshold: Option<f32>,
+    pub profile: Option<String>,
+    pub model: Option<String>,
+    /// Collapse related posts into one entry.
+    pub group: bool,
+    pub topic_quota: Option<usize>,
+}
+
+impl Default for RecommendOptions {
+    fn default() -> Self {
+        Self {
+            page: None,
+            affinity_threshold: None,
+            profile: None,
+            model: None,
+            group: true,
+            topic_quota: None,
+        }
+    }
+}
+
+/// One page of upstream posts and co-favorite candidates scored for the account, sorted
+/// best first, filtered, grouped and diversified.
+pub async fn recommend(
+    account_id: i32,
+    opts: &RecommendOptions,
+) -> Result<Vec<ScoredPost>, std::io::Error> {
+    let (ctx, scored) = score(account_id, opts).await?;
+    rerank(&ctx, account_id, scored, opts)
+}
+
+/// One page of upstream posts and co-favorite candidates scored for the account, sorted
+/// best first and filtered by `affinity_threshold`, with every post still in the list.
+pub async fn score(
+    account_id: i32,
+    opts: &RecommendOptions,
+) -> Result<(ScoringContext, Vec<ScoredPost>), std::io::Error> {
+    let ctx = ScoringContext::load(account_id, opts.profile.as_deref(), opts.model.as_deref())?;
+    let cfg = &ctx.cfg;
+    let mut posts: Vec<Post> = api::get_posts(&ctx.account, opts.page).await;
+
+    let collab = cofavorites()?.scores_for(account_id);
+
+    if let Some(per_page) = cfg.collab_candidates.filter(|n| *n > 0) {
+        let page_no = opts.page.unwrap_or(1).max(1) as usize;
+        let seen: HashSet<i64> = posts.iter().map(|p| p.id).collect();
+        let ids: Vec<i64> = CoFavoriteIndex::top_candidates(&collab, page_no, per_page)
+            .into_iter()
+            .filter(|id| !seen.contains(id))
+            .collect();
+        posts.extend(api::get_posts_by_ids(&ctx.account, &ids).await);
+    }
+
+    let scorer = ctx.scorer();
+
+    let mut scored: Vec<ScoredPost> = Vec::with_capacity(posts.len());
+    for post in posts {
+        let s = scorer.score(&post, *collab.get(&post.id).unwrap_or(&0.0));
+        scored.push(ScoredPost { post, score: s, related: Vec::new() });
+    }
+    drop(scorer);
+
+    if let Some(threshold) = opts.affinity_threshold {
+        scored.retain(|sp| sp.score >= threshold);
+    }
+
+    // Best first: grouping keeps each group at its first post, and feeds and digests
+    // truncate the result.
+    scored.sort_by(|a, b| b.score.total_cmp(&a.score));
+    Ok((ctx, scored))
+}
+
+/// Groups related posts and applies the topic quota and diversity caps to `scored`, which
+/// come from `score`.
+pub fn rerank(
+    ctx: &ScoringContext,
+    account_id: i32,
+    mut scored: Vec<ScoredPost>,
+    opts: &RecommendOptions,
+) -> Result<Vec<ScoredPost>, std::io::Error> {
+    let cfg = &ctx.cfg;
+    if opts.group {
+        scored = utils::group_related(scored);
+    }
+
+    let mut vectors: Vec<_> = scored.iter().map(|sp| ctx.post_vector(&sp.post)).collect();
+
+    if let Some(quota) = opts.topic_quota.or(cfg.topics.quota).filter(|q| *q > 0) {
+        let topics = ctx.topics(account_id, &cfg.topics)?;
+        let keep = utils::topic_quota(&scored, &vectors, &topics, quota);
+        (scored, vectors) = scored
+            .into_iter()
+            .zip(vectors)
+            .zip(keep)
+            .filter(|(_, keep)| *keep)
+            .map(|(pair, _)| pair)
+            .unzip();
+    }
+    Ok(utils::diversify(scored, &vectors, &cfg.diversity))
+}
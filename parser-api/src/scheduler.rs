@@ -0,0 +1,146 @@
+use chrono::{DateTime, Utc};
+use rocket::fairing::{Fairing, Info, Kind};
+use rocket::{Orbit, Rocket};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use crate::models::{Digest, cfg};
+use crate::recommend::{self, RecommendOptions};
+use crate::{db, logging, metrics, sync, webhooks};
+
+/// How often the scheduler looks for accounts that are due. `[schedule]` is read again
+/// on every tick, so config reloads apply without a restart.
+const TICK: Duration = Duration::from_secs(60);
+
+pub struct Scheduler;
+
+#[rocket::async_trait]
+impl Fairing for Scheduler {
+    fn info(&self) -> Info {
+        Info {
+            name: "Background re-sync",
+            kind: Kind::Liftoff,
+        }
+    }
+
+    async fn on_liftoff(&self, _: &Rocket<Orbit>) {
+        tokio::spawn(run());
+    }
+}
+
+async fn run() {
+    // Accounts whose last scheduled sync failed: failures in a row and when the last one
+    // happened. A failed sync leaves `synced_at` alone, so without this they would be
+    // retried on every tick.
+    let mut failures: HashMap<i32, (u32, DateTime<Utc>)> = HashMap::new();
+    loop {
+        tokio::time::sleep(TICK).await;
+
+        let settings = cfg().schedule.clone();
+        if !settings.enabled {
+            continue;
+        }
+
+        let accounts = match db::get_sync_times() {
+            Ok(accounts) => accounts,
+            Err(e) => {
+                warn!("Scheduler could not list accounts: {e}");
+                continue;
+            }
+        };
+        let interval =
+            chrono::Duration::milliseconds((settings.interval_minutes * 60_000.0) as i64);
+
+        // Deleted accounts drop out of the list; forget their failures too.
+        failures.retain(|id, _| accounts.iter().any(|(account_id, _)| account_id == id));
+
+        for (account_id, synced_at) in accounts {
+            if synced_at.is_some_and(|t| Utc::now() - t < interval) {
+                continue;
+            }
+            if let Some(&(count, at)) = failures.get(&account_id)
+                && Utc::now() - at < retry_delay(count, interval)
+            {
+                continue;
+            }
+            // The upstream client panics on failed requests; a panic only ends this task.
+            let error = match tokio::spawn(logging::scoped(
+                format!("sync-{account_id}"),
+                refresh(account_id),
+            ))
+            .await
+            {
+                Ok(Ok(digest)) => {
+                    info!(
+                        "Synced account {account_id}, digest has {} new post(s)",
+                        digest.posts.len()
+                    );
+                    failures.remove(&account_id);
+                    continue;
+                }
+                Ok(Err(e)) => format!("failed: {e}"),
+                Err(e) => format!("panicked: {e}"),
+            };
+            let count = failures.get(&account_id).map_or(1, |&(count, _)| count + 1);
+            failures.insert(account_id, (count, Utc::now()));
+            warn!(
+                "Scheduled sync of account {account_id} {error}; retrying in {} minute(s)",
+                retry_delay(count, interval).num_minutes()
+            );
+        }
+    }
+}
+
+/// Wait before retrying an account after `failures` failed syncs in a row: one tick,
+/// doubling each time, up to the regular interval.
+fn retry_delay(failures: u32, interval: chrono::Duration) -> chrono::Duration {
+    let tick = chrono::Duration::from_std(TICK).unwrap_or_default();
+    (tick * 2i32.saturating_pow(failures.saturating_sub(1).min(16))).min(interval)
+}
+
+/// Syncs the account's favorites, fires webhooks for new posts and stores a digest of
+/// what is new since the last one.
+pub async fn refresh(account_id: i32) -> Result<Digest, String> {
+    sync::sync_account(account_id).await?;
+    let started = Instant::now();
+    let result = build_digest(account_id).await;
+    metrics::observe_job("digest", started, result.is_ok());
+    result
+}
+
+async fn build_digest(account_id: i32) -> Result<Digest, String> {
+    let settings = cfg().schedule.clone();
+    let since = db::get_digests(account_id, 1)?
+        .into_iter()
+        .next()
+        .map(|d| d.created_at);
+
+    let opts = RecommendOptions::default();
+    let (ctx, mut scored) = recommend::score(account_id, &opts)
+        .await
+        .map_err(|e| e.to_string())?;
+    scored.retain(|sp| since.is_none_or(|s| sp.post.created_at > s));
+
+    // Webhooks apply their own thresholds, so they see every new post, including ones
+    // grouping or the diversity caps keep out of the digest.
+    match webhooks::dispatch(account_id, &scored) {
+        Ok(0) => {}
+        Ok(queued) => info!("Queued {queued} webhook delivery(s) for account {account_id}"),
+        Err(e) => warn!("Webhooks of account {account_id} failed: {e}"),
+    }
+
+    let mut posts =
+        recommend::rerank(&ctx, account_id, scored, &opts).map_err(|e| e.to_string())?;
+
+    posts.retain(|sp| sp.score >= settings.digest_min_score);
+    posts.truncate(settings.digest_size);
+
+    let digest = Digest {
+        account_id,
+        created_at: Utc::now(),
+        since,
+        posts,
+    };
+    db::save_digest(&digest, settings.keep_digests)?;
+    Ok(digest)
+}
@@ -0,0 +1,92 @@
+use std::collections::HashSet;
+use std::sync::{LazyLock, Mutex};
+use std::time::Instant;
+
+use crate::models::{Post, TAG_GROUPS, UserApiResponse, cfg};
+use crate::{api, db, metrics, recommend};
+
+/// Accounts with a sync in progress; a second sync would drop the posts the first is saving.
+static SYNCING: LazyLock<Mutex<HashSet<i32>>> = LazyLock::new(|| Mutex::new(HashSet::new()));
+
+struct SyncGuard(i32);
+
+impl SyncGuard {
+    fn acquire(account_id: i32) -> Option<Self> {
+        SYNCING
+            .lock()
+            .unwrap()
+            .insert(account_id)
+            .then_some(SyncGuard(account_id))
+    }
+}
+
+impl Drop for SyncGuard {
+    fn drop(&mut self) {
+        SYNCING.lock().unwrap().remove(&self.0);
+    }
+}
+
+/// Fetches all favorites of the account again and recomputes its tag counts.
+pub async fn sync_account(account_id: i32) -> Result<(), String> {
+    let started = Instant::now();
+    let result = sync_favorites(account_id).await;
+    // Even a failed sync may have dropped or saved some favorites.
+    recommend::invalidate_favorites();
+    metrics::observe_job("sync", started, result.is_ok());
+    result
+}
+
+async fn sync_favorites(account_id: i32) -> Result<(), String> {
+    let _guard = SyncGuard::acquire(account_id)
+        .ok_or_else(|| format!("Account {account_id} is already being synced"))?;
+
+    let cfg = cfg();
+    let blacklist: HashSet<String> = cfg
+        .tag_blacklist
+        .iter()
+        .map(|s| s.to_lowercase())
+        .collect();
+    let account = db::get_account_by_id(account_id).map_err(|e| e.to_string())?;
+    let user = api::get_account(&account).await;
+    let favcount = match user {
+        UserApiResponse::FullCurrentUser(u) => u.favorite_count,
+        UserApiResponse::FullUser(u) => u.favorite_count,
+    };
+    let pages = (favcount / cfg.posts_limit) + (if favcount % cfg.posts_limit > 0 { 1 } else { 0 });
+
+    db::drop_account_posts(account_id).map_err(|e| format!("Failed to drop account posts: {e}"))?;
+
+    for i in 1..=pages {
+        let first_position = ((i - 1) * cfg.posts_limit) as i64;
+        let raw_posts = api::get_favorites(&account, i).await;
+        let posts: Vec<Post> = raw_posts
+            .into_iter()
+            .map(|p| strip_blacklisted_tags(p, &blacklist))
+            .collect();
+        info!("{} post(s) found on page {}", posts.len(), i);
+
+        db::save_posts(&posts, account.id, first_position)
+            .map_err(|e| format!("Failed to save posts: {e}"))?;
+
+        db::save_posts_tags_batch(&posts, &blacklist, &cfg.tag_groups)
+            .map_err(|e| format!("Failed to save tags for page {i}: {e}"))?;
+    }
+
+    db::set_favorite_times(account_id)
+        .map_err(|e| format!("Failed to estimate favorite times: {e}"))?;
+    db::set_tag_counts(account_id, cfg.fav_decay_tau_days)
+        .map_err(|e| format!("Failed to set account tag counts: {e}"))?;
+    db::set_synced_at(account_id)
+}
+
+fn strip_blacklisted_tags(mut p: Post, blacklist: &HashSet<String>) -> Post {
+    let filter = |v: &mut Vec<String>| {
+        v.retain(|t| !blacklist.contains(&t.to_lowercase().trim().to_string()));
+    };
+    for group in TAG_GROUPS {
+        if let Some(tags) = p.tags.group_mut(group) {
+            filter(tags);
+        }
+    }
+    p
+}
@@ -0,0 +1,304 @@
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+use crate::evaluation::EvaluationData;
+use crate::models::Config;
+use crate::utils::{Priors, RankingMetrics, SplitMix64};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Strategy {
+    Random,
+    Grid,
+}
+
+impl Strategy {
+    pub fn parse(value: &str) -> Result<Self, String> {
+        match value {
+            "random" => Ok(Strategy::Random),
+            "grid" => Ok(Strategy::Grid),
+            other => Err(format!("unknown strategy '{other}', expected 'random' or 'grid'")),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Metric {
+    Precision,
+    Recall,
+    Ndcg,
+    Mrr,
+}
+
+impl Metric {
+    pub fn parse(value: &str) -> Result<Self, String> {
+        match value {
+            "precision" => Ok(Metric::Precision),
+            "recall" => Ok(Metric::Recall),
+            "ndcg" => Ok(Metric::Ndcg),
+            "mrr" => Ok(Metric::Mrr),
+            other => Err(format!(
+                "unknown metric '{other}', expected precision, recall, ndcg or mrr"
+            )),
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Metric::Precision => "precision",
+            Metric::Recall => "recall",
+            Metric::Ndcg => "ndcg",
+            Metric::Mrr => "mrr",
+        }
+    }
+
+    fn value(self, m: &RankingMetrics) -> f32 {
+        match self {
+            Metric::Precision => m.precision,
+            Metric::Recall => m.recall,
+            Metric::Ndcg => m.ndcg,
+            Metric::Mrr => m.mrr,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TuningOptions {
+    pub strategy: Strategy,
+    pub trials: usize,
+    pub metric: Metric,
+    pub k: usize,
+    pub seed: u64,
+}
+
+#[derive(Debug, Clone)]
+pub struct TuningResult {
+    pub config: Config,
+    /// Metric of the base and the chosen config on the validation split they were
+    /// compared on; `best` is biased upwards by the choice itself.
+    pub baseline: f32,
+    pub best: f32,
+    /// The same two on the test split, which played no part in the choice.
+    pub test_baseline: f32,
+    pub test_best: f32,
+    pub evaluated: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Param {
+    MixSim,
+    MixQuality,
+    MixRecency,
+    MixCollab,
+    FreqAlpha,
+    IdfLambda,
+    IdfAlpha,
+    DfFloor,
+    IdfMax,
+    NegativePenalty,
+    GroupWeight(String),
+}
+
+impl Param {
+    fn range(&self) -> (f32, f32) {
+        match self {
+            Param::MixSim | Param::MixQuality | Param::MixRecency | Param::MixCollab => (0.0, 1.0),
+            Param::FreqAlpha => (0.0, 1.5),
+            Param::IdfLambda | Param::IdfAlpha => (0.0, 1.0),
+            Param::DfFloor => (0.0, 5.0),
+            Param::IdfMax => (1.0, 20.0),
+            Param::NegativePenalty => (0.0, 1.0),
+            Param::GroupWeight(_) => (0.0, 3.0),
+        }
+    }
+
+    fn grid(&self) -> Vec<f32> {
+        let (lo, hi) = self.range();
+        (0..=4).map(|i| lo + (hi - lo) * i as f32 / 4.0).collect()
+    }
+
+    fn set(&self, cfg: &mut Config, value: f32) {
+        let p = &mut cfg.priors;
+        match self {
+            Param::MixSim => p.mix_sim = value,
+            Param::MixQuality => p.mix_quality = value,
+            Param::MixRecency => p.mix_recency = value,
+            Param::MixCollab => p.mix_collab = value,
+            Param::FreqAlpha => p.freq_alpha = value,
+            Param::IdfLambda => p.idf_lambda = Some(value),
+            Param::IdfAlpha => p.idf_alpha = Some(value),
+            Param::DfFloor => cfg.df_floor = value,
+            Param::IdfMax => cfg.idf_max = value,
+            Param::NegativePenalty => cfg.negative.penalty = value,
+            Param::GroupWeight(g) => {
+                cfg.group_weights.insert(g.clone(), value);
+            }
+        }
+    }
+}
+
+fn search_space(base: &Config) -> Vec<Param> {
+    let mut groups: Vec<String> = base.tag_groups.clone();
+    groups.extend(base.group_weights.keys().cloned());
+    groups.sort();
+    groups.dedup();
+
+    let mut params = vec![
+        Param::MixSim,
+        Param::MixQuality,
+        Param::MixRecency,
+        Param::MixCollab,
+        Param::FreqAlpha,
+        Param::IdfLambda,
+        Param::IdfAlpha,
+        Param::DfFloor,
+        Param::IdfMax,
+        Param::NegativePenalty,
+    ];
+    params.extend(groups.into_iter().map(Param::GroupWeight));
+    params
+}
+
+fn objective(datasets: &[EvaluationData], cfg: &Config, opts: &TuningOptions) -> f32 {
+    if datasets.is_empty() {
+        return 0.0;
+    }
+    let total: f32 = datasets
+        .iter()
+        .map(|d| opts.metric.value(&d.evaluate(cfg, opts.k).metrics))
+        .sum();
+    total / datasets.len() as f32
+}
+
+/// Searches for the config scoring best on `validation`, then scores the base and the
+/// winner on `test`.
+pub fn tune(
+    validation: &[EvaluationData],
+    test: &[EvaluationData],
+    base: &Config,
+    opts: &TuningOptions,
+) -> TuningResult {
+    let params = search_space(base);
+    let mut rng = SplitMix64::new(opts.seed);
+
+    let baseline = objective(validation, base, opts);
+    let mut best_cfg = base.clone();
+    let mut best = baseline;
+    let mut evaluated = 1;
+
+    match opts.strategy {
+        Strategy::Random => {
+            for trial in 0..opts.trials {
+                let mut candidate = base.clone();
+                for param in &params {
+                    let (lo, hi) = param.range();
+                    param.set(&mut candidate, lo + (hi - lo) * rng.next_f32());
+                }
+                let score = objective(validation, &candidate, opts);
+                evaluated += 1;
+                if score > best {
+                    info!("trial {}: {} improved to {score:.4}", trial + 1, opts.metric.name());
+                    best = score;
+                    best_cfg = candidate;
+                }
+            }
+        }
+        Strategy::Grid => {
+            for round in 0..opts.trials.max(1) {
+                let mut improved = false;
+                for param in &params {
+                    for value in param.grid() {
+                        let mut candidate = best_cfg.clone();
+                        param.set(&mut candidate, value);
+                        let score = objective(validation, &candidate, opts);
+                        evaluated += 1;
+                        if score > best {
+                            info!(
+                                "round {}: {param:?} = {value} improved {} to {score:.4}",
+                                round + 1,
+                                opts.metric.name()
+                            );
+                            best = score;
+                            best_cfg = candidate;
+                            improved = true;
+                        }
+                    }
+                }
+                if !improved {
+                    break;
+                }
+            }
+        }
+    }
+
+    TuningResult {
+        test_baseline: objective(test, base, opts),
+        test_best: objective(test, &best_cfg, opts),
+        config: best_cfg,
+        baseline,
+        best,
+        evaluated,
+    }
+}
+
+#[derive(Serialize)]
+struct TunedSnippet<'a> {
+    df_floor: f32,
+    idf_max: f32,
+    group_weights: BTreeMap<&'a str, f32>,
+    negative: NegativeSnippet,
+    priors: Priors,
+}
+
+#[derive(Serialize)]
+struct NegativeSnippet {
+    penalty: f32,
+}
+
+#[inline]
+fn round3(v: f32) -> f32 {
+    (v * 1000.0).round() / 1000.0
+}
+
+pub fn to_toml_snippet(result: &TuningResult, accounts: &[i32], opts: &TuningOptions) -> Result<String, String> {
+    let cfg = &result.config;
+    let mut priors = cfg.priors.clone();
+    for v in [
+        &mut priors.mix_sim,
+        &mut priors.mix_quality,
+        &mut priors.mix_recency,
+        &mut priors.mix_collab,
+        &mut priors.freq_alpha,
+    ] {
+        *v = round3(*v);
+    }
+    priors.idf_lambda = priors.idf_lambda.map(round3);
+    priors.idf_alpha = priors.idf_alpha.map(round3);
+
+    let snippet = TunedSnippet {
+        df_floor: round3(cfg.df_floor),
+        idf_max: round3(cfg.idf_max),
+        group_weights: cfg
+            .group_weights
+            .iter()
+            .map(|(k, v)| (k.as_str(), round3(*v)))
+            .collect(),
+        negative: NegativeSnippet {
+            penalty: round3(cfg.negative.penalty),
+        },
+        priors,
+    };
+
+    let body = toml::to_string(&snippet).map_err(|e| format!("Failed to serialize config: {e}"))?;
+    let ids = accounts.iter().map(i32::to_string).collect::<Vec<_>>().join(", ");
+    Ok(format!(
+        "# tuned for account(s) {ids}: {metric}@{k} on test {test:.4} (baseline {test_baseline:.4}), \
+         on validation {best:.4} (baseline {baseline:.4}), {n} configs evaluated\n{body}",
+        metric = opts.metric.name(),
+        k = opts.k,
+        test = result.test_best,
+        test_baseline = result.test_baseline,
+        best = result.best,
+        baseline = result.baseline,
+        n = result.evaluated,
+    ))
+}
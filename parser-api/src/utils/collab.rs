@@ -0,0 +1,77 @@
+use std::collections::{HashMap, HashSet};
+
+#[derive(Debug, Clone, Default)]
+pub struct CoFavoriteIndex {
+    by_account: HashMap<i32, HashSet<i64>>,
+    by_post: HashMap<i64, Vec<i32>>,
+}
+
+impl CoFavoriteIndex {
+    pub fn from_rows(rows: &[(i32, i64)]) -> Self {
+        let mut index = Self::default();
+        for &(account_id, post_id) in rows {
+            index.by_account.entry(account_id).or_default().insert(post_id);
+            index.by_post.entry(post_id).or_default().push(account_id);
+        }
+        index
+    }
+
+    pub fn from_db(
+        get_rows: impl Fn() -> Result<Vec<(i32, i64)>, String>,
+    ) -> Result<Self, String> {
+        Ok(Self::from_rows(&get_rows()?))
+    }
+
+    #[inline]
+    fn popularity(&self, post_id: i64) -> f32 {
+        self.by_post.get(&post_id).map_or(0, |a| a.len()) as f32
+    }
+
+    // Item-item cosine over binary favorite vectors, summed over the account's favorites:
+    // score(p) = sum_q co(q, p) / sqrt(n_q * n_p), normalized so the best candidate is 1.0.
+    pub fn scores_for(&self, account_id: i32) -> HashMap<i64, f32> {
+        let Some(mine) = self.by_account.get(&account_id) else {
+            return HashMap::new();
+        };
+
+        let mut scores: HashMap<i64, f32> = HashMap::new();
+        for (&peer, theirs) in &self.by_account {
+            if peer == account_id {
+                continue;
+            }
+            let peer_weight: f32 = mine
+                .intersection(theirs)
+                .map(|&q| 1.0 / self.popularity(q).sqrt())
+                .sum();
+            if peer_weight <= 0.0 {
+                continue;
+            }
+            for &p in theirs.difference(mine) {
+                *scores.entry(p).or_insert(0.0) += peer_weight;
+            }
+        }
+
+        for (p, s) in scores.iter_mut() {
+            *s /= self.popularity(*p).sqrt().max(1.0);
+        }
+
+        let max = scores.values().copied().fold(0.0f32, f32::max);
+        if max > 0.0 {
+            for s in scores.values_mut() {
+                *s /= max;
+            }
+        }
+        scores
+    }
+
+    pub fn top_candidates(scores: &HashMap<i64, f32>, page: usize, per_page: usize) -> Vec<i64> {
+        let mut ranked: Vec<(&i64, &f32)> = scores.iter().collect();
+        ranked.sort_by(|a, b| b.1.total_cmp(a.1).then_with(|| b.0.cmp(a.0)));
+        ranked
+            .into_iter()
+            .skip(page.saturating_sub(1) * per_page)
+            .take(per_page)
+            .map(|(&id, _)| id)
+            .collect()
+    }
+}
@@ -0,0 +1,65 @@
+use std::collections::{HashMap, HashSet};
+use crate::models::{SharedTag, TagCount};
+use crate::utils::vector_cosine;
+
+fn tag_key(t: &TagCount) -> String {
+    format!("{}|{}", t.group_type, t.name.to_lowercase())
+}
+
+fn unique_tags(
+    own: &[TagCount],
+    own_vec: &HashMap<String, f32>,
+    other_keys: &HashSet<String>,
+    limit: usize,
+) -> Vec<TagCount> {
+    let mut unique: Vec<(f32, TagCount)> = own
+        .iter()
+        .filter(|t| t.count > 0)
+        .map(|t| (tag_key(t), t))
+        .filter(|(k, _)| !other_keys.contains(k))
+        .map(|(k, t)| (*own_vec.get(&k).unwrap_or(&0.0), t.clone()))
+        .collect();
+    unique.sort_by(|a, b| b.0.total_cmp(&a.0).then_with(|| a.1.name.cmp(&b.1.name)));
+    unique.into_iter().take(limit).map(|(_, t)| t).collect()
+}
+
+pub fn compare_tag_profiles(
+    a_counts: &[TagCount],
+    b_counts: &[TagCount],
+    a_vec: &HashMap<String, f32>,
+    b_vec: &HashMap<String, f32>,
+    limit: usize,
+) -> (f32, Vec<SharedTag>, Vec<TagCount>, Vec<TagCount>) {
+    let similarity = vector_cosine(a_vec, b_vec);
+
+    let a_norm = a_vec.values().map(|v| v * v).sum::<f32>().sqrt().max(f32::EPSILON);
+    let b_norm = b_vec.values().map(|v| v * v).sum::<f32>().sqrt().max(f32::EPSILON);
+
+    let b_by_key: HashMap<String, &TagCount> = b_counts.iter().map(|t| (tag_key(t), t)).collect();
+    let a_keys: HashSet<String> = a_counts.iter().map(tag_key).collect();
+    let b_keys: HashSet<String> = b_by_key.keys().cloned().collect();
+
+    let mut shared: Vec<SharedTag> = a_counts
+        .iter()
+        .filter_map(|a| {
+            let key = tag_key(a);
+            let b = b_by_key.get(&key)?;
+            let contribution = a_vec.get(&key).unwrap_or(&0.0) / a_norm
+                * b_vec.get(&key).unwrap_or(&0.0) / b_norm;
+            Some(SharedTag {
+                name: a.name.clone(),
+                group_type: a.group_type.clone(),
+                count_a: a.count,
+                count_b: b.count,
+                contribution,
+            })
+        })
+        .collect();
+    shared.sort_by(|x, y| y.contribution.total_cmp(&x.contribution).then_with(|| x.name.cmp(&y.name)));
+    shared.truncate(limit);
+
+    let unique_a = unique_tags(a_counts, a_vec, &b_keys, limit);
+    let unique_b = unique_tags(b_counts, b_vec, &a_keys, limit);
+
+    (similarity, shared, unique_a, unique_b)
+}
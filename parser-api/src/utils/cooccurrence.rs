@@ -0,0 +1,91 @@
+use std::str::FromStr;
+
+use crate::models::{RelatedTag, RelatedTags};
+
+/// How often a candidate tag shows up in a scope, alone and next to the queried tag.
+pub struct CoCount {
+    pub name: String,
+    pub group_type: String,
+    pub together: i64,
+    pub count: i64,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RelatedSort {
+    Count,
+    Lift,
+    Pmi,
+    /// Default: unlike PMI and lift it does not float tags seen only a handful of times.
+    #[default]
+    Jaccard,
+}
+
+impl FromStr for RelatedSort {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "count" => Ok(RelatedSort::Count),
+            "lift" => Ok(RelatedSort::Lift),
+            "pmi" => Ok(RelatedSort::Pmi),
+            "jaccard" => Ok(RelatedSort::Jaccard),
+            other => Err(format!(
+                "Unknown sort '{other}', expected count, lift, pmi or jaccard"
+            )),
+        }
+    }
+}
+
+impl RelatedSort {
+    fn key(self, t: &RelatedTag) -> f64 {
+        match self {
+            RelatedSort::Count => t.together as f64,
+            RelatedSort::Lift => t.lift,
+            RelatedSort::Pmi => t.pmi,
+            RelatedSort::Jaccard => t.jaccard,
+        }
+    }
+}
+
+/// Co-occurrence statistics of `tag` against every candidate: with `N` posts in scope,
+/// `a` carrying the tag, `b` the candidate and `ab` both,
+/// lift = ab·N / (a·b), PMI = log2(lift) and Jaccard = ab / (a + b - ab).
+pub fn related_tags(
+    tag: &str,
+    scope_posts: i64,
+    tag_posts: i64,
+    counts: Vec<CoCount>,
+    sort: RelatedSort,
+    limit: usize,
+) -> RelatedTags {
+    let n = scope_posts.max(1) as f64;
+    let a = tag_posts.max(1) as f64;
+
+    let mut related: Vec<RelatedTag> = counts
+        .into_iter()
+        .filter(|c| c.together > 0 && c.count > 0)
+        .map(|c| {
+            let (ab, b) = (c.together as f64, c.count as f64);
+            let lift = ab * n / (a * b);
+            RelatedTag {
+                name: c.name,
+                group_type: c.group_type,
+                together: c.together,
+                count: c.count,
+                lift,
+                pmi: lift.log2(),
+                jaccard: ab / (a + b - ab),
+            }
+        })
+        .collect();
+
+    related.sort_by(|x, y| sort.key(y).total_cmp(&sort.key(x)).then_with(|| x.name.cmp(&y.name)));
+    related.truncate(limit);
+
+    RelatedTags {
+        tag: tag.to_string(),
+        scope_posts,
+        tag_posts,
+        related,
+    }
+}
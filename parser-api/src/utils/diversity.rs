@@ -0,0 +1,101 @@
+use std::collections::HashMap;
+use serde::Deserialize;
+use crate::models::ScoredPost;
+use crate::utils::vector_cosine;
+
+/// Tags e621 files under `artist` that do not name an artist.
+pub const NON_ARTIST_TAGS: [&str; 6] = [
+    "conditional_dnp",
+    "sound_warning",
+    "unknown_artist",
+    "anonymous_artist",
+    "third-party_edit",
+    "epilepsy_warning",
+];
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Diversity {
+    pub strength: f32,
+    pub max_per_artist: Option<usize>,
+    pub max_per_copyright: Option<usize>,
+}
+
+fn over_cap(tags: &[String], cap: Option<usize>, seen: &HashMap<String, usize>) -> bool {
+    cap.is_some_and(|cap| tags.iter().any(|t| *seen.get(t).unwrap_or(&0) >= cap))
+}
+
+fn count_in(tags: &[String], seen: &mut HashMap<String, usize>) {
+    for t in tags {
+        *seen.entry(t.clone()).or_insert(0) += 1;
+    }
+}
+
+// Maximal marginal relevance: each pick maximizes
+// (1 - strength) * score - strength * max_similarity_to_already_picked,
+// skipping posts whose artist or copyright already hit the per-page cap.
+pub fn diversify(
+    scored: Vec<ScoredPost>,
+    vectors: &[HashMap<String, f32>],
+    diversity: &Diversity,
+) -> Vec<ScoredPost> {
+    let strength = diversity.strength.clamp(0.0, 1.0);
+    if strength == 0.0 && diversity.max_per_artist.is_none() && diversity.max_per_copyright.is_none() {
+        return scored;
+    }
+
+    let artists: Vec<Vec<String>> = scored
+        .iter()
+        .map(|sp| {
+            sp.post
+                .tags
+                .artist
+                .iter()
+                .filter(|a| !NON_ARTIST_TAGS.contains(&a.as_str()))
+                .cloned()
+                .collect()
+        })
+        .collect();
+
+    let mut remaining: Vec<usize> = (0..scored.len()).collect();
+    let mut max_sim: Vec<f32> = vec![0.0; scored.len()];
+    let mut picked: Vec<usize> = Vec::with_capacity(scored.len());
+    let mut artist_seen: HashMap<String, usize> = HashMap::new();
+    let mut copyright_seen: HashMap<String, usize> = HashMap::new();
+
+    while !remaining.is_empty() {
+        remaining.retain(|&i| {
+            !over_cap(&artists[i], diversity.max_per_artist, &artist_seen)
+                && !over_cap(
+                    &scored[i].post.tags.copyright,
+                    diversity.max_per_copyright,
+                    &copyright_seen,
+                )
+        });
+
+        let mmr = |i: usize| (1.0 - strength) * scored[i].score - strength * max_sim[i];
+        let Some((pos, &best)) = remaining
+            .iter()
+            .enumerate()
+            .max_by(|(_, a), (_, b)| mmr(**a).total_cmp(&mmr(**b)).then_with(|| b.cmp(a)))
+        else {
+            break;
+        };
+
+        remaining.swap_remove(pos);
+        picked.push(best);
+        count_in(&artists[best], &mut artist_seen);
+        count_in(&scored[best].post.tags.copyright, &mut copyright_seen);
+
+        if strength > 0.0 {
+            for &i in &remaining {
+                let sim = vector_cosine(&vectors[i], &vectors[best]);
+                if sim > max_sim[i] {
+                    max_sim[i] = sim;
+                }
+            }
+        }
+    }
+
+    let mut slots: Vec<Option<ScoredPost>> = scored.into_iter().map(Some).collect();
+    picked.into_iter().filter_map(|i| slots[i].take()).collect()
+}
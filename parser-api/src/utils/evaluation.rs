@@ -0,0 +1,97 @@
+use std::collections::HashMap;
+use chrono::{DateTime, Utc};
+use crate::models::TagCount;
+use crate::utils::favorite_decay;
+
+#[derive(Debug, Clone)]
+pub struct SplitMix64(u64);
+
+impl SplitMix64 {
+    pub fn new(seed: u64) -> Self { Self(seed) }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    pub fn next_f32(&mut self) -> f32 {
+        (self.next_u64() >> 40) as f32 / (1u64 << 24) as f32
+    }
+
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            let j = (self.next_u64() % (i as u64 + 1)) as usize;
+            items.swap(i, j);
+        }
+    }
+}
+
+pub fn aggregate_tag_counts<'a>(
+    rows: impl IntoIterator<Item = (&'a str, &'a str, Option<DateTime<Utc>>)>,
+    decay_tau_days: Option<f32>,
+    now: DateTime<Utc>,
+) -> Vec<TagCount> {
+    let mut totals: HashMap<(&str, &str), (i64, f64)> = HashMap::new();
+
+    for (name, group_type, favorited_at) in rows {
+        let weight = match (decay_tau_days, favorited_at) {
+            (Some(tau), Some(at)) => favorite_decay(at, now, tau) as f64,
+            _ => 1.0,
+        };
+        let entry = totals.entry((name, group_type)).or_insert((0, 0.0));
+        entry.0 += 1;
+        entry.1 += weight;
+    }
+
+    totals
+        .into_iter()
+        .map(|((name, group_type), (count, weight))| TagCount {
+            name: name.to_string(),
+            group_type: group_type.to_string(),
+            count,
+            weight,
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RankingMetrics {
+    pub precision: f32,
+    pub recall: f32,
+    pub ndcg: f32,
+    pub mrr: f32,
+}
+
+pub fn ranking_metrics(ranked_relevance: &[bool], total_relevant: usize, k: usize) -> RankingMetrics {
+    if total_relevant == 0 || k == 0 {
+        return RankingMetrics::default();
+    }
+
+    let top = &ranked_relevance[..k.min(ranked_relevance.len())];
+    let hits = top.iter().filter(|&&r| r).count() as f32;
+
+    let dcg: f32 = top
+        .iter()
+        .enumerate()
+        .filter(|(_, r)| **r)
+        .map(|(i, _)| 1.0 / (i as f32 + 2.0).log2())
+        .sum();
+    let idcg: f32 = (0..total_relevant.min(k))
+        .map(|i| 1.0 / (i as f32 + 2.0).log2())
+        .sum();
+
+    let mrr = ranked_relevance
+        .iter()
+        .position(|&r| r)
+        .map_or(0.0, |i| 1.0 / (i as f32 + 1.0));
+
+    RankingMetrics {
+        precision: hits / k as f32,
+        recall: hits / total_relevant as f32,
+        ndcg: if idcg > 0.0 { dcg / idcg } else { 0.0 },
+        mrr,
+    }
+}
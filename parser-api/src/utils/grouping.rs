@@ -0,0 +1,74 @@
+use std::collections::HashMap;
+use crate::models::ScoredPost;
+
+fn find(parent: &mut [usize], mut i: usize) -> usize {
+    while parent[i] != i {
+        parent[i] = parent[parent[i]];
+        i = parent[i];
+    }
+    i
+}
+
+// Keys that mark two posts as variants of the same thing: one parent/child family,
+// a shared pool or an identical file.
+fn relation_keys(sp: &ScoredPost) -> Vec<String> {
+    let post = &sp.post;
+    let mut keys = Vec::new();
+
+    if let Some(parent) = post.relationships.parent_id {
+        keys.push(format!("family:{parent}"));
+    }
+    if post.relationships.has_children || !post.relationships.children.is_empty() {
+        keys.push(format!("family:{}", post.id));
+    }
+    keys.extend(post.pools.iter().map(|p| format!("pool:{p}")));
+    if let Some(md5) = post.file.as_ref().and_then(|f| f.md5.as_deref()) {
+        keys.push(format!("md5:{md5}"));
+    }
+    keys
+}
+
+// Collapses related posts into the best scoring one of each group, which keeps the
+// position of the group's first post; the rest go to its `related` list, best first.
+pub fn group_related(scored: Vec<ScoredPost>) -> Vec<ScoredPost> {
+    let mut parent: Vec<usize> = (0..scored.len()).collect();
+    let mut owner: HashMap<String, usize> = HashMap::new();
+
+    for (i, sp) in scored.iter().enumerate() {
+        for key in relation_keys(sp) {
+            match owner.get(&key) {
+                Some(&j) => {
+                    let (a, b) = (find(&mut parent, i), find(&mut parent, j));
+                    if a != b {
+                        parent[a.max(b)] = a.min(b);
+                    }
+                }
+                None => {
+                    owner.insert(key, i);
+                }
+            }
+        }
+    }
+
+    let mut groups: Vec<Vec<ScoredPost>> = Vec::new();
+    let mut slot: HashMap<usize, usize> = HashMap::new();
+    for (i, sp) in scored.into_iter().enumerate() {
+        let root = find(&mut parent, i);
+        let idx = *slot.entry(root).or_insert_with(|| {
+            groups.push(Vec::new());
+            groups.len() - 1
+        });
+        groups[idx].push(sp);
+    }
+
+    groups
+        .into_iter()
+        .filter_map(|mut members| {
+            members.sort_by(|a, b| b.score.total_cmp(&a.score));
+            let mut members = members.into_iter();
+            let mut head = members.next()?;
+            head.related.extend(members);
+            Some(head)
+        })
+        .collect()
+}
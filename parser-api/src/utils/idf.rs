@@ -1,40 +1,51 @@
 use std::collections::HashMap;
 use chrono::{DateTime, Utc};
-use crate::models::cfg;
 
 #[derive(Debug, Clone)]
 pub struct IdfIndex {
     idf: HashMap<String, f32>,
+    df: HashMap<String, i64>,
+    df_total: i64,
     pub n_posts: i64,
     pub computed_at: DateTime<Utc>,
 }
 
 impl IdfIndex {
-    pub fn from_df(df: &HashMap<String, i64>, n_posts: i64, now: DateTime<Utc>) -> Self {
-        let cfg = cfg();
+    pub fn from_df(
+        df: &HashMap<String, i64>,
+        n_posts: i64,
+        now: DateTime<Utc>,
+        df_floor: f32,
+        idf_max: f32,
+    ) -> Self {
         let mut idf = HashMap::with_capacity(df.len());
+        let mut df_lc = HashMap::with_capacity(df.len());
         let n = n_posts.max(1) as f32;
 
         for (tag, &df_raw) in df {
+            *df_lc.entry(tag.to_lowercase()).or_insert(0) += df_raw.max(0);
             let dfv = df_raw.max(0) as f32;
-            let dfp = dfv + cfg.df_floor;
+            let dfp = dfv + df_floor;
             let val = (1.0 + ((n - dfp + 0.5) / (dfp + 0.5)).max(0.0)).ln()
-                .min(cfg.idf_max)
+                .min(idf_max)
                 .max(0.0);
             idf.insert(tag.to_lowercase(), val);
         }
 
-        Self { idf, n_posts, computed_at: now }
+        let df_total = df_lc.values().sum();
+        Self { idf, df: df_lc, df_total, n_posts, computed_at: now }
     }
 
     pub fn from_db(
         get_df: impl Fn() -> rusqlite::Result<HashMap<String, i64>>,
         get_post_count: impl Fn() -> i64,
         now: DateTime<Utc>,
+        df_floor: f32,
+        idf_max: f32,
     ) -> rusqlite::Result<Self> {
         let df = get_df()?;
         let n_posts = get_post_count();
-        Ok(Self::from_df(&df, n_posts, now))
+        Ok(Self::from_df(&df, n_posts, now, df_floor, idf_max))
     }
 
     #[inline]
@@ -49,5 +60,22 @@ impl IdfIndex {
         blended.powf(alpha.clamp(0.0, 1.0))
     }
 
+    /// Smoothed share of all stored tag occurrences that belong to `tag`.
+    #[inline]
+    pub fn background_prob(&self, tag: &str, smoothing: f32) -> f32 {
+        let df = *self.df.get(&tag.to_lowercase()).unwrap_or(&0) as f32;
+        let vocab = self.df.len().max(1) as f32;
+        (df + smoothing) / (self.df_total as f32 + smoothing * vocab)
+    }
+
+    /// Tags present on at least `min_share` of stored posts, with their share.
+    pub fn common_tags(&self, min_share: f32) -> impl Iterator<Item = (&str, f32)> {
+        let n = self.n_posts.max(1) as f32;
+        self.df
+            .iter()
+            .map(move |(tag, &df)| (tag.as_str(), df as f32 / n))
+            .filter(move |(_, share)| *share >= min_share)
+    }
+
     pub fn as_map(&self) -> &HashMap<String, f32> { &self.idf }
 }
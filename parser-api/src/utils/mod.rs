@@ -1,5 +1,25 @@
+mod collab;
+mod compare;
+mod cooccurrence;
+mod diversity;
+mod evaluation;
+mod grouping;
 mod scorer;
+mod scorers;
 mod idf;
+mod negative;
+mod timeline;
+mod topics;
 
+pub use collab::*;
+pub use compare::*;
+pub use cooccurrence::*;
+pub use diversity::*;
+pub use evaluation::*;
+pub use grouping::*;
 pub use scorer::*;
-pub use idf::*;
\ No newline at end of file
+pub use scorers::*;
+pub use idf::*;
+pub use negative::*;
+pub use timeline::*;
+pub use topics::*;
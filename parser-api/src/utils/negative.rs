@@ -0,0 +1,130 @@
+use serde::Deserialize;
+use std::collections::HashMap;
+
+use crate::models::{Post, TagCount};
+use crate::utils::idf::IdfIndex;
+use crate::utils::scorers::Scorer;
+use crate::utils::scorer::Priors;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct NegativeSignals {
+    /// Share of the similarity a fully matched negative profile removes; 0 disables it.
+    pub penalty: f32,
+    pub blacklist_weight: f32,
+    pub dislike_weight: f32,
+    pub common_weight: f32,
+    /// Tags on at least this share of stored posts count as common.
+    pub common_min_share: f32,
+}
+
+impl Default for NegativeSignals {
+    fn default() -> Self {
+        Self {
+            penalty: 0.0,
+            blacklist_weight: 1.0,
+            dislike_weight: 1.0,
+            common_weight: 0.25,
+            common_min_share: 0.05,
+        }
+    }
+}
+
+/// Tags that should pull a post's similarity down, keyed by lowercase tag name.
+#[derive(Debug, Clone, Default)]
+pub struct NegativeProfile {
+    weights: HashMap<String, f32>,
+    groups: Vec<String>,
+    penalty: f32,
+}
+
+// Positive terms of each blacklist line; `young -rating:s` still marks `young` as unwanted.
+fn blacklist_tags(blacklist: &str) -> impl Iterator<Item = String> + '_ {
+    blacklist
+        .lines()
+        .flat_map(str::split_whitespace)
+        .filter(|t| !t.starts_with('-') && !t.starts_with('~') && !t.contains(':'))
+        .map(str::to_lowercase)
+}
+
+impl NegativeProfile {
+    pub fn build(
+        signals: &NegativeSignals,
+        groups: &[String],
+        blacklist: &str,
+        dislikes: &[String],
+        account_tag_counts: &[TagCount],
+        idf: &IdfIndex,
+    ) -> Self {
+        if signals.penalty <= 0.0 {
+            return Self::default();
+        }
+
+        let mut weights: HashMap<String, f32> = HashMap::new();
+
+        let mut add = |tag: String, w: f32| {
+            if w > 0.0 {
+                let e = weights.entry(tag).or_insert(0.0);
+                *e = e.max(w);
+            }
+        };
+
+        for tag in blacklist_tags(blacklist) {
+            add(tag, signals.blacklist_weight);
+        }
+        for tag in dislikes {
+            add(tag.trim().to_lowercase(), signals.dislike_weight);
+        }
+
+        let favored: std::collections::HashSet<String> = account_tag_counts
+            .iter()
+            .filter(|t| t.count > 0)
+            .map(|t| t.name.to_lowercase())
+            .collect();
+        for (tag, share) in idf.common_tags(signals.common_min_share) {
+            if !favored.contains(tag) {
+                add(tag.to_string(), signals.common_weight * share.sqrt());
+            }
+        }
+
+        Self {
+            weights,
+            groups: groups.to_vec(),
+            penalty: signals.penalty.clamp(0.0, 1.0),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.penalty <= 0.0 || self.weights.is_empty()
+    }
+
+    /// Factor in `[1 - penalty, 1]` applied to a post's similarity.
+    pub fn multiplier(&self, post: &Post) -> f32 {
+        if self.is_empty() {
+            return 1.0;
+        }
+        let hit: f32 = post
+            .tags
+            .in_groups(&self.groups)
+            .filter_map(|(_, t)| self.weights.get(&t.to_lowercase()))
+            .sum();
+
+        1.0 - self.penalty * (1.0 - (-hit).exp())
+    }
+}
+
+/// Wraps any scorer so negative tags scale its similarity down.
+pub struct Penalized<'a> {
+    pub inner: Box<dyn Scorer + 'a>,
+    pub negative: &'a NegativeProfile,
+}
+
+impl Scorer for Penalized<'_> {
+    fn similarity(&self, post: &Post) -> f32 {
+        self.inner.similarity(post) * self.negative.multiplier(post)
+    }
+
+    fn priors(&self) -> &Priors {
+        self.inner.priors()
+    }
+}
@@ -1,10 +1,10 @@
 use chrono::{DateTime, Utc};
 use std::collections::HashMap;
-use serde::Deserialize;
-use crate::models::{Post, TagCount};
+use serde::{Deserialize, Serialize};
+use crate::models::{Post, Problems, TagCount};
 use crate::utils::idf::IdfIndex;
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct Priors {
     pub now: DateTime<Utc>,
     pub recency_tau_days: f32,
@@ -13,13 +13,54 @@ pub struct Priors {
     pub mix_sim: f32,
     pub mix_quality: f32,
     pub mix_recency: f32,
+    #[serde(default)]
+    pub mix_collab: f32,
     pub idf_lambda: Option<f32>,
     pub idf_alpha:  Option<f32>,
     pub freq_alpha: f32,
 }
 
+impl Priors {
+    /// Adds out-of-range values to `problems`, with field names under `prefix`.
+    pub fn validate(&self, prefix: &str, problems: &mut Problems) {
+        problems.positive(&format!("{prefix}.recency_tau_days"), self.recency_tau_days);
+        // Unbounded coefficients on score and favorite count; only NaN or infinity break them.
+        for (name, coefficient) in [("quality_a", self.quality_a), ("quality_b", self.quality_b)] {
+            problems.check(coefficient.is_finite(), || {
+                format!("{prefix}.{name} must be a finite number, got {coefficient}")
+            });
+        }
+        let mixes = [
+            ("mix_sim", self.mix_sim),
+            ("mix_quality", self.mix_quality),
+            ("mix_recency", self.mix_recency),
+            ("mix_collab", self.mix_collab),
+        ];
+        for (name, mix) in mixes {
+            problems.at_least(&format!("{prefix}.{name}"), mix, 0.0);
+        }
+        problems.check(mixes.iter().any(|(_, mix)| *mix > 0.0), || {
+            format!("{prefix} needs at least one positive mix_* weight")
+        });
+        // Both are clamped to 0..=1 when used; anything else is a typo.
+        if let Some(lambda) = self.idf_lambda {
+            problems.range(&format!("{prefix}.idf_lambda"), lambda, 0.0, 1.0);
+        }
+        if let Some(alpha) = self.idf_alpha {
+            problems.range(&format!("{prefix}.idf_alpha"), alpha, 0.0, 1.0);
+        }
+        problems.at_least(&format!("{prefix}.freq_alpha"), self.freq_alpha, 0.0);
+    }
+}
+
 #[inline]
-fn sigmoid(x: f32) -> f32 { 1.0 / (1.0 + (-x).exp()) }
+pub(crate) fn sigmoid(x: f32) -> f32 { 1.0 / (1.0 + (-x).exp()) }
+
+#[inline]
+pub fn favorite_decay(favorited_at: DateTime<Utc>, now: DateTime<Utc>, tau_days: f32) -> f32 {
+    let age_days = (now - favorited_at).num_seconds().max(0) as f32 / 86_400.0;
+    (-age_days / tau_days.max(1e-3)).exp().clamp(0.0, 1.0)
+}
 
 #[inline]
 fn gw<'a>(group_wts: &HashMap<&'a str, f32>, group: &'a str) -> f32 {
@@ -27,75 +68,81 @@ fn gw<'a>(group_wts: &HashMap<&'a str, f32>, group: &'a str) -> f32 {
 }
 
 
-pub fn post_affinity(
+pub fn user_vector(
     account_tag_counts: &[TagCount],
-    origin_post: &Post,
     group_wts: &HashMap<String, f32>,
     priors: &Priors,
     idf: &IdfIndex,
-) -> f32 {
-    let group_wts_hash: HashMap<&str, f32> =
-        group_wts.iter().map(|(k, v)| (k.as_str(), *v)).collect();
-
+) -> HashMap<String, f32> {
     let lambda = priors.idf_lambda.unwrap_or(0.4);
     let alpha  = priors.idf_alpha.unwrap_or(0.5);
 
     let mut user: HashMap<String, f32> = HashMap::default();
-    let mut u_norm_sq = 0.0f32;
 
     for t in account_tag_counts {
         if t.count <= 0 { continue; }
-        let g = *group_wts_hash.get(t.group_type.as_str()).unwrap_or(&1.0);
+        let g = *group_wts.get(t.group_type.as_str()).unwrap_or(&1.0);
         let tlc = t.name.to_lowercase();
         let idf_w = idf.idf_tempered(&tlc, lambda, alpha);
-        let w = (t.count as f32).powf(priors.freq_alpha) * g * idf_w;
+        let w = (t.weight as f32).powf(priors.freq_alpha) * g * idf_w;
         if w > 0.0 {
             let key = format!("{}|{}", t.group_type, tlc);
             let e = user.entry(key).or_insert(0.0);
             *e += w;
         }
     }
-    for &uw in user.values() { u_norm_sq += uw * uw; }
 
-    let mut dot = 0.0f32;
-    let mut p_norm_sq = 0.0f32;
+    user
+}
+
+pub fn vector_cosine(a: &HashMap<String, f32>, b: &HashMap<String, f32>) -> f32 {
+    let (small, large) = if a.len() <= b.len() { (a, b) } else { (b, a) };
+    let dot: f32 = small
+        .iter()
+        .filter_map(|(k, v)| large.get(k).map(|w| v * w))
+        .sum();
+    let a_norm = a.values().map(|v| v * v).sum::<f32>().sqrt();
+    let b_norm = b.values().map(|v| v * v).sum::<f32>().sqrt();
+
+    if a_norm == 0.0 || b_norm == 0.0 { 0.0 } else { dot / (a_norm * b_norm) }
+}
+
+pub fn post_vector(
+    post: &Post,
+    groups: &[String],
+    group_wts: &HashMap<String, f32>,
+    priors: &Priors,
+    idf: &IdfIndex,
+) -> HashMap<String, f32> {
+    let group_wts_hash: HashMap<&str, f32> =
+        group_wts.iter().map(|(k, v)| (k.as_str(), *v)).collect();
+
+    let lambda = priors.idf_lambda.unwrap_or(0.4);
+    let alpha  = priors.idf_alpha.unwrap_or(0.5);
 
-    let mut acc = |tags: &Vec<String>, group: &'static str| {
+    let mut vector: HashMap<String, f32> = HashMap::default();
+
+    for (group, t) in post.tags.in_groups(groups) {
         let g = gw(&group_wts_hash, group);
-        for t in tags {
-            if t.is_empty() { continue; }
-            let tlc = t.to_lowercase();
-            let idf_w = idf.idf_tempered(&tlc, lambda, alpha);
-            let pw = g * idf_w;
-            p_norm_sq += pw * pw;
-
-            let key = {
-                let mut s = String::with_capacity(group.len() + 1 + tlc.len());
-                s.push_str(group);
-                s.push('|');
-                s.push_str(&tlc);
-                s
-            };
-            if let Some(&uw) = user.get(&key) {
-                dot += uw * pw;
-            }
-        }
-    };
-
-    acc(&origin_post.tags.artist,    "artist");
-    acc(&origin_post.tags.character, "character");
-    acc(&origin_post.tags.copyright, "copyright");
-    acc(&origin_post.tags.general,   "general");
-    acc(&origin_post.tags.lore,      "lore");
-    acc(&origin_post.tags.meta,      "meta");
-    acc(&origin_post.tags.species,   "species");
-
-    let sim = if u_norm_sq == 0.0 || p_norm_sq == 0.0 {
-        0.0
-    } else {
-        dot / (u_norm_sq.sqrt() * p_norm_sq.sqrt())
-    };
+        let tlc = t.to_lowercase();
+        let idf_w = idf.idf_tempered(&tlc, lambda, alpha);
+        let pw = g * idf_w;
+
+        let key = {
+            let mut s = String::with_capacity(group.len() + 1 + tlc.len());
+            s.push_str(group);
+            s.push('|');
+            s.push_str(&tlc);
+            s
+        };
+        *vector.entry(key).or_insert(0.0) += pw;
+    }
 
+    vector
+}
+
+/// Mixes a model's tag similarity with the quality, recency and co-favorite signals.
+pub fn blend_score(origin_post: &Post, sim: f32, priors: &Priors, collab: f32) -> f32 {
     let quality = sigmoid(
         priors.quality_a * origin_post.score.total as f32 +
             priors.quality_b * origin_post.fav_count as f32
@@ -103,10 +150,15 @@ pub fn post_affinity(
     let age_days = (priors.now - origin_post.created_at).num_seconds() as f32 / 86_400.0;
     let recency = (-age_days / priors.recency_tau_days.max(1e-3)).exp().clamp(0.0, 1.0);
 
-    let sum = priors.mix_sim + priors.mix_quality + priors.mix_recency;
-    let (ms, mq, mr) = if sum > 0.0 {
-        (priors.mix_sim / sum, priors.mix_quality / sum, priors.mix_recency / sum)
-    } else { (0.0, 0.0, 0.0) };
-
-    (ms * sim + mq * quality + mr * recency).clamp(0.0, 1.0)
-}
\ No newline at end of file
+    let sum = priors.mix_sim + priors.mix_quality + priors.mix_recency + priors.mix_collab;
+    let (ms, mq, mr, mc) = if sum > 0.0 {
+        (
+            priors.mix_sim / sum,
+            priors.mix_quality / sum,
+            priors.mix_recency / sum,
+            priors.mix_collab / sum,
+        )
+    } else { (0.0, 0.0, 0.0, 0.0) };
+
+    (ms * sim + mq * quality + mr * recency + mc * collab.clamp(0.0, 1.0)).clamp(0.0, 1.0)
+}
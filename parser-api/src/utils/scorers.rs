@@ -0,0 +1,243 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use crate::models::{Config, Post, TagCount};
+use crate::utils::idf::IdfIndex;
+use crate::utils::negative::{NegativeProfile, Penalized};
+use crate::utils::scorer::{Priors, blend_score, post_vector, sigmoid, user_vector, vector_cosine};
+
+// BM25 length normalization; posts carry roughly this many tags on average.
+const BM25_K1: f32 = 1.2;
+const BM25_B: f32 = 0.75;
+const BM25_AVG_TAGS: f32 = 40.0;
+
+const NB_SMOOTHING: f32 = 1.0;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ScoringModel {
+    /// TF-IDF weighted cosine between the account and post tag vectors.
+    #[default]
+    Cosine,
+    /// BM25 with the account's weighted tags as the query and the post as the document.
+    Bm25,
+    /// Multinomial naive Bayes log-odds of the post's tags against the stored corpus.
+    NaiveBayes,
+}
+
+impl FromStr for ScoringModel {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "cosine" | "tfidf" => Ok(ScoringModel::Cosine),
+            "bm25" => Ok(ScoringModel::Bm25),
+            "naive_bayes" | "log_odds" => Ok(ScoringModel::NaiveBayes),
+            other => Err(format!(
+                "Unknown scoring model '{other}', expected cosine, bm25 or naive_bayes"
+            )),
+        }
+    }
+}
+
+pub trait Scorer {
+    /// How well the post's tags match the account, in `[0, 1]`.
+    fn similarity(&self, post: &Post) -> f32;
+
+    fn priors(&self) -> &Priors;
+
+    fn score(&self, post: &Post, collab: f32) -> f32 {
+        blend_score(post, self.similarity(post), self.priors(), collab)
+    }
+}
+
+/// Builds the account side of `model` once so it can be reused for every candidate.
+pub fn build_scorer<'a>(
+    model: ScoringModel,
+    account_tag_counts: &[TagCount],
+    cfg: &'a Config,
+    priors: &'a Priors,
+    idf: &'a IdfIndex,
+    negative: &'a NegativeProfile,
+) -> Box<dyn Scorer + 'a> {
+    let scorer: Box<dyn Scorer + 'a> = match model {
+        ScoringModel::Cosine => Box::new(CosineScorer {
+            user: user_vector(account_tag_counts, &cfg.group_weights, priors, idf),
+            cfg,
+            priors,
+            idf,
+        }),
+        ScoringModel::Bm25 => Box::new(Bm25Scorer::new(account_tag_counts, cfg, priors, idf)),
+        ScoringModel::NaiveBayes => {
+            Box::new(NaiveBayesScorer::new(account_tag_counts, cfg, priors, idf))
+        }
+    };
+
+    if negative.is_empty() {
+        scorer
+    } else {
+        Box::new(Penalized { inner: scorer, negative })
+    }
+}
+
+fn post_tags<'a>(post: &'a Post, groups: &'a [String]) -> impl Iterator<Item = (&'a str, String)> {
+    post.tags.in_groups(groups).map(|(group, t)| (group, t.to_lowercase()))
+}
+
+#[inline]
+fn group_weight(group_wts: &HashMap<String, f32>, group: &str) -> f32 {
+    *group_wts.get(group).unwrap_or(&1.0)
+}
+
+pub struct CosineScorer<'a> {
+    user: HashMap<String, f32>,
+    cfg: &'a Config,
+    priors: &'a Priors,
+    idf: &'a IdfIndex,
+}
+
+impl Scorer for CosineScorer<'_> {
+    fn similarity(&self, post: &Post) -> f32 {
+        let post = post_vector(post, &self.cfg.tag_groups, &self.cfg.group_weights, self.priors, self.idf);
+        vector_cosine(&self.user, &post)
+    }
+
+    fn priors(&self) -> &Priors {
+        self.priors
+    }
+}
+
+pub struct Bm25Scorer<'a> {
+    // query term weight before IDF, keyed "group|tag"
+    query: HashMap<String, f32>,
+    ideal: f32,
+    groups: &'a [String],
+    priors: &'a Priors,
+    idf: &'a IdfIndex,
+}
+
+impl<'a> Bm25Scorer<'a> {
+    fn new(
+        account_tag_counts: &[TagCount],
+        cfg: &'a Config,
+        priors: &'a Priors,
+        idf: &'a IdfIndex,
+    ) -> Self {
+        let group_wts = &cfg.group_weights;
+        let mut query: HashMap<String, f32> = HashMap::new();
+        for t in account_tag_counts.iter().filter(|t| t.count > 0) {
+            let w = (t.weight as f32).powf(priors.freq_alpha) * group_weight(group_wts, &t.group_type);
+            if w > 0.0 {
+                *query
+                    .entry(format!("{}|{}", t.group_type, t.name.to_lowercase()))
+                    .or_insert(0.0) += w;
+            }
+        }
+
+        let mut s = Self { query, ideal: 0.0, groups: &cfg.tag_groups, priors, idf };
+
+        // Best case: a post of average length made of the account's strongest tags.
+        let mut terms: Vec<f32> = s.query.iter().map(|(k, q)| q * s.term_idf(k)).collect();
+        terms.sort_by(|a, b| b.total_cmp(a));
+        s.ideal = terms.into_iter().take(BM25_AVG_TAGS as usize).sum();
+        s
+    }
+
+    fn term_idf(&self, key: &str) -> f32 {
+        let tag = key.split_once('|').map_or(key, |(_, t)| t);
+        self.idf.idf_tempered(
+            tag,
+            self.priors.idf_lambda.unwrap_or(0.4),
+            self.priors.idf_alpha.unwrap_or(0.5),
+        )
+    }
+}
+
+impl Scorer for Bm25Scorer<'_> {
+    fn similarity(&self, post: &Post) -> f32 {
+        if self.ideal <= 0.0 {
+            return 0.0;
+        }
+        let tags: Vec<(&str, String)> = post_tags(post, self.groups).collect();
+        let len_norm = BM25_K1 * (1.0 - BM25_B + BM25_B * tags.len() as f32 / BM25_AVG_TAGS);
+        let tf = (BM25_K1 + 1.0) / (1.0 + len_norm);
+
+        let raw: f32 = tags
+            .iter()
+            .filter_map(|(group, tag)| {
+                let key = format!("{group}|{tag}");
+                self.query.get(&key).map(|q| q * self.term_idf(&key) * tf)
+            })
+            .sum();
+
+        (raw / self.ideal).clamp(0.0, 1.0)
+    }
+
+    fn priors(&self) -> &Priors {
+        self.priors
+    }
+}
+
+pub struct NaiveBayesScorer<'a> {
+    // ln P(tag | account), keyed "group|tag"
+    log_p_user: HashMap<String, f32>,
+    log_p_unseen: f32,
+    cfg: &'a Config,
+    priors: &'a Priors,
+    idf: &'a IdfIndex,
+}
+
+impl<'a> NaiveBayesScorer<'a> {
+    fn new(
+        account_tag_counts: &[TagCount],
+        cfg: &'a Config,
+        priors: &'a Priors,
+        idf: &'a IdfIndex,
+    ) -> Self {
+        let mut weights: HashMap<String, f32> = HashMap::new();
+        for t in account_tag_counts.iter().filter(|t| t.count > 0) {
+            *weights
+                .entry(format!("{}|{}", t.group_type, t.name.to_lowercase()))
+                .or_insert(0.0) += (t.weight as f32).max(0.0).powf(priors.freq_alpha);
+        }
+
+        let vocab = idf.as_map().len().max(weights.len()).max(1) as f32;
+        let denom = weights.values().sum::<f32>() + NB_SMOOTHING * vocab;
+        let log_p_user = weights
+            .into_iter()
+            .map(|(k, w)| (k, ((w + NB_SMOOTHING) / denom).ln()))
+            .collect();
+
+        Self {
+            log_p_user,
+            log_p_unseen: (NB_SMOOTHING / denom).ln(),
+            cfg,
+            priors,
+            idf,
+        }
+    }
+}
+
+impl Scorer for NaiveBayesScorer<'_> {
+    fn similarity(&self, post: &Post) -> f32 {
+        let (mut sum, mut norm) = (0.0f32, 0.0f32);
+        for (group, tag) in post_tags(post, &self.cfg.tag_groups) {
+            let g = group_weight(&self.cfg.group_weights, group);
+            let log_user = *self
+                .log_p_user
+                .get(&format!("{group}|{tag}"))
+                .unwrap_or(&self.log_p_unseen);
+            let log_bg = self.idf.background_prob(&tag, NB_SMOOTHING).ln();
+            sum += g * (log_user - log_bg);
+            norm += g;
+        }
+
+        if norm > 0.0 { sigmoid(sum / norm) } else { 0.0 }
+    }
+
+    fn priors(&self) -> &Priors {
+        self.priors
+    }
+}
@@ -0,0 +1,123 @@
+use std::collections::{HashMap, HashSet};
+use crate::models::{PeriodTagCount, TagTimeline, TagTrend, TimelineBucket, TimelinePeriod};
+
+const TREND_RATIO: f64 = 4.0;
+const MIN_TREND_COUNT: i64 = 3;
+
+#[inline]
+fn share(count: i64, favorites: i64) -> f64 {
+    if favorites <= 0 { 0.0 } else { count as f64 / favorites as f64 }
+}
+
+pub fn build_timeline(
+    period: TimelinePeriod,
+    favorites: &[(String, i64)],
+    rows: &[(String, String, String, i64)],
+    recent_periods: usize,
+    limit: usize,
+) -> TagTimeline {
+    let mut totals: HashMap<(&str, &str), i64> = HashMap::new();
+    for (_, name, group, count) in rows {
+        *totals.entry((name.as_str(), group.as_str())).or_insert(0) += count;
+    }
+
+    let mut by_group: HashMap<&str, Vec<(&str, i64)>> = HashMap::new();
+    for (&(name, group), &count) in &totals {
+        by_group.entry(group).or_default().push((name, count));
+    }
+    let mut tracked: HashSet<(&str, &str)> = HashSet::new();
+    for (group, tags) in by_group.iter_mut() {
+        tags.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+        for (name, _) in tags.iter().take(limit) {
+            tracked.insert((name, group));
+        }
+    }
+
+    let favorites_by_period: HashMap<&str, i64> =
+        favorites.iter().map(|(p, c)| (p.as_str(), *c)).collect();
+
+    let mut buckets: Vec<TimelineBucket> = favorites
+        .iter()
+        .map(|(p, c)| TimelineBucket { period: p.clone(), favorites: *c, tags: Vec::new() })
+        .collect();
+    let bucket_index: HashMap<&str, usize> = favorites
+        .iter()
+        .enumerate()
+        .map(|(i, (p, _))| (p.as_str(), i))
+        .collect();
+
+    let recent_start = favorites.len().saturating_sub(recent_periods.max(1));
+    let recent_keys: HashSet<&str> = favorites[recent_start..].iter().map(|(p, _)| p.as_str()).collect();
+    let recent_favorites: i64 = favorites[recent_start..].iter().map(|(_, c)| c).sum();
+    let earlier_favorites: i64 = favorites[..recent_start].iter().map(|(_, c)| c).sum();
+
+    let mut split: HashMap<(&str, &str), (i64, i64)> = HashMap::new();
+
+    for (p, name, group, count) in rows {
+        let key = (name.as_str(), group.as_str());
+        let entry = split.entry(key).or_insert((0, 0));
+        if recent_keys.contains(p.as_str()) {
+            entry.1 += count;
+        } else {
+            entry.0 += count;
+        }
+
+        if !tracked.contains(&key) {
+            continue;
+        }
+        if let Some(&i) = bucket_index.get(p.as_str()) {
+            let fav = *favorites_by_period.get(p.as_str()).unwrap_or(&0);
+            buckets[i].tags.push(PeriodTagCount {
+                name: name.clone(),
+                group_type: group.clone(),
+                count: *count,
+                share: share(*count, fav),
+            });
+        }
+    }
+
+    for bucket in buckets.iter_mut() {
+        bucket.tags.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.name.cmp(&b.name)));
+    }
+
+    let mut new_interests = Vec::new();
+    let mut abandoned_interests = Vec::new();
+
+    if earlier_favorites > 0 && recent_favorites > 0 {
+        for (&(name, group), &(earlier, recent)) in &split {
+            let trend = TagTrend {
+                name: name.to_string(),
+                group_type: group.to_string(),
+                earlier_count: earlier,
+                recent_count: recent,
+                earlier_share: share(earlier, earlier_favorites),
+                recent_share: share(recent, recent_favorites),
+            };
+            if recent >= MIN_TREND_COUNT && trend.earlier_share * TREND_RATIO <= trend.recent_share {
+                new_interests.push(trend);
+            } else if earlier >= MIN_TREND_COUNT && trend.recent_share * TREND_RATIO <= trend.earlier_share {
+                abandoned_interests.push(trend);
+            }
+        }
+    }
+
+    new_interests.sort_by(|a, b| {
+        (b.recent_share - b.earlier_share)
+            .total_cmp(&(a.recent_share - a.earlier_share))
+            .then_with(|| a.name.cmp(&b.name))
+    });
+    abandoned_interests.sort_by(|a, b| {
+        (b.earlier_share - b.recent_share)
+            .total_cmp(&(a.earlier_share - a.recent_share))
+            .then_with(|| a.name.cmp(&b.name))
+    });
+    new_interests.truncate(limit);
+    abandoned_interests.truncate(limit);
+
+    TagTimeline {
+        period: period.as_str().to_string(),
+        buckets,
+        new_interests,
+        abandoned_interests,
+    }
+}
@@ -0,0 +1,224 @@
+use serde::Deserialize;
+use std::collections::HashMap;
+
+use crate::models::{ScoredPost, Topic, TopicTag};
+use crate::utils::{SplitMix64, vector_cosine};
+
+const TOP_TAGS: usize = 10;
+const EXAMPLE_POSTS: usize = 6;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct TopicSettings {
+    /// Number of topics to split the favorites into.
+    pub k: usize,
+    pub max_iter: usize,
+    /// Tags used for each topic's label.
+    pub label_tags: usize,
+    /// Most posts per topic on one feed page; `None` disables the quota.
+    pub quota: Option<usize>,
+    pub seed: u64,
+}
+
+impl Default for TopicSettings {
+    fn default() -> Self {
+        Self {
+            k: 8,
+            max_iter: 25,
+            label_tags: 3,
+            quota: None,
+            seed: 42,
+        }
+    }
+}
+
+pub struct TopicModel {
+    centroids: Vec<HashMap<String, f32>>,
+    pub topics: Vec<Topic>,
+}
+
+fn normalized(v: &HashMap<String, f32>) -> HashMap<String, f32> {
+    let norm = v.values().map(|w| w * w).sum::<f32>().sqrt();
+    if norm == 0.0 {
+        return HashMap::new();
+    }
+    v.iter().map(|(k, w)| (k.clone(), w / norm)).collect()
+}
+
+fn nearest(centroids: &[HashMap<String, f32>], v: &HashMap<String, f32>) -> Option<(usize, f32)> {
+    centroids
+        .iter()
+        .enumerate()
+        .map(|(i, c)| (i, vector_cosine(c, v)))
+        .filter(|(_, sim)| *sim > 0.0)
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+}
+
+// k-means++ seeding on cosine distance: each next center is drawn with probability
+// proportional to the squared distance from the closest center picked so far.
+fn seed_centroids(vectors: &[HashMap<String, f32>], k: usize, rng: &mut SplitMix64) -> Vec<HashMap<String, f32>> {
+    let mut centroids = vec![vectors[(rng.next_u64() % vectors.len() as u64) as usize].clone()];
+    while centroids.len() < k {
+        let dist: Vec<f32> = vectors
+            .iter()
+            .map(|v| {
+                let sim = nearest(&centroids, v).map_or(0.0, |(_, s)| s);
+                (1.0 - sim).powi(2)
+            })
+            .collect();
+        let total: f32 = dist.iter().sum();
+        if total <= 0.0 {
+            break;
+        }
+
+        let mut target = rng.next_f32() * total;
+        let pick = dist
+            .iter()
+            .position(|d| {
+                target -= d;
+                target <= 0.0
+            })
+            .unwrap_or(dist.len() - 1);
+        centroids.push(vectors[pick].clone());
+    }
+    centroids
+}
+
+fn mean(members: impl Iterator<Item = HashMap<String, f32>>) -> HashMap<String, f32> {
+    let mut sum: HashMap<String, f32> = HashMap::new();
+    for v in members {
+        for (k, w) in v {
+            *sum.entry(k).or_insert(0.0) += w;
+        }
+    }
+    normalized(&sum)
+}
+
+impl TopicModel {
+    /// Spherical k-means over the tag vectors of favorited posts, keyed `group|tag`.
+    pub fn fit(posts: &[(i64, HashMap<String, f32>)], settings: &TopicSettings) -> Self {
+        let ids: Vec<i64> = posts.iter().filter(|(_, v)| !v.is_empty()).map(|(id, _)| *id).collect();
+        let vectors: Vec<HashMap<String, f32>> = posts
+            .iter()
+            .filter(|(_, v)| !v.is_empty())
+            .map(|(_, v)| normalized(v))
+            .collect();
+
+        let k = settings.k.min(vectors.len());
+        if k == 0 {
+            return Self { centroids: Vec::new(), topics: Vec::new() };
+        }
+
+        let mut rng = SplitMix64::new(settings.seed);
+        let mut centroids = seed_centroids(&vectors, k, &mut rng);
+        let mut assignment: Vec<Option<usize>> = vec![None; vectors.len()];
+
+        for _ in 0..settings.max_iter.max(1) {
+            let next: Vec<Option<usize>> = vectors
+                .iter()
+                .map(|v| nearest(&centroids, v).map(|(i, _)| i))
+                .collect();
+            let changed = next != assignment;
+            assignment = next;
+            if !changed {
+                break;
+            }
+
+            for (c, centroid) in centroids.iter_mut().enumerate() {
+                let members = vectors
+                    .iter()
+                    .zip(&assignment)
+                    .filter(|(_, a)| **a == Some(c))
+                    .map(|(v, _)| v.clone());
+                let updated = mean(members);
+                // an emptied topic keeps its old center
+                if !updated.is_empty() {
+                    *centroid = updated;
+                }
+            }
+        }
+
+        let mut order: Vec<usize> = (0..centroids.len()).collect();
+        let sizes: Vec<usize> = (0..centroids.len())
+            .map(|c| assignment.iter().filter(|a| **a == Some(c)).count())
+            .collect();
+        order.retain(|c| sizes[*c] > 0);
+        order.sort_by(|a, b| sizes[*b].cmp(&sizes[*a]));
+
+        let total = vectors.len() as f32;
+        let centroids: Vec<HashMap<String, f32>> = order.iter().map(|c| centroids[*c].clone()).collect();
+        let topics = order
+            .iter()
+            .enumerate()
+            .map(|(id, &c)| {
+                let mut tags: Vec<(&String, &f32)> = centroids[id].iter().collect();
+                tags.sort_by(|a, b| b.1.total_cmp(a.1).then_with(|| a.0.cmp(b.0)));
+                let top_tags: Vec<TopicTag> = tags
+                    .into_iter()
+                    .take(TOP_TAGS)
+                    .map(|(key, w)| {
+                        let (group, name) = key.split_once('|').unwrap_or(("", key));
+                        TopicTag {
+                            name: name.to_string(),
+                            group_type: group.to_string(),
+                            weight: *w,
+                        }
+                    })
+                    .collect();
+
+                let mut members: Vec<(i64, f32)> = ids
+                    .iter()
+                    .zip(&vectors)
+                    .zip(&assignment)
+                    .filter(|(_, a)| **a == Some(c))
+                    .map(|((post_id, v), _)| (*post_id, vector_cosine(&centroids[id], v)))
+                    .collect();
+                members.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+                Topic {
+                    id,
+                    label: top_tags
+                        .iter()
+                        .take(settings.label_tags.max(1))
+                        .map(|t| t.name.as_str())
+                        .collect::<Vec<_>>()
+                        .join(" / "),
+                    top_tags,
+                    size: sizes[c],
+                    share: sizes[c] as f32 / total,
+                    example_post_ids: members.into_iter().take(EXAMPLE_POSTS).map(|(id, _)| id).collect(),
+                }
+            })
+            .collect();
+
+        Self { centroids, topics }
+    }
+
+    /// Topic closest to a post's tag vector, `None` if it shares no tag with any topic.
+    pub fn assign(&self, v: &HashMap<String, f32>) -> Option<usize> {
+        nearest(&self.centroids, v).map(|(i, _)| i)
+    }
+}
+
+/// Which posts to keep so that, going down by score, no topic fills more than `quota`
+/// places; posts matching no topic are always kept.
+pub fn topic_quota(
+    scored: &[ScoredPost],
+    vectors: &[HashMap<String, f32>],
+    topics: &TopicModel,
+    quota: usize,
+) -> Vec<bool> {
+    let mut order: Vec<usize> = (0..scored.len()).collect();
+    order.sort_by(|a, b| scored[*b].score.total_cmp(&scored[*a].score));
+
+    let mut keep = vec![true; scored.len()];
+    let mut taken: HashMap<usize, usize> = HashMap::new();
+    for i in order {
+        if let Some(topic) = topics.assign(&vectors[i]) {
+            let n = taken.entry(topic).or_insert(0);
+            *n += 1;
+            keep[i] = *n <= quota;
+        }
+    }
+    keep
+}
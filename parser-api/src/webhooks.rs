@@ -0,0 +1,372 @@
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+use reqwest::redirect::Policy;
+use reqwest::{Client, StatusCode, Url};
+use sha2::Sha256;
+use std::collections::HashSet;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::{Arc, LazyLock, Mutex};
+use std::time::Duration;
+use tokio::time::sleep;
+
+use crate::models::{ScoredPost, Webhook, WebhookDelivery, WebhookPayload, WebhookPost, cfg};
+use crate::{db, logging, metrics};
+
+pub const MATCHED_EVENT: &str = "post.matched";
+pub const TEST_EVENT: &str = "test";
+
+/// Deliveries per webhook and scheduler run; the rest wait for the next run.
+const MAX_PER_RUN: usize = 20;
+/// Wait before the first retry within a delivery, doubled for each further one.
+const RETRY_DELAY: Duration = Duration::from_secs(1);
+
+/// Separate from the upstream client: webhooks don't wait out `rps_delay_ms`. Users pick
+/// the URLs, so redirects are not followed and names only resolve to public addresses.
+static CLIENT: LazyLock<Client> = LazyLock::new(|| {
+    Client::builder()
+        .user_agent(concat!("e621-account-parser-api/", env!("CARGO_PKG_VERSION"), " webhooks"))
+        .connect_timeout(Duration::from_secs(10))
+        .redirect(Policy::none())
+        .dns_resolver(Arc::new(PublicResolver))
+        .build()
+        .expect("webhook client")
+});
+
+/// Resolves webhook hosts, dropping addresses that are not public unless the host is in
+/// `webhooks.allowed_hosts`. Checking at connect time also covers names that resolved
+/// to a public address when the webhook was created.
+struct PublicResolver;
+
+impl Resolve for PublicResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        let host = name.as_str().to_string();
+        Box::pin(async move {
+            let allowed = is_allowed_host(&host);
+            let addrs: Vec<SocketAddr> = tokio::net::lookup_host((host.as_str(), 0))
+                .await?
+                .filter(|addr| allowed || is_public(addr.ip()))
+                .collect();
+            if addrs.is_empty() {
+                return Err(format!("{host} has no public address").into());
+            }
+            Ok(Box::new(addrs.into_iter()) as Addrs)
+        })
+    }
+}
+
+/// Webhooks with a send in progress; a second one would deliver the same queue twice.
+static SENDING: LazyLock<Mutex<HashSet<i64>>> = LazyLock::new(|| Mutex::new(HashSet::new()));
+
+struct SendGuard(i64);
+
+impl SendGuard {
+    fn acquire(webhook_id: i64) -> Option<Self> {
+        SENDING
+            .lock()
+            .unwrap()
+            .insert(webhook_id)
+            .then_some(SendGuard(webhook_id))
+    }
+}
+
+impl Drop for SendGuard {
+    fn drop(&mut self) {
+        SENDING.lock().unwrap().remove(&self.0);
+    }
+}
+
+/// Queues the account's new posts for each of its webhooks that wants them and starts
+/// sending in the background. Posts uploaded before a webhook was created or already
+/// delivered to it are skipped. Returns how many posts were queued.
+pub fn dispatch(account_id: i32, posts: &[ScoredPost]) -> Result<usize, String> {
+    let settings = cfg().webhooks.clone();
+    let oldest = log_start(settings.keep_days);
+
+    let mut queued = 0;
+    for webhook in db::get_webhooks(account_id)? {
+        let delivered = db::get_delivered_posts(webhook.id)?;
+        // Older posts may have dropped out of the trimmed delivery log and would be sent twice.
+        let matches = posts.iter().filter(|sp| {
+            wants(&webhook, sp) && sp.post.created_at > oldest && !delivered.contains(&sp.post.id)
+        });
+        for sp in matches {
+            if db::queue_webhook_post(webhook.id, sp)? {
+                queued += 1;
+            }
+        }
+
+        // Each webhook sends on its own task, so a slow one holds up no other.
+        if let Some(guard) = SendGuard::acquire(webhook.id) {
+            let id = format!("webhook-{}", webhook.id);
+            tokio::spawn(logging::scoped(id, send_queued(webhook, guard)));
+        }
+    }
+    Ok(queued)
+}
+
+/// Delivers the oldest queued posts of the webhook. Posts that fail stay queued for the
+/// next run, up to `webhooks.retry_runs` runs.
+async fn send_queued(webhook: Webhook, _guard: SendGuard) {
+    let settings = cfg().webhooks.clone();
+    let queue = match db::get_webhook_queue(webhook.id, MAX_PER_RUN) {
+        Ok(queue) => queue,
+        Err(e) => {
+            warn!("Could not read the queue of webhook {}: {e}", webhook.id);
+            return;
+        }
+    };
+
+    for sp in &queue {
+        let result = match deliver(&webhook, MATCHED_EVENT, Some(sp)).await {
+            Ok(delivery) if delivery.succeeded() => db::dequeue_webhook_post(webhook.id, sp.post.id),
+            Ok(delivery) => {
+                let error = delivery.error.as_deref().unwrap_or("unknown error");
+                warn!("Webhook {} failed for post {}: {error}", webhook.id, sp.post.id);
+                db::fail_webhook_post(webhook.id, sp.post.id, settings.retry_runs).map(|dropped| {
+                    if dropped {
+                        warn!("Gave up on post {} for webhook {}", sp.post.id, webhook.id);
+                    }
+                })
+            }
+            Err(e) => Err(e),
+        };
+        if let Err(e) = result {
+            warn!("Webhook {} delivery of post {} failed: {e}", webhook.id, sp.post.id);
+        }
+    }
+
+    if let Err(e) = db::prune_webhook_deliveries(log_start(settings.keep_days)) {
+        warn!("Could not prune webhook deliveries: {e}");
+    }
+}
+
+/// Start of the kept delivery log.
+fn log_start(keep_days: f32) -> DateTime<Utc> {
+    Utc::now() - chrono::Duration::seconds((keep_days * 86_400.0) as i64)
+}
+
+fn wants(webhook: &Webhook, sp: &ScoredPost) -> bool {
+    sp.score >= webhook.min_score
+        && (webhook.ratings.is_empty() || webhook.ratings.contains(&sp.post.rating))
+        && sp.post.created_at > webhook.created_at
+}
+
+/// Sends a sample payload built from the account's latest favorite, if it has one.
+pub async fn send_test(webhook: &Webhook) -> Result<WebhookDelivery, String> {
+    let sample = db::get_account_favorites(webhook.account_id)?
+        .first()
+        .map(|(id, _)| db::get_stored_posts(&[*id]))
+        .transpose()?
+        .and_then(|posts| posts.into_iter().next())
+        .map(|post| ScoredPost {
+            post,
+            score: 1.0,
+            related: Vec::new(),
+        });
+
+    deliver(webhook, TEST_EVENT, sample.as_ref()).await
+}
+
+/// POSTs the payload, retrying 429, 5xx and connection errors up to `webhooks.attempts`
+/// times. Every attempt is logged; the last one is returned.
+async fn deliver(
+    webhook: &Webhook,
+    event: &str,
+    sp: Option<&ScoredPost>,
+) -> Result<WebhookDelivery, String> {
+    let settings = cfg().webhooks.clone();
+    let body = rocket::serde::json::to_string(&payload(webhook, event, sp))
+        .map_err(|e| format!("Failed to serialize webhook payload: {e}"))?;
+    let signature = webhook.secret.as_deref().map(|secret| sign(secret, &body));
+    let timeout = Duration::from_secs_f32(settings.timeout_seconds.max(0.1));
+
+    // Checked again on every delivery: the resolver never sees IP addresses, and the
+    // webhook may predate a change to `allowed_hosts`.
+    let blocked = validate_url(&webhook.url).await.err();
+
+    let mut delay = RETRY_DELAY;
+    let mut attempt = 1;
+    loop {
+        debug!("POST webhook {} {event} to {} (attempt {attempt})", webhook.id, origin(&webhook.url));
+        let result = match &blocked {
+            Some(e) => Err(e.clone()),
+            None => send(&webhook.url, event, &body, signature.as_deref(), timeout).await,
+        };
+        let label = result.as_ref().map_or("error", |status| status.as_str());
+        metrics::inc("webhook_requests_total", &[("status", label)]);
+
+        let (status, error) = match result {
+            Ok(status) if status.is_success() => (Some(status.as_u16()), None),
+            Ok(status) => (Some(status.as_u16()), Some(format!("Webhook answered {status}"))),
+            Err(e) => (None, Some(e)),
+        };
+        let mut delivery = WebhookDelivery {
+            id: 0,
+            webhook_id: webhook.id,
+            event: event.to_string(),
+            post_id: sp.map(|sp| sp.post.id),
+            attempt,
+            status,
+            error,
+            delivered_at: Utc::now(),
+        };
+        delivery.id = db::save_webhook_delivery(&delivery)?;
+
+        let retryable = blocked.is_none() && status.is_none_or(|s| s == 429 || s >= 500);
+        if !retryable || attempt >= settings.attempts {
+            return Ok(delivery);
+        }
+        metrics::inc("webhook_retries_total", &[]);
+        sleep(delay).await;
+        delay = delay.saturating_mul(2);
+        attempt += 1;
+    }
+}
+
+async fn send(
+    url: &str,
+    event: &str,
+    body: &str,
+    signature: Option<&str>,
+    timeout: Duration,
+) -> Result<StatusCode, String> {
+    let mut builder = CLIENT
+        .post(url)
+        .timeout(timeout)
+        .header("Content-Type", "application/json")
+        .header("X-Webhook-Event", event)
+        .body(body.to_string());
+    if let Some(signature) = signature {
+        builder = builder.header("X-Webhook-Signature", format!("sha256={signature}"));
+    }
+
+    builder
+        .send()
+        .await
+        .map(|resp| resp.status())
+        .map_err(|e| format!("Webhook request failed: {}", e.without_url()))
+}
+
+fn payload(webhook: &Webhook, event: &str, sp: Option<&ScoredPost>) -> WebhookPayload {
+    let domain = cfg().posts_domain.clone();
+    let post = sp.map(|sp| {
+        let post = &sp.post;
+        WebhookPost {
+            id: post.id,
+            url: format!("{domain}/posts/{}", post.id),
+            score: sp.score,
+            rating: post.rating,
+            created_at: post.created_at,
+            preview_url: post.preview.as_ref().and_then(|p| p.url.clone()),
+            file_url: post.file.as_ref().and_then(|f| f.url.clone()),
+            artists: post.tags.artist.clone(),
+            tags: post.tags.general.clone(),
+        }
+    });
+
+    let text = match &post {
+        Some(p) if event == TEST_EVENT => format!("Test delivery, sample post: {}", p.url),
+        Some(p) => format!("New match ({:.2}) by {}: {}", p.score, artists(&p.artists), p.url),
+        None => "Test delivery".to_string(),
+    };
+
+    WebhookPayload {
+        event: event.to_string(),
+        account_id: webhook.account_id,
+        webhook_id: webhook.id,
+        content: text.clone(),
+        text,
+        post,
+    }
+}
+
+fn artists(names: &[String]) -> String {
+    if names.is_empty() {
+        "unknown artist".to_string()
+    } else {
+        names.join(", ")
+    }
+}
+
+/// Hex HMAC-SHA256 of the body, sent as `X-Webhook-Signature: sha256=<hex>`.
+fn sign(secret: &str, body: &str) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(body.as_bytes());
+    hex::encode(mac.finalize().into_bytes())
+}
+
+/// Rejects webhook URLs that could never be delivered and, unless their host is in
+/// `webhooks.allowed_hosts`, ones that resolve to loopback, private, link-local or other
+/// non-public addresses; users could otherwise probe the server's network with them.
+pub async fn validate_url(url: &str) -> Result<(), String> {
+    let url = url.trim();
+    // Errors are logged, so they name the host at most: chat webhook URLs carry their
+    // secret in the path.
+    let parsed = Url::parse(url).map_err(|e| format!("Invalid webhook URL: {e}"))?;
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return Err(format!("Webhook URL must start with http:// or https://, got {}:", parsed.scheme()));
+    }
+    let host = parsed
+        .host_str()
+        .ok_or_else(|| "Webhook URL has no host".to_string())?
+        .trim_start_matches('[')
+        .trim_end_matches(']');
+    if is_allowed_host(host) {
+        return Ok(());
+    }
+    let port = parsed.port_or_known_default().unwrap_or(80);
+    let addrs = tokio::net::lookup_host((host, port))
+        .await
+        .map_err(|e| format!("Webhook host '{host}' could not be resolved: {e}"))?;
+    for addr in addrs {
+        if !is_public(addr.ip()) {
+            return Err(format!(
+                "Webhook host '{host}' resolves to {}, which is not a public address",
+                addr.ip()
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Scheme, host and port of a webhook URL, safe to log.
+fn origin(url: &str) -> String {
+    Url::parse(url).map_or_else(|_| "an invalid URL".to_string(), |u| u.origin().ascii_serialization())
+}
+
+fn is_allowed_host(host: &str) -> bool {
+    cfg().webhooks.allowed_hosts.iter().any(|h| h.eq_ignore_ascii_case(host))
+}
+
+fn is_public(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let [a, b, ..] = ip.octets();
+            !(ip.is_loopback()
+                || ip.is_private()
+                || ip.is_link_local()
+                || ip.is_unspecified()
+                || ip.is_broadcast()
+                || ip.is_multicast()
+                || ip.is_documentation()
+                || a == 0
+                // Shared address space used by carrier-grade NAT, 100.64.0.0/10.
+                || (a == 100 && (64..128).contains(&b)))
+        }
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(v4) => is_public(IpAddr::V4(v4)),
+            None => {
+                let first = ip.segments()[0];
+                // Unique local fc00::/7 and link-local fe80::/10.
+                !(ip.is_loopback()
+                    || ip.is_unspecified()
+                    || ip.is_multicast()
+                    || first & 0xfe00 == 0xfc00
+                    || first & 0xffc0 == 0xfe80)
+            }
+        },
+    }
+}
+
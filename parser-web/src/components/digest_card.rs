@@ -0,0 +1,86 @@
+use reqwasm::http::Request;
+use yew::prelude::*;
+
+use crate::components::fallback_image_url;
+use crate::models::{Digest, read_config_from_head, with_access_token, with_auth};
+
+#[derive(Properties, PartialEq)]
+pub struct DigestCardProps {
+    pub account_id: Option<i64>,
+}
+
+/// Newest posts found by the last background sync; hidden until the first one ran.
+#[function_component(DigestCard)]
+pub fn digest_card(props: &DigestCardProps) -> Html {
+    let cfg = read_config_from_head().unwrap();
+    let digest = use_state(|| Option::<Digest>::None);
+
+    {
+        let digest = digest.clone();
+        let api_base = cfg.backend_domain.clone();
+
+        use_effect_with(props.account_id, move |id| {
+            digest.set(None);
+            if let Some(id) = *id {
+                wasm_bindgen_futures::spawn_local(async move {
+                    let url = format!("{api_base}/account/{id}/digest");
+                    if let Ok(response) = with_auth(Request::get(&url)).send().await
+                        && response.ok()
+                        && let Ok(d) = response.json::<Digest>().await
+                    {
+                        digest.set(Some(d));
+                    }
+                });
+            }
+            || ()
+        });
+    }
+
+    let Some(d) = &*digest else {
+        return html! {};
+    };
+    let feed_url = with_access_token(&format!("{}/feed/{}.atom?digest=true", cfg.backend_domain, d.account_id));
+
+    html! {
+        <div class="card shadow-sm my-3" id="feed-digest">
+            <div class="card-header d-flex justify-content-between align-items-center">
+                <span>
+                    <strong>{ "New since last sync" }</strong>
+                    <span class="text-muted small ms-2">{ d.created_at.format("%Y-%m-%d %H:%M").to_string() }</span>
+                </span>
+                <a class="btn btn-sm btn-outline-secondary" href={feed_url} target="_blank" title="Atom feed of digests">
+                    <i class="bi bi-rss"></i>
+                </a>
+            </div>
+            <div class="card-body">
+                {
+                    if d.posts.is_empty() {
+                        html! { <p class="text-muted small mb-0">{ "Nothing new above the digest threshold." }</p> }
+                    } else {
+                        html! {
+                            <div class="d-flex flex-wrap gap-2">
+                                {
+                                    for d.posts.iter().map(|sp| {
+                                        let thumb = fallback_image_url(&sp.post);
+                                        let title = format!("#{} · {:.2}", sp.post.id, sp.score);
+                                        html! {
+                                            <a href={format!("{}/posts/{}", cfg.posts_domain, sp.post.id)} target="_blank" title={title.clone()}>
+                                                {
+                                                    if thumb.is_empty() {
+                                                        html! { <span class="badge bg-secondary">{ title }</span> }
+                                                    } else {
+                                                        html! { <img src={thumb} class="rounded" style="height: 96px;" loading="lazy" alt={title} /> }
+                                                    }
+                                                }
+                                            </a>
+                                        }
+                                    })
+                                }
+                            </div>
+                        }
+                    }
+                }
+            </div>
+        </div>
+    }
+}
@@ -3,6 +3,7 @@ use yew::{
     Callback, Html, MouseEvent, Properties, UseStateHandle, function_component, html, use_state,
 };
 
+use crate::models::with_auth;
 use crate::pages::{TagCount, UserInfo};
 
 #[derive(Properties, PartialEq)]
@@ -46,7 +47,7 @@ pub fn fetch_analyze_button(props: &AnalyzeButtonProps) -> Html {
             error.set(None);
 
             wasm_bindgen_futures::spawn_local(async move {
-                match Request::get(&format!("{}/account/{}/tag_counts", &api_base, user_id))
+                match with_auth(Request::get(&format!("{}/account/{}/tag_counts", &api_base, user_id)))
                     .send()
                     .await
                 {
@@ -108,7 +109,7 @@ pub fn fetch_analyze_button(props: &AnalyzeButtonProps) -> Html {
             error.set(None);
 
             wasm_bindgen_futures::spawn_local(async move {
-                match Request::post(&format!("{}/process/{}", &api_base, user_id))
+                match with_auth(Request::post(&format!("{}/process/{}", &api_base, user_id)))
                     .send()
                     .await
                 {
@@ -1,6 +1,7 @@
 use crate::ThemeToggle;
-use yew::{Html, classes, function_component, html};
-use crate::models::{read_config_from_head, start_tour, AttachTo, Button, Step};
+use reqwasm::http::Request;
+use yew::{Callback, Html, MouseEvent, classes, function_component, html};
+use crate::models::{clear_login, current_user, read_config_from_head, start_tour, with_auth, AttachTo, Button, Step};
 
 #[function_component(Header)]
 pub fn header() -> Html {
@@ -184,6 +185,20 @@ pub fn header() -> Html {
         }
     }
 
+    let user = current_user();
+    let on_logout = Callback::from(|e: MouseEvent| {
+        e.prevent_default();
+        let api_base = read_config_from_head().unwrap().backend_domain;
+        wasm_bindgen_futures::spawn_local(async move {
+            // The token is dropped locally even if the server can't be reached.
+            let _ = with_auth(Request::post(&format!("{api_base}/auth/logout"))).send().await;
+            clear_login();
+            if let Some(w) = web_sys::window() {
+                let _ = w.location().set_href("/login");
+            }
+        });
+    });
+
     html! {
         <nav class="navbar bg-body-tertiary border flex-nowrap" id="header">
             <div class="container-fluid d-flex align-items-center gap-3 flex-nowrap">
@@ -218,8 +233,59 @@ pub fn header() -> Html {
                             {"Feed"}
                         </a>
                     </li>
+                    <li class="nav-item">
+                        <a
+                            class={classes!("nav-link", is_active("/compare").then_some("active"))}
+                            aria-current={is_active("/compare").then_some("page")}
+                            href="/compare"
+                        >
+                            {"Compare"}
+                        </a>
+                    </li>
+                    <li class="nav-item">
+                        <a
+                            class={classes!("nav-link", is_active("/artists").then_some("active"))}
+                            aria-current={is_active("/artists").then_some("page")}
+                            href="/artists"
+                        >
+                            {"Artists"}
+                        </a>
+                    </li>
+                    <li class="nav-item">
+                        <a
+                            class={classes!("nav-link", is_active("/pools").then_some("active"))}
+                            aria-current={is_active("/pools").then_some("page")}
+                            href="/pools"
+                        >
+                            {"Pools"}
+                        </a>
+                    </li>
                 </ul>
-                <ul class="navbar-nav flex-row ms-auto flex-nowrap">
+                <ul class="navbar-nav flex-row ms-auto flex-nowrap gap-2 align-items-center">
+                    {
+                        match &user {
+                            Some(u) => html! {
+                                <>
+                                    <li class="nav-item navbar-text text-nowrap">
+                                        <i class="bi bi-person me-1"></i>{ &u.username }
+                                    </li>
+                                    <li class="nav-item">
+                                        <a class="nav-link" href="/login" onclick={on_logout}>{"Log out"}</a>
+                                    </li>
+                                </>
+                            },
+                            None => html! {
+                                <li class="nav-item">
+                                    <a
+                                        class={classes!("nav-link", is_active("/login").then_some("active"))}
+                                        href="/login"
+                                    >
+                                        {"Log in"}
+                                    </a>
+                                </li>
+                            },
+                        }
+                    }
                     <li class="nav-item">
                         <ThemeToggle />
                     </li>
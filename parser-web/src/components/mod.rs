@@ -1,17 +1,29 @@
+pub mod digest_card;
 pub mod header;
 pub mod theme_toggle;
+pub mod topics_card;
 pub mod post_card;
+pub mod related_tags_panel;
 pub mod saved_accounts_select;
+pub mod scoring_profile_card;
 pub mod fetch_analyze_button;
 pub mod tag_chart_card;
+pub mod tag_timeline_card;
 pub mod user_info_alert;
 pub mod user_search_form;
+pub mod webhooks_card;
 
+pub use digest_card::*;
 pub use header::*;
 pub use theme_toggle::*;
+pub use topics_card::*;
 pub use post_card::*;
+pub use related_tags_panel::*;
 pub use saved_accounts_select::*;
+pub use scoring_profile_card::*;
 pub use fetch_analyze_button::*;
 pub use tag_chart_card::*;
+pub use tag_timeline_card::*;
 pub use user_info_alert::*;
-pub use user_search_form::*;
\ No newline at end of file
+pub use user_search_form::*;
+pub use webhooks_card::*;
\ No newline at end of file
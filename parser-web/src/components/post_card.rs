@@ -12,6 +12,8 @@ pub struct PostCardProps {
     pub affinity: f32,
     #[prop_or_default]
     pub alt: Option<AttrValue>,
+    #[prop_or_default]
+    pub related: Rc<Vec<ScoredPost>>,
 }
 
 #[function_component(PostCard)]
@@ -19,6 +21,7 @@ pub fn post_card(props: &PostCardProps) -> Html {
     let post = &props.post;
 
     let root_ref = use_node_ref();
+    let expanded = use_state(|| false);
     let current_img_url = {
         let url = fallback_image_url(post);
         let initial = Some(AttrValue::from(url.clone()));
@@ -160,6 +163,65 @@ pub fn post_card(props: &PostCardProps) -> Html {
         })
     };
 
+    let on_toggle_related = {
+        let expanded = expanded.clone();
+        Callback::from(move |e: MouseEvent| {
+            e.prevent_default();
+            e.stop_propagation();
+            expanded.set(!*expanded);
+        })
+    };
+
+    let related_html = if props.related.is_empty() {
+        html! {}
+    } else {
+        let posts_domain = read_config_from_head().unwrap().posts_domain;
+        html! {
+            <div class="px-2 pb-2">
+                <span
+                    class="badge bg-info text-dark"
+                    role="button"
+                    title="Show related posts"
+                    onmousedown={on_toggle_related}
+                >
+                    { if *expanded { "Hide related".to_string() } else { format!("+{} related", props.related.len()) } }
+                </span>
+                {
+                    if *expanded {
+                        html! {
+                            <div class="d-flex flex-wrap gap-1 mt-2">
+                                {
+                                    for props.related.iter().map(|sp| {
+                                        let href = format!("{posts_domain}/posts/{}", sp.post.id);
+                                        let thumb = fallback_image_url(&sp.post);
+                                        html! {
+                                            <a
+                                                href={href}
+                                                target="_blank"
+                                                title={format!("#{} ({:.2})", sp.post.id, sp.score)}
+                                                onmousedown={Callback::from(|e: MouseEvent| e.stop_propagation())}
+                                            >
+                                                {
+                                                    if thumb.is_empty() {
+                                                        html! { <span class="badge bg-secondary">{ format!("#{}", sp.post.id) }</span> }
+                                                    } else {
+                                                        html! { <img src={thumb} class="rounded" style="height: 48px;" loading="lazy" alt={format!("Post #{}", sp.post.id)} /> }
+                                                    }
+                                                }
+                                            </a>
+                                        }
+                                    })
+                                }
+                            </div>
+                        }
+                    } else {
+                        html! {}
+                    }
+                }
+            </div>
+        }
+    };
+
     let root_classes = classes!(
         "card",
         "h-100",
@@ -230,6 +292,7 @@ pub fn post_card(props: &PostCardProps) -> Html {
                     }
                 }
             </div>
+            { related_html }
         </>
     };
 
@@ -255,7 +318,7 @@ fn is_supported_image(url: &str) -> bool {
     ALLOWED.iter().any(|ext| url.to_ascii_lowercase().ends_with(ext))
 }
 
-fn fallback_image_url(post: &Post) -> String {
+pub fn fallback_image_url(post: &Post) -> String {
     if post.preview.clone().unwrap().url.is_some() && is_supported_image(&post.preview.clone().unwrap().url.unwrap()) {
         post.preview.clone().unwrap().url.unwrap()
     }
@@ -0,0 +1,169 @@
+use reqwasm::http::Request;
+use web_sys::HtmlSelectElement;
+use web_sys::js_sys::encode_uri_component;
+use yew::prelude::*;
+
+use crate::models::{RelatedTags, read_config_from_head, with_auth};
+
+const SORTS: [(&str, &str); 4] = [
+    ("jaccard", "Jaccard"),
+    ("lift", "Lift"),
+    ("pmi", "PMI"),
+    ("count", "Co-occurrences"),
+];
+
+#[derive(Properties, PartialEq)]
+pub struct RelatedTagsPanelProps {
+    pub tag: String,
+    pub group: String,
+    /// Account whose favorites form the default scope; the whole corpus without one.
+    #[prop_or_default]
+    pub account_id: Option<i64>,
+    /// Called with `(name, group)` when a related tag is clicked.
+    pub on_select: Callback<(String, String)>,
+}
+
+#[function_component(RelatedTagsPanel)]
+pub fn related_tags_panel(props: &RelatedTagsPanelProps) -> Html {
+    let whole_corpus = use_state(|| props.account_id.is_none());
+    let sort = use_state(|| "jaccard".to_string());
+    let data = use_state(|| Option::<RelatedTags>::None);
+    let is_loading = use_state(|| false);
+    let error = use_state(|| Option::<String>::None);
+
+    {
+        let data = data.clone();
+        let is_loading = is_loading.clone();
+        let error = error.clone();
+        let api_base = read_config_from_head().unwrap().backend_domain;
+        let account = if *whole_corpus { None } else { props.account_id };
+        let mut url = format!(
+            "{api_base}/tags/related?tag={}&group={}&sort={}",
+            String::from(encode_uri_component(&props.tag)),
+            String::from(encode_uri_component(&props.group)),
+            *sort
+        );
+        if let Some(id) = account {
+            url.push_str(&format!("&account_id={id}"));
+        }
+
+        use_effect_with(url, move |url| {
+            let url = url.clone();
+            is_loading.set(true);
+            error.set(None);
+            wasm_bindgen_futures::spawn_local(async move {
+                let result = async {
+                    let response = with_auth(Request::get(&url))
+                        .send()
+                        .await
+                        .map_err(|e| format!("Network error: {e}"))?;
+                    if !response.ok() {
+                        return Err(format!("Error {}", response.status()));
+                    }
+                    response
+                        .json::<RelatedTags>()
+                        .await
+                        .map_err(|e| format!("Failed to parse response: {e}"))
+                }
+                .await;
+
+                match result {
+                    Ok(r) => data.set(Some(r)),
+                    Err(e) => error.set(Some(e)),
+                }
+                is_loading.set(false);
+            });
+            || ()
+        });
+    }
+
+    let on_sort_change = {
+        let sort = sort.clone();
+        Callback::from(move |e: Event| {
+            let select: HtmlSelectElement = e.target_unchecked_into();
+            sort.set(select.value());
+        })
+    };
+
+    let scope_button = |corpus: bool, label: &str| {
+        let whole_corpus = whole_corpus.clone();
+        html! {
+            <button
+                class={classes!("btn", "btn-sm", "btn-outline-secondary", (*whole_corpus == corpus).then_some("active"))}
+                disabled={!corpus && props.account_id.is_none()}
+                onclick={Callback::from(move |_| whole_corpus.set(corpus))}
+            >
+                { label.to_string() }
+            </button>
+        }
+    };
+
+    html! {
+        <div class="border-top pt-3 mt-3">
+            <div class="d-flex flex-wrap align-items-center gap-2 mb-2">
+                <h6 class="mb-0 me-auto">{ format!("Related to {}", props.tag) }</h6>
+                <div class="btn-group" role="group" aria-label="Scope">
+                    { scope_button(false, "Favorites") }
+                    { scope_button(true, "All posts") }
+                </div>
+                <select class="form-select form-select-sm w-auto" onchange={on_sort_change}>
+                    {
+                        for SORTS.iter().map(|(value, label)| html! {
+                            <option value={*value} selected={*sort == *value}>{ *label }</option>
+                        })
+                    }
+                </select>
+            </div>
+
+            {
+                if let Some(err) = &*error {
+                    html! { <div class="alert alert-danger">{ err }</div> }
+                } else if *is_loading {
+                    html! { <div class="text-muted small">{ "Loading..." }</div> }
+                } else if let Some(r) = &*data {
+                    html! {
+                        <>
+                            <p class="text-muted small mb-2">
+                                { format!("{} of {} posts carry this tag", r.tag_posts, r.scope_posts) }
+                            </p>
+                            <div class="table-responsive">
+                                <table class="table table-sm table-hover mb-0">
+                                    <thead>
+                                        <tr>
+                                            <th>{"Tag"}</th>
+                                            <th>{"Group"}</th>
+                                            <th class="text-end">{"Together"}</th>
+                                            <th class="text-end">{"Lift"}</th>
+                                            <th class="text-end">{"PMI"}</th>
+                                            <th class="text-end">{"Jaccard"}</th>
+                                        </tr>
+                                    </thead>
+                                    <tbody>
+                                        {
+                                            for r.related.iter().map(|t| {
+                                                let on_select = props.on_select.clone();
+                                                let key = (t.name.clone(), t.group_type.clone());
+                                                html! {
+                                                    <tr role="button" onclick={Callback::from(move |_| on_select.emit(key.clone()))}>
+                                                        <td>{ &t.name }</td>
+                                                        <td class="text-muted">{ &t.group_type }</td>
+                                                        <td class="text-end">{ format!("{} / {}", t.together, t.count) }</td>
+                                                        <td class="text-end">{ format!("{:.2}", t.lift) }</td>
+                                                        <td class="text-end">{ format!("{:.2}", t.pmi) }</td>
+                                                        <td class="text-end">{ format!("{:.3}", t.jaccard) }</td>
+                                                    </tr>
+                                                }
+                                            })
+                                        }
+                                    </tbody>
+                                </table>
+                            </div>
+                        </>
+                    }
+                } else {
+                    html! {}
+                }
+            }
+        </div>
+    }
+}
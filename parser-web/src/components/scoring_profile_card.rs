@@ -0,0 +1,350 @@
+use reqwasm::http::Request;
+use std::collections::BTreeMap;
+use serde::{Serialize, de::DeserializeOwned};
+use web_sys::{HtmlInputElement, HtmlSelectElement, HtmlTextAreaElement};
+use yew::prelude::*;
+
+use crate::components::SavedAccountsSelect;
+use crate::models::{AccountScoring, PriorsOverride, ScoringProfile, with_auth};
+use crate::pages::UserInfo;
+
+const MODELS: [(&str, &str); 3] = [
+    ("cosine", "TF-IDF cosine"),
+    ("bm25", "BM25"),
+    ("naive_bayes", "Naive Bayes log-odds"),
+];
+
+type PriorField = (&'static str, fn(&mut PriorsOverride) -> &mut Option<f32>);
+
+const PRIORS: [PriorField; 6] = [
+    ("mix_sim", |p| &mut p.mix_sim),
+    ("mix_quality", |p| &mut p.mix_quality),
+    ("mix_recency", |p| &mut p.mix_recency),
+    ("mix_collab", |p| &mut p.mix_collab),
+    ("freq_alpha", |p| &mut p.freq_alpha),
+    ("recency_tau_days", |p| &mut p.recency_tau_days),
+];
+
+#[derive(Properties, PartialEq)]
+pub struct ScoringProfileCardProps {
+    pub api_base: String,
+}
+
+#[function_component(ScoringProfileCard)]
+pub fn scoring_profile_card(props: &ScoringProfileCardProps) -> Html {
+    let selected_user = use_state(|| Option::<UserInfo>::None);
+    let is_loading = use_state(|| false);
+    let profiles = use_state(BTreeMap::<String, ScoringProfile>::new);
+    let groups = use_state(Vec::<String>::new);
+    let scoring = use_state(|| Option::<AccountScoring>::None);
+    let dislikes = use_state(String::new);
+    let message = use_state(|| Option::<(bool, String)>::None);
+
+    {
+        let profiles = profiles.clone();
+        let groups = groups.clone();
+        let api_base = props.api_base.clone();
+        use_effect_with((), move |_| {
+            wasm_bindgen_futures::spawn_local(async move {
+                if let Ok(p) = get_json(&format!("{api_base}/profiles")).await {
+                    profiles.set(p);
+                }
+                if let Ok(g) = get_json(&format!("{api_base}/tag_groups")).await {
+                    groups.set(g);
+                }
+            });
+            || ()
+        });
+    }
+
+    {
+        let scoring = scoring.clone();
+        let dislikes = dislikes.clone();
+        let message = message.clone();
+        let api_base = props.api_base.clone();
+        let user_id = selected_user.as_ref().map(|u| u.id);
+
+        use_effect_with(user_id, move |user_id| {
+            scoring.set(None);
+            dislikes.set(String::new());
+            message.set(None);
+            if let Some(id) = *user_id {
+                wasm_bindgen_futures::spawn_local(async move {
+                    let result = async {
+                        let s: AccountScoring =
+                            get_json(&format!("{api_base}/account/{id}/scoring")).await?;
+                        let d: Vec<String> =
+                            get_json(&format!("{api_base}/account/{id}/dislikes")).await?;
+                        Ok::<_, String>((s, d))
+                    }
+                    .await;
+
+                    match result {
+                        Ok((s, d)) => {
+                            scoring.set(Some(s));
+                            dislikes.set(d.join("\n"));
+                        }
+                        Err(e) => message.set(Some((true, e))),
+                    }
+                });
+            }
+            || ()
+        });
+    }
+
+    let update = {
+        let scoring = scoring.clone();
+        Callback::from(move |f: Box<dyn FnOnce(&mut AccountScoring)>| {
+            if let Some(current) = &*scoring {
+                let mut next = current.clone();
+                f(&mut next);
+                scoring.set(Some(next));
+            }
+        })
+    };
+
+    let on_save = {
+        let scoring = scoring.clone();
+        let dislikes = dislikes.clone();
+        let message = message.clone();
+        let is_loading = is_loading.clone();
+        let api_base = props.api_base.clone();
+        let user_id = selected_user.as_ref().map(|u| u.id);
+
+        Callback::from(move |_: MouseEvent| {
+            let (Some(id), Some(body)) = (user_id, (*scoring).clone()) else {
+                return;
+            };
+            let disliked: Vec<String> = dislikes
+                .lines()
+                .map(str::trim)
+                .filter(|t| !t.is_empty())
+                .map(str::to_string)
+                .collect();
+            let message = message.clone();
+            let is_loading = is_loading.clone();
+            let api_base = api_base.clone();
+            is_loading.set(true);
+
+            wasm_bindgen_futures::spawn_local(async move {
+                let result = async {
+                    put_json(&format!("{api_base}/account/{id}/scoring"), &body).await?;
+                    put_json(&format!("{api_base}/account/{id}/dislikes"), &disliked).await
+                }
+                .await;
+
+                match result {
+                    Ok(()) => message.set(Some((false, "Scoring profile saved".into()))),
+                    Err(e) => message.set(Some((true, e))),
+                }
+                is_loading.set(false);
+            });
+        })
+    };
+
+    let on_dislikes_change = {
+        let dislikes = dislikes.clone();
+        Callback::from(move |e: Event| {
+            let input: HtmlTextAreaElement = e.target_unchecked_into();
+            dislikes.set(input.value());
+        })
+    };
+
+    let on_profile_change = {
+        let update = update.clone();
+        Callback::from(move |e: Event| {
+            let select: HtmlSelectElement = e.target_unchecked_into();
+            let value = select.value();
+            update.emit(Box::new(move |s: &mut AccountScoring| {
+                s.profile = (!value.is_empty()).then_some(value);
+            }));
+        })
+    };
+
+    let on_model_change = {
+        let update = update.clone();
+        Callback::from(move |e: Event| {
+            let select: HtmlSelectElement = e.target_unchecked_into();
+            let value = select.value();
+            update.emit(Box::new(move |s: &mut AccountScoring| {
+                s.overrides.model = (!value.is_empty()).then_some(value);
+            }));
+        })
+    };
+
+    let number_input = |id: String, value: Option<f32>, onchange: Callback<Option<f32>>| {
+        html! {
+            <input
+                type="number"
+                step="0.05"
+                class="form-control form-control-sm"
+                id={id}
+                placeholder="inherit"
+                value={value.map(|v| v.to_string()).unwrap_or_default()}
+                disabled={*is_loading}
+                onchange={Callback::from(move |e: Event| {
+                    let input: HtmlInputElement = e.target_unchecked_into();
+                    onchange.emit(input.value().trim().parse::<f32>().ok());
+                })}
+            />
+        }
+    };
+
+    let body = match &*scoring {
+        None => html! {
+            <p class="text-muted small mb-0">{"Select an account to edit how its feed is scored."}</p>
+        },
+        Some(s) => html! {
+            <>
+                <div class="mb-3">
+                    <label for="scoring-profile" class="form-label">{"Profile"}</label>
+                    <select id="scoring-profile" class="form-select" onchange={on_profile_change} disabled={*is_loading}>
+                        <option value="" selected={s.profile.is_none()}>{"Default"}</option>
+                        {
+                            for profiles.keys().map(|name| html! {
+                                <option value={name.clone()} selected={s.profile.as_deref() == Some(name.as_str())}>
+                                    { name }
+                                </option>
+                            })
+                        }
+                    </select>
+                </div>
+
+                <div class="mb-3">
+                    <label for="scoring-model" class="form-label">{"Ranking model"}</label>
+                    <select id="scoring-model" class="form-select" onchange={on_model_change} disabled={*is_loading}>
+                        <option value="" selected={s.overrides.model.is_none()}>{"From profile"}</option>
+                        {
+                            for MODELS.iter().map(|(value, label)| html! {
+                                <option value={*value} selected={s.overrides.model.as_deref() == Some(*value)}>
+                                    { *label }
+                                </option>
+                            })
+                        }
+                    </select>
+                </div>
+
+                <h6>{"Group weights"}</h6>
+                <div class="row g-2 mb-3">
+                    {
+                        for groups.iter().map(|group| {
+                            let key = group.clone();
+                            let update = update.clone();
+                            let onchange = Callback::from(move |v: Option<f32>| {
+                                let key = key.clone();
+                                update.emit(Box::new(move |s: &mut AccountScoring| {
+                                    match v {
+                                        Some(v) => s.overrides.group_weights.insert(key, v),
+                                        None => s.overrides.group_weights.remove(&key),
+                                    };
+                                }));
+                            });
+                            html! {
+                                <div class="col-6 col-md-4">
+                                    <label class="form-label small" for={format!("weight-{group}")}>{ group }</label>
+                                    { number_input(format!("weight-{group}"), s.overrides.group_weights.get(group).copied(), onchange) }
+                                </div>
+                            }
+                        })
+                    }
+                </div>
+
+                <h6>{"Priors"}</h6>
+                <div class="row g-2 mb-3">
+                    {
+                        for PRIORS.iter().map(|(name, field)| {
+                            let field = *field;
+                            let update = update.clone();
+                            let onchange = Callback::from(move |v: Option<f32>| {
+                                update.emit(Box::new(move |s: &mut AccountScoring| {
+                                    *field(&mut s.overrides.priors) = v;
+                                }));
+                            });
+                            let current = *field(&mut s.overrides.priors.clone());
+                            html! {
+                                <div class="col-6 col-md-4">
+                                    <label class="form-label small" for={format!("prior-{name}")}>{ *name }</label>
+                                    { number_input(format!("prior-{name}"), current, onchange) }
+                                </div>
+                            }
+                        })
+                    }
+                </div>
+
+                <div class="mb-3">
+                    <label for="scoring-dislikes" class="form-label">{"Disliked tags"}</label>
+                    <textarea
+                        class="form-control"
+                        id="scoring-dislikes"
+                        rows="3"
+                        value={(*dislikes).clone()}
+                        onchange={on_dislikes_change}
+                        placeholder="One tag per line; posts carrying them rank lower"
+                        disabled={*is_loading}
+                    />
+                </div>
+
+                <button class="btn btn-primary w-100" onclick={on_save} disabled={*is_loading}>
+                    {"Save Scoring"}
+                </button>
+            </>
+        },
+    };
+
+    html! {
+        <div class="card shadow mt-4">
+            <div class="card-body">
+                <h2 class="card-title text-center mb-4">{"Scoring Profile"}</h2>
+                <SavedAccountsSelect selected_user={selected_user.clone()} is_loading={is_loading.clone()} />
+                { body }
+                {
+                    if let Some((is_error, text)) = &*message {
+                        html! {
+                            <div class={classes!("alert", "mt-3", if *is_error { "alert-danger" } else { "alert-success" })}>
+                                { text }
+                            </div>
+                        }
+                    } else {
+                        html! {}
+                    }
+                }
+            </div>
+        </div>
+    }
+}
+
+async fn get_json<T: DeserializeOwned>(url: &str) -> Result<T, String> {
+    let response = with_auth(Request::get(url))
+        .send()
+        .await
+        .map_err(|e| format!("Network error: {e}"))?;
+    if !response.ok() {
+        return Err(error_text(response).await);
+    }
+    response
+        .json::<T>()
+        .await
+        .map_err(|e| format!("Failed to parse response: {e}"))
+}
+
+async fn put_json<T: Serialize>(url: &str, body: &T) -> Result<(), String> {
+    let response = with_auth(Request::put(url))
+        .header("Content-Type", "application/json")
+        .body(serde_json::to_string(body).unwrap())
+        .send()
+        .await
+        .map_err(|e| format!("Network error: {e}"))?;
+    if !response.ok() {
+        return Err(error_text(response).await);
+    }
+    Ok(())
+}
+
+async fn error_text(response: reqwasm::http::Response) -> String {
+    let status = response.status();
+    let text = response
+        .text()
+        .await
+        .unwrap_or_else(|_| "Unknown error".into());
+    format!("Error {status}: {text}")
+}
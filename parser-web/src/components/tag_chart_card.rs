@@ -1,12 +1,13 @@
 use crate::TagCount;
+use crate::components::RelatedTagsPanel;
 use wasm_bindgen::JsCast;
 use wasm_bindgen::prelude::Closure;
 use web_sys::{
     CanvasRenderingContext2d, HtmlCanvasElement, MutationObserver, MutationObserverInit, js_sys,
 };
 use yew::{
-    Callback, Html, NodeRef, Properties, classes, function_component, html, use_effect,
-    use_effect_with, use_state,
+    Callback, Html, MouseEvent, NodeRef, Properties, classes, function_component, hook, html,
+    use_effect, use_effect_with, use_state,
 };
 use yew::{UseStateHandle, use_memo};
 
@@ -14,13 +15,19 @@ use yew::{UseStateHandle, use_memo};
 pub struct TagChartCardProps {
     pub canvas_ref: NodeRef,
     pub tag_counts: UseStateHandle<Vec<TagCount>>,
+    /// Scope of the related-tags panel opened by clicking a bar.
+    #[prop_or_default]
+    pub account_id: Option<i64>,
 }
 
+const BAR_SPACING: f64 = 30.0;
+const TOP_PADDING: f64 = 30.0;
+
 #[function_component(TagChartCard)]
 pub fn tag_chart_card(props: &TagChartCardProps) -> Html {
-    let theme_trigger = use_state(|| 0);
     let selected_group = use_state(String::new);
-    let resize_trigger = use_state(|| 0);
+    let selected_tag = use_state(|| Option::<(String, String)>::None);
+    let redraw_trigger = use_chart_redraw_trigger();
 
     let current_tags = use_memo(
         (selected_group.clone(), props.tag_counts.clone()),
@@ -55,6 +62,107 @@ pub fn tag_chart_card(props: &TagChartCardProps) -> Html {
         },
     );
 
+    use_effect_with(
+        (
+            props.canvas_ref.clone(),
+            current_tags.clone(),
+            redraw_trigger,
+        ),
+        |(canvas_ref, current_tags, _)| {
+            if let Some(canvas) = canvas_ref.cast::<HtmlCanvasElement>() {
+                draw_chart(&canvas, current_tags);
+            }
+            || ()
+        },
+    );
+
+    let on_tab_click = {
+        let selected_group = selected_group.clone();
+        Callback::from(move |group: String| {
+            selected_group.set(group);
+        })
+    };
+
+    let on_chart_click = {
+        let selected_tag = selected_tag.clone();
+        let current_tags = current_tags.clone();
+        Callback::from(move |e: MouseEvent| {
+            let row = ((e.offset_y() as f64 - TOP_PADDING) / BAR_SPACING).floor();
+            if row >= 0.0
+                && let Some(tag) = current_tags.get(row as usize)
+            {
+                selected_tag.set(Some((tag.name.clone(), tag.group_type.clone())));
+            }
+        })
+    };
+
+    let on_related_select = {
+        let selected_tag = selected_tag.clone();
+        Callback::from(move |tag: (String, String)| selected_tag.set(Some(tag)))
+    };
+
+    if props.tag_counts.is_empty() {
+        return html! {};
+    }
+
+    html! {
+        <div class="card mt-4">
+            <div class="card-header bg-primary text-white">
+                <h5 class="mb-0">{"Tag Analysis"}</h5>
+            </div>
+            <div class="card-body">
+                <ul class="nav nav-tabs mb-3">
+                    {
+                        for group_types.iter().map(|group| {
+                            let is_active = *group == *selected_group;
+                            let group_clone = group.clone();
+                            html! {
+                                <li class="nav-item">
+                                    <button
+                                        class={classes!("nav-link", if is_active { "active" } else { "" })}
+                                        onclick={on_tab_click.reform(move |_| group_clone.clone())}
+                                    >
+                                        { group }
+                                    </button>
+                                </li>
+                            }
+                        })
+                    }
+                </ul>
+
+                <div class="chart-container" style="max-width: 100%;">
+                    <canvas
+                        ref={props.canvas_ref.clone()}
+                        style="display: block; width: 100%; cursor: pointer"
+                        title="Click a tag to see related tags"
+                        onclick={on_chart_click}
+                    />
+                </div>
+
+                {
+                    if let Some((tag, group)) = &*selected_tag {
+                        html! {
+                            <RelatedTagsPanel
+                                tag={tag.clone()}
+                                group={group.clone()}
+                                account_id={props.account_id}
+                                on_select={on_related_select}
+                            />
+                        }
+                    } else {
+                        html! {}
+                    }
+                }
+            </div>
+        </div>
+    }
+}
+
+#[hook]
+pub fn use_chart_redraw_trigger() -> (i32, i32) {
+    let theme_trigger = use_state(|| 0);
+    let resize_trigger = use_state(|| 0);
+
     use_effect({
         let theme_trigger = theme_trigger.clone();
         move || {
@@ -113,66 +221,7 @@ pub fn tag_chart_card(props: &TagChartCardProps) -> Html {
         }
     });
 
-    use_effect_with(
-        (
-            props.canvas_ref.clone(),
-            current_tags.clone(),
-            *theme_trigger,
-            *resize_trigger,
-        ),
-        |(canvas_ref, current_tags, _, _)| {
-            if let Some(canvas) = canvas_ref.cast::<HtmlCanvasElement>() {
-                draw_chart(&canvas, current_tags);
-            }
-            || ()
-        },
-    );
-
-    let on_tab_click = {
-        let selected_group = selected_group.clone();
-        Callback::from(move |group: String| {
-            selected_group.set(group);
-        })
-    };
-
-    if props.tag_counts.is_empty() {
-        return html! {};
-    }
-
-    html! {
-        <div class="card mt-4">
-            <div class="card-header bg-primary text-white">
-                <h5 class="mb-0">{"Tag Analysis"}</h5>
-            </div>
-            <div class="card-body">
-                <ul class="nav nav-tabs mb-3">
-                    {
-                        for group_types.iter().map(|group| {
-                            let is_active = *group == *selected_group;
-                            let group_clone = group.clone();
-                            html! {
-                                <li class="nav-item">
-                                    <button
-                                        class={classes!("nav-link", if is_active { "active" } else { "" })}
-                                        onclick={on_tab_click.reform(move |_| group_clone.clone())}
-                                    >
-                                        { group }
-                                    </button>
-                                </li>
-                            }
-                        })
-                    }
-                </ul>
-
-                <div class="chart-container" style="max-width: 100%;">
-                    <canvas
-                        ref={props.canvas_ref.clone()}
-                        style="display: block; width: 100%"
-                    />
-                </div>
-            </div>
-        </div>
-    }
+    (*theme_trigger, *resize_trigger)
 }
 
 fn draw_chart(canvas: &HtmlCanvasElement, tag_counts: &[TagCount]) {
@@ -184,8 +233,8 @@ fn draw_chart(canvas: &HtmlCanvasElement, tag_counts: &[TagCount]) {
         return;
     }
 
-    let bar_spacing = 30.0;
-    let top_padding = 30.0;
+    let bar_spacing = BAR_SPACING;
+    let top_padding = TOP_PADDING;
     let bottom_padding = 30.0;
 
     let ideal_height = top_padding + bottom_padding + (bar_spacing * tag_counts.len() as f64);
@@ -249,15 +298,7 @@ fn draw_chart(canvas: &HtmlCanvasElement, tag_counts: &[TagCount]) {
 
     let el: &web_sys::Element = canvas.as_ref();
 
-    let colors = [
-        get_css_variable_value_on(el, "--bs-primary").unwrap_or("#0d6efd".into()),
-        get_css_variable_value_on(el, "--bs-success").unwrap_or("#198754".into()),
-        get_css_variable_value_on(el, "--bs-info").unwrap_or("#0dcaf0".into()),
-        get_css_variable_value_on(el, "--bs-warning").unwrap_or("#ffc107".into()),
-        get_css_variable_value_on(el, "--bs-danger").unwrap_or("#dc3545".into()),
-        get_css_variable_value_on(el, "--bs-secondary").unwrap_or("#6c757d".into()),
-        get_css_variable_value_on(el, "--bs-dark").unwrap_or("#212529".into()),
-    ];
+    let colors = chart_colors(el);
     let text_color = get_css_variable_value_on(el, "--bs-body-color").unwrap_or("#212529".into());
 
     for (i, tag) in tag_counts.iter().enumerate() {
@@ -299,7 +340,19 @@ fn draw_chart(canvas: &HtmlCanvasElement, tag_counts: &[TagCount]) {
         .unwrap_or(());
 }
 
-fn get_css_variable_value_on(el: &web_sys::Element, var_name: &str) -> Option<String> {
+pub(crate) fn chart_colors(el: &web_sys::Element) -> [String; 7] {
+    [
+        get_css_variable_value_on(el, "--bs-primary").unwrap_or("#0d6efd".into()),
+        get_css_variable_value_on(el, "--bs-success").unwrap_or("#198754".into()),
+        get_css_variable_value_on(el, "--bs-info").unwrap_or("#0dcaf0".into()),
+        get_css_variable_value_on(el, "--bs-warning").unwrap_or("#ffc107".into()),
+        get_css_variable_value_on(el, "--bs-danger").unwrap_or("#dc3545".into()),
+        get_css_variable_value_on(el, "--bs-secondary").unwrap_or("#6c757d".into()),
+        get_css_variable_value_on(el, "--bs-dark").unwrap_or("#212529".into()),
+    ]
+}
+
+pub(crate) fn get_css_variable_value_on(el: &web_sys::Element, var_name: &str) -> Option<String> {
     let window = web_sys::window()?;
     let computed = window.get_computed_style(el).ok()??;
     computed
@@ -0,0 +1,349 @@
+use reqwasm::http::Request;
+use wasm_bindgen::JsCast;
+use web_sys::{CanvasRenderingContext2d, HtmlCanvasElement, HtmlSelectElement};
+use yew::{
+    Callback, Event, Html, Properties, TargetCast, UseStateHandle, classes, function_component,
+    html, use_effect_with, use_memo, use_node_ref, use_state,
+};
+
+use crate::components::tag_chart_card::{
+    chart_colors, get_css_variable_value_on, use_chart_redraw_trigger,
+};
+use crate::models::{TagTimeline, TagTrend, with_auth};
+use crate::pages::{TagCount, UserInfo};
+
+#[derive(Properties, PartialEq)]
+pub struct TagTimelineCardProps {
+    pub user: UseStateHandle<Option<UserInfo>>,
+    pub api_base: String,
+    pub tag_counts: UseStateHandle<Vec<TagCount>>,
+}
+
+#[derive(PartialEq)]
+struct Series {
+    name: String,
+    shares: Vec<f64>,
+}
+
+#[function_component(TagTimelineCard)]
+pub fn tag_timeline_card(props: &TagTimelineCardProps) -> Html {
+    let canvas_ref = use_node_ref();
+    let timeline = use_state(|| Option::<TagTimeline>::None);
+    let error = use_state(|| Option::<String>::None);
+    let period = use_state(|| "month".to_string());
+    let selected_group = use_state(|| "artist".to_string());
+    let redraw_trigger = use_chart_redraw_trigger();
+
+    {
+        let timeline = timeline.clone();
+        let error = error.clone();
+        let api_base = props.api_base.clone();
+        let user_id = props.user.as_ref().map(|u| u.id);
+
+        use_effect_with(
+            (user_id, props.tag_counts.len(), (*period).clone()),
+            move |(user_id, tag_count_len, period)| {
+                if let Some(user_id) = *user_id
+                    && *tag_count_len > 0
+                {
+                    let url = format!("{api_base}/account/{user_id}/tag_timeline?period={period}");
+
+                    wasm_bindgen_futures::spawn_local(async move {
+                        match with_auth(Request::get(&url)).send().await {
+                            Ok(response) => {
+                                if response.ok() {
+                                    match response.json::<TagTimeline>().await {
+                                        Ok(t) => {
+                                            timeline.set(Some(t));
+                                            error.set(None);
+                                        }
+                                        Err(e) => {
+                                            error.set(Some(format!(
+                                                "Failed to parse timeline data: {e}"
+                                            )));
+                                        }
+                                    }
+                                } else {
+                                    let status = response.status();
+                                    let text = response
+                                        .text()
+                                        .await
+                                        .unwrap_or_else(|_| "Unknown error".into());
+                                    error.set(Some(format!("Error {status}: {text}")));
+                                }
+                            }
+                            Err(e) => error.set(Some(format!("Network error: {e}"))),
+                        }
+                    });
+                } else {
+                    timeline.set(None);
+                }
+                || ()
+            },
+        );
+    }
+
+    let group_types = use_memo(timeline.clone(), |timeline| {
+        let mut groups: Vec<String> = timeline
+            .iter()
+            .flat_map(|t| t.buckets.iter())
+            .flat_map(|b| b.tags.iter().map(|tag| tag.group_type.clone()))
+            .collect();
+        groups.sort();
+        groups.dedup();
+        groups
+    });
+
+    let series = use_memo(
+        (timeline.clone(), selected_group.clone()),
+        |(timeline, group)| {
+            let Some(timeline) = &**timeline else {
+                return Vec::<Series>::new();
+            };
+            let mut series: Vec<Series> = Vec::new();
+            for (i, bucket) in timeline.buckets.iter().enumerate() {
+                for tag in bucket.tags.iter().filter(|t| t.group_type == **group) {
+                    let idx = match series.iter().position(|s| s.name == tag.name) {
+                        Some(idx) => idx,
+                        None => {
+                            series.push(Series {
+                                name: tag.name.clone(),
+                                shares: vec![0.0; timeline.buckets.len()],
+                            });
+                            series.len() - 1
+                        }
+                    };
+                    series[idx].shares[i] = tag.share;
+                }
+            }
+            series
+        },
+    );
+
+    {
+        let timeline = timeline.clone();
+        use_effect_with(
+            (canvas_ref.clone(), series.clone(), redraw_trigger),
+            move |(canvas_ref, series, _)| {
+                if let (Some(canvas), Some(timeline)) =
+                    (canvas_ref.cast::<HtmlCanvasElement>(), &*timeline)
+                {
+                    let labels: Vec<&str> =
+                        timeline.buckets.iter().map(|b| b.period.as_str()).collect();
+                    draw_timeline(&canvas, &labels, series);
+                }
+                || ()
+            },
+        );
+    }
+
+    let on_period_change = {
+        let period = period.clone();
+        Callback::from(move |e: Event| {
+            let select: HtmlSelectElement = e.target_unchecked_into();
+            period.set(select.value());
+        })
+    };
+
+    let on_tab_click = {
+        let selected_group = selected_group.clone();
+        Callback::from(move |group: String| selected_group.set(group))
+    };
+
+    let Some(data) = &*timeline else {
+        return html! {};
+    };
+
+    html! {
+        <div class="card mt-4">
+            <div class="card-header bg-primary text-white d-flex justify-content-between align-items-center">
+                <h5 class="mb-0">{"Taste Timeline"}</h5>
+                <select class="form-select form-select-sm w-auto" onchange={on_period_change}>
+                    <option value="month" selected={*period == "month"}>{"Monthly"}</option>
+                    <option value="quarter" selected={*period == "quarter"}>{"Quarterly"}</option>
+                </select>
+            </div>
+            <div class="card-body">
+                {
+                    if let Some(err) = &*error {
+                        html! { <div class="alert alert-danger mb-3">{err}</div> }
+                    } else {
+                        html! {}
+                    }
+                }
+
+                <ul class="nav nav-tabs mb-3">
+                    {
+                        for group_types.iter().map(|group| {
+                            let is_active = *group == *selected_group;
+                            let group_clone = group.clone();
+                            html! {
+                                <li class="nav-item">
+                                    <button
+                                        class={classes!("nav-link", if is_active { "active" } else { "" })}
+                                        onclick={on_tab_click.reform(move |_| group_clone.clone())}
+                                    >
+                                        { group }
+                                    </button>
+                                </li>
+                            }
+                        })
+                    }
+                </ul>
+
+                <div class="chart-container" style="max-width: 100%;">
+                    <canvas ref={canvas_ref.clone()} style="display: block; width: 100%" />
+                </div>
+
+                <div class="row mt-4">
+                    <div class="col-md-6">
+                        <h6>{"New interests"}</h6>
+                        { trend_list(&data.new_interests, true) }
+                    </div>
+                    <div class="col-md-6">
+                        <h6>{"Abandoned interests"}</h6>
+                        { trend_list(&data.abandoned_interests, false) }
+                    </div>
+                </div>
+            </div>
+        </div>
+    }
+}
+
+fn trend_list(trends: &[TagTrend], rising: bool) -> Html {
+    if trends.is_empty() {
+        return html! { <p class="text-muted small mb-0">{"—"}</p> };
+    }
+
+    html! {
+        <ul class="list-group list-group-flush">
+            {
+                for trends.iter().map(|t| {
+                    let (from, to) = (t.earlier_share * 100.0, t.recent_share * 100.0);
+                    html! {
+                        <li class="list-group-item d-flex justify-content-between align-items-center px-0">
+                            <span>
+                                <span class="badge bg-secondary me-2">{ &t.group_type }</span>
+                                { &t.name }
+                            </span>
+                            <span class={classes!("small", if rising { "text-success" } else { "text-danger" })}>
+                                { format!("{from:.1}% → {to:.1}%") }
+                            </span>
+                        </li>
+                    }
+                })
+            }
+        </ul>
+    }
+}
+
+fn draw_timeline(canvas: &HtmlCanvasElement, labels: &[&str], series: &[Series]) {
+    let window = web_sys::window().expect("no global window exists");
+    let device_pixel_ratio = window.device_pixel_ratio();
+
+    let logical_width = canvas.client_width() as f64;
+    if logical_width == 0.0 {
+        return;
+    }
+
+    let logical_height = 360.0;
+    let top_padding = 20.0;
+    let bottom_padding = 40.0;
+    let left_padding = 50.0;
+    let legend_width = 180.0;
+
+    let physical_width = (logical_width * device_pixel_ratio).round();
+    let physical_height = (logical_height * device_pixel_ratio).round();
+    if canvas.width() != physical_width as u32 {
+        canvas.set_width(physical_width as u32);
+    }
+    if canvas.height() != physical_height as u32 {
+        canvas.set_height(physical_height as u32);
+    }
+
+    let ctx: CanvasRenderingContext2d = canvas
+        .get_context("2d")
+        .unwrap()
+        .unwrap()
+        .dyn_into()
+        .unwrap();
+
+    ctx.set_transform(1.0, 0.0, 0.0, 1.0, 0.0, 0.0).unwrap();
+    ctx.scale(device_pixel_ratio, device_pixel_ratio)
+        .expect("Failed to scale context");
+    ctx.clear_rect(0.0, 0.0, logical_width, logical_height);
+
+    let el: &web_sys::Element = canvas.as_ref();
+    let colors = chart_colors(el);
+    let text_color = get_css_variable_value_on(el, "--bs-body-color").unwrap_or("#212529".into());
+
+    let chart_width = logical_width - left_padding - legend_width;
+    let chart_height = logical_height - top_padding - bottom_padding;
+    if chart_width <= 0.0 || labels.is_empty() {
+        return;
+    }
+
+    let max_share = series
+        .iter()
+        .flat_map(|s| s.shares.iter().copied())
+        .fold(0.0f64, f64::max)
+        .max(0.01);
+    let step = if labels.len() > 1 {
+        chart_width / (labels.len() - 1) as f64
+    } else {
+        0.0
+    };
+    let x_at = |i: usize| left_padding + i as f64 * step;
+    let y_at = |v: f64| top_padding + chart_height - (v / max_share) * chart_height;
+
+    ctx.set_stroke_style_str(&text_color);
+    ctx.set_line_width(1.0);
+    ctx.begin_path();
+    ctx.move_to(left_padding, top_padding);
+    ctx.line_to(left_padding, top_padding + chart_height);
+    ctx.line_to(left_padding + chart_width, top_padding + chart_height);
+    ctx.stroke();
+
+    ctx.set_font("12px Arial");
+    ctx.set_fill_style_str(&text_color);
+    ctx.set_text_align("right");
+    ctx.set_text_baseline("middle");
+    ctx.fill_text(&format!("{:.0}%", max_share * 100.0), left_padding - 6.0, top_padding)
+        .unwrap_or(());
+    ctx.fill_text("0%", left_padding - 6.0, top_padding + chart_height)
+        .unwrap_or(());
+
+    ctx.set_text_align("center");
+    ctx.set_text_baseline("top");
+    let label_every = (labels.len() as f64 / (chart_width / 70.0).max(1.0)).ceil().max(1.0) as usize;
+    for (i, label) in labels.iter().enumerate() {
+        if i % label_every == 0 {
+            ctx.fill_text(label, x_at(i), top_padding + chart_height + 8.0)
+                .unwrap_or(());
+        }
+    }
+
+    ctx.set_line_width(2.0);
+    for (i, s) in series.iter().enumerate() {
+        let color = &colors[i % colors.len()];
+        ctx.set_stroke_style_str(color);
+        ctx.begin_path();
+        for (j, &v) in s.shares.iter().enumerate() {
+            if j == 0 {
+                ctx.move_to(x_at(j), y_at(v));
+            } else {
+                ctx.line_to(x_at(j), y_at(v));
+            }
+        }
+        ctx.stroke();
+
+        let legend_y = top_padding + i as f64 * 18.0;
+        ctx.set_fill_style_str(color);
+        ctx.fill_rect(logical_width - legend_width + 10.0, legend_y - 5.0, 10.0, 10.0);
+        ctx.set_fill_style_str(&text_color);
+        ctx.set_text_align("left");
+        ctx.set_text_baseline("middle");
+        ctx.fill_text(&s.name, logical_width - legend_width + 26.0, legend_y)
+            .unwrap_or(());
+    }
+}
@@ -0,0 +1,138 @@
+use reqwasm::http::Request;
+use web_sys::HtmlSelectElement;
+use yew::{
+    Callback, Event, Html, Properties, TargetCast, UseStateHandle, function_component, html,
+    use_effect_with, use_state,
+};
+
+use crate::models::{Topic, read_config_from_head, with_auth};
+use crate::pages::{TagCount, UserInfo};
+
+const BAR_CLASSES: [&str; 6] = ["bg-primary", "bg-success", "bg-info", "bg-warning", "bg-danger", "bg-secondary"];
+
+#[derive(Properties, PartialEq)]
+pub struct TopicsCardProps {
+    pub user: UseStateHandle<Option<UserInfo>>,
+    pub api_base: String,
+    pub tag_counts: UseStateHandle<Vec<TagCount>>,
+}
+
+#[function_component(TopicsCard)]
+pub fn topics_card(props: &TopicsCardProps) -> Html {
+    let topics = use_state(Vec::<Topic>::new);
+    let error = use_state(|| Option::<String>::None);
+    let k = use_state(|| 8usize);
+
+    {
+        let topics = topics.clone();
+        let error = error.clone();
+        let api_base = props.api_base.clone();
+        let user_id = props.user.as_ref().map(|u| u.id);
+
+        use_effect_with(
+            (user_id, props.tag_counts.len(), *k),
+            move |(user_id, tag_count_len, k)| {
+                topics.set(Vec::new());
+                if let Some(user_id) = *user_id
+                    && *tag_count_len > 0
+                {
+                    let url = format!("{api_base}/account/{user_id}/topics?k={k}");
+                    wasm_bindgen_futures::spawn_local(async move {
+                        match with_auth(Request::get(&url)).send().await {
+                            Ok(response) if response.ok() => match response.json::<Vec<Topic>>().await {
+                                Ok(t) => {
+                                    topics.set(t);
+                                    error.set(None);
+                                }
+                                Err(e) => error.set(Some(format!("Failed to parse topics: {e}"))),
+                            },
+                            Ok(response) => {
+                                let status = response.status();
+                                let text = response
+                                    .text()
+                                    .await
+                                    .unwrap_or_else(|_| "Unknown error".into());
+                                error.set(Some(format!("Error {status}: {text}")));
+                            }
+                            Err(e) => error.set(Some(format!("Network error: {e}"))),
+                        }
+                    });
+                }
+                || ()
+            },
+        );
+    }
+
+    let on_k_change = {
+        let k = k.clone();
+        Callback::from(move |e: Event| {
+            let select: HtmlSelectElement = e.target_unchecked_into();
+            if let Ok(v) = select.value().parse() {
+                k.set(v);
+            }
+        })
+    };
+
+    if props.tag_counts.is_empty() {
+        return html! {};
+    }
+
+    let posts_domain = read_config_from_head().unwrap().posts_domain;
+
+    html! {
+        <div class="card mt-4">
+            <div class="card-header bg-primary text-white d-flex justify-content-between align-items-center">
+                <h5 class="mb-0">{"Taste Topics"}</h5>
+                <select class="form-select form-select-sm w-auto" onchange={on_k_change}>
+                    {
+                        for [4usize, 6, 8, 12, 16].iter().map(|n| html! {
+                            <option value={n.to_string()} selected={*k == *n}>{ format!("{n} topics") }</option>
+                        })
+                    }
+                </select>
+            </div>
+            <div class="card-body">
+                {
+                    if let Some(err) = &*error {
+                        html! { <div class="alert alert-danger mb-3">{err}</div> }
+                    } else {
+                        html! {}
+                    }
+                }
+
+                <div class="d-flex flex-column gap-3">
+                    {
+                        for topics.iter().map(|t| html! {
+                            <div>
+                                <div class="d-flex justify-content-between small mb-1">
+                                    <strong>{ &t.label }</strong>
+                                    <span class="text-muted">{ format!("{} posts · {:.0}%", t.size, t.share * 100.0) }</span>
+                                </div>
+                                <div class="progress mb-1" role="progressbar" aria-label={t.label.clone()}>
+                                    <div
+                                        class={format!("progress-bar {}", BAR_CLASSES[t.id % BAR_CLASSES.len()])}
+                                        style={format!("width: {:.1}%", t.share * 100.0)}
+                                    />
+                                </div>
+                                <div class="d-flex flex-wrap gap-1">
+                                    {
+                                        for t.top_tags.iter().map(|tag| html! {
+                                            <span class="badge text-bg-light border" title={tag.group_type.clone()}>{ &tag.name }</span>
+                                        })
+                                    }
+                                    {
+                                        for t.example_post_ids.iter().map(|id| html! {
+                                            <a class="badge text-bg-secondary" href={format!("{posts_domain}/posts/{id}")} target="_blank">
+                                                { format!("#{id}") }
+                                            </a>
+                                        })
+                                    }
+                                </div>
+                            </div>
+                        })
+                    }
+                </div>
+            </div>
+        </div>
+    }
+}
@@ -4,6 +4,7 @@ use yew::{
     Callback, Html, Properties, TargetCast, UseStateHandle, function_component, html, use_state,
 };
 
+use crate::models::with_auth;
 use crate::pages::UserInfo;
 
 #[derive(Properties, PartialEq)]
@@ -62,7 +63,7 @@ pub fn user_search_form(props: &UserSearchProps) -> Html {
             let error = error.clone();
 
             wasm_bindgen_futures::spawn_local(async move {
-                match Request::get(&url).send().await {
+                match with_auth(Request::get(&url)).send().await {
                     Ok(response) => {
                         if response.ok() {
                             match response.json::<UserInfo>().await {
@@ -0,0 +1,383 @@
+use reqwasm::http::{Request, Response};
+use serde::de::DeserializeOwned;
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+
+use crate::components::SavedAccountsSelect;
+use crate::models::{NewWebhook, Webhook, WebhookDelivery, with_auth};
+use crate::pages::UserInfo;
+
+const RATINGS: [(&str, &str); 3] = [("s", "Safe"), ("q", "Questionable"), ("e", "Explicit")];
+
+#[derive(Properties, PartialEq)]
+pub struct WebhooksCardProps {
+    pub api_base: String,
+}
+
+#[function_component(WebhooksCard)]
+pub fn webhooks_card(props: &WebhooksCardProps) -> Html {
+    let selected_user = use_state(|| Option::<UserInfo>::None);
+    let is_loading = use_state(|| false);
+    let webhooks = use_state(Vec::<Webhook>::new);
+    let draft = use_state(NewWebhook::default);
+    let deliveries = use_state(|| Option::<(i64, Vec<WebhookDelivery>)>::None);
+    let message = use_state(|| Option::<(bool, String)>::None);
+    let reload = use_state(|| 0u32);
+    let user_id = selected_user.as_ref().map(|u| u.id);
+
+    {
+        let webhooks = webhooks.clone();
+        let deliveries = deliveries.clone();
+        let message = message.clone();
+        let api_base = props.api_base.clone();
+
+        use_effect_with((user_id, *reload), move |(user_id, _)| {
+            webhooks.set(Vec::new());
+            deliveries.set(None);
+            if let Some(id) = *user_id {
+                wasm_bindgen_futures::spawn_local(async move {
+                    match get_json::<Vec<Webhook>>(&format!("{api_base}/account/{id}/webhooks")).await {
+                        Ok(w) => webhooks.set(w),
+                        Err(e) => message.set(Some((true, e))),
+                    }
+                });
+            }
+            || ()
+        });
+    }
+
+    let on_add = {
+        let draft = draft.clone();
+        let message = message.clone();
+        let is_loading = is_loading.clone();
+        let reload = reload.clone();
+        let api_base = props.api_base.clone();
+
+        Callback::from(move |_: MouseEvent| {
+            let Some(id) = user_id else {
+                return;
+            };
+            let mut body = (*draft).clone();
+            body.secret = body.secret.filter(|s| !s.trim().is_empty());
+            let draft = draft.clone();
+            let message = message.clone();
+            let is_loading = is_loading.clone();
+            let reload = reload.clone();
+            let url = format!("{api_base}/account/{id}/webhooks");
+            is_loading.set(true);
+
+            wasm_bindgen_futures::spawn_local(async move {
+                let result = with_auth(Request::post(&url))
+                    .header("Content-Type", "application/json")
+                    .body(serde_json::to_string(&body).unwrap())
+                    .send()
+                    .await
+                    .map_err(|e| format!("Network error: {e}"));
+                match result {
+                    Ok(r) if r.ok() => {
+                        // Validation errors come back as plain text.
+                        let text = r.text().await.unwrap_or_default();
+                        if serde_json::from_str::<Webhook>(&text).is_ok() {
+                            draft.set(NewWebhook::default());
+                            message.set(Some((false, "Webhook added".into())));
+                            reload.set(*reload + 1);
+                        } else {
+                            message.set(Some((true, text)));
+                        }
+                    }
+                    Ok(r) => message.set(Some((true, error_text(r).await))),
+                    Err(e) => message.set(Some((true, e))),
+                }
+                is_loading.set(false);
+            });
+        })
+    };
+
+    let on_delete = {
+        let message = message.clone();
+        let reload = reload.clone();
+        let api_base = props.api_base.clone();
+
+        Callback::from(move |webhook_id: i64| {
+            let Some(id) = user_id else {
+                return;
+            };
+            let message = message.clone();
+            let reload = reload.clone();
+            let url = format!("{api_base}/account/{id}/webhooks/{webhook_id}");
+
+            wasm_bindgen_futures::spawn_local(async move {
+                match with_auth(Request::delete(&url)).send().await {
+                    Ok(r) if r.ok() => reload.set(*reload + 1),
+                    Ok(r) => message.set(Some((true, error_text(r).await))),
+                    Err(e) => message.set(Some((true, format!("Network error: {e}")))),
+                }
+            });
+        })
+    };
+
+    let on_test = {
+        let message = message.clone();
+        let api_base = props.api_base.clone();
+
+        Callback::from(move |webhook_id: i64| {
+            let Some(id) = user_id else {
+                return;
+            };
+            let message = message.clone();
+            let url = format!("{api_base}/account/{id}/webhooks/{webhook_id}/test");
+
+            wasm_bindgen_futures::spawn_local(async move {
+                let result = async {
+                    let r = with_auth(Request::post(&url))
+                        .send()
+                        .await
+                        .map_err(|e| format!("Network error: {e}"))?;
+                    if !r.ok() {
+                        return Err(error_text(r).await);
+                    }
+                    r.json::<WebhookDelivery>()
+                        .await
+                        .map_err(|e| format!("Failed to parse response: {e}"))
+                }
+                .await;
+
+                match result {
+                    Ok(d) if d.error.is_none() => message.set(Some((false, delivery_summary(&d)))),
+                    Ok(d) => message.set(Some((true, delivery_summary(&d)))),
+                    Err(e) => message.set(Some((true, e))),
+                }
+            });
+        })
+    };
+
+    let on_show_deliveries = {
+        let deliveries = deliveries.clone();
+        let message = message.clone();
+        let api_base = props.api_base.clone();
+
+        Callback::from(move |webhook_id: i64| {
+            let Some(id) = user_id else {
+                return;
+            };
+            if deliveries.as_ref().is_some_and(|(shown, _)| *shown == webhook_id) {
+                deliveries.set(None);
+                return;
+            }
+            let deliveries = deliveries.clone();
+            let message = message.clone();
+            let url = format!("{api_base}/account/{id}/webhooks/{webhook_id}/deliveries?limit=20");
+
+            wasm_bindgen_futures::spawn_local(async move {
+                match get_json::<Vec<WebhookDelivery>>(&url).await {
+                    Ok(d) => deliveries.set(Some((webhook_id, d))),
+                    Err(e) => message.set(Some((true, e))),
+                }
+            });
+        })
+    };
+
+    let update = {
+        let draft = draft.clone();
+        Callback::from(move |f: Box<dyn FnOnce(&mut NewWebhook)>| {
+            let mut next = (*draft).clone();
+            f(&mut next);
+            draft.set(next);
+        })
+    };
+
+    let text_input = |id: &str, kind: &str, placeholder: &str, value: String, set: fn(&mut NewWebhook, String)| {
+        let update = update.clone();
+        html! {
+            <input
+                type={kind.to_string()}
+                class="form-control form-control-sm"
+                id={id.to_string()}
+                placeholder={placeholder.to_string()}
+                value={value}
+                disabled={*is_loading}
+                onchange={Callback::from(move |e: Event| {
+                    let input: HtmlInputElement = e.target_unchecked_into();
+                    let value = input.value();
+                    update.emit(Box::new(move |d: &mut NewWebhook| set(d, value)));
+                })}
+            />
+        }
+    };
+
+    let body = if user_id.is_none() {
+        html! { <p class="text-muted small mb-0">{"Select an account to manage its webhooks."}</p> }
+    } else {
+        html! {
+            <>
+                {
+                    if webhooks.is_empty() {
+                        html! { <p class="text-muted small">{"No webhooks yet."}</p> }
+                    } else {
+                        html! {
+                            <ul class="list-group mb-3">
+                                { for webhooks.iter().map(|w| webhook_item(w, &deliveries, &on_test, &on_show_deliveries, &on_delete)) }
+                            </ul>
+                        }
+                    }
+                }
+
+                <h6>{"Add webhook"}</h6>
+                <div class="mb-2">
+                    { text_input("webhook-url", "url", "https://chat.example/hooks/...", draft.url.clone(), |d, v| d.url = v) }
+                </div>
+                <div class="row g-2 mb-2">
+                    <div class="col-6">
+                        <label class="form-label small" for="webhook-min-score">{"Minimal score"}</label>
+                        { text_input("webhook-min-score", "number", "0", draft.min_score.to_string(), |d, v| d.min_score = v.parse().unwrap_or(0.0)) }
+                    </div>
+                    <div class="col-6">
+                        <label class="form-label small" for="webhook-secret">{"Secret (optional)"}</label>
+                        { text_input("webhook-secret", "password", "HMAC secret", draft.secret.clone().unwrap_or_default(), |d, v| d.secret = Some(v)) }
+                    </div>
+                </div>
+                <div class="mb-3">
+                    {
+                        for RATINGS.iter().map(|(value, label)| {
+                            let update = update.clone();
+                            let checked = draft.ratings.iter().any(|r| r == value);
+                            let input_id = format!("webhook-rating-{value}");
+                            let value = value.to_string();
+                            html! {
+                                <div class="form-check form-check-inline">
+                                    <input
+                                        class="form-check-input"
+                                        type="checkbox"
+                                        id={input_id.clone()}
+                                        checked={checked}
+                                        onchange={Callback::from(move |_| {
+                                            let value = value.clone();
+                                            update.emit(Box::new(move |d: &mut NewWebhook| {
+                                                if let Some(i) = d.ratings.iter().position(|r| *r == value) {
+                                                    d.ratings.remove(i);
+                                                } else {
+                                                    d.ratings.push(value);
+                                                }
+                                            }));
+                                        })}
+                                    />
+                                    <label class="form-check-label small" for={input_id}>{ *label }</label>
+                                </div>
+                            }
+                        })
+                    }
+                    <div class="form-text">{"No rating selected sends every rating."}</div>
+                </div>
+                <button class="btn btn-primary w-100" onclick={on_add} disabled={*is_loading || draft.url.trim().is_empty()}>
+                    {"Add Webhook"}
+                </button>
+            </>
+        }
+    };
+
+    html! {
+        <div class="card shadow mt-4">
+            <div class="card-body">
+                <h2 class="card-title text-center mb-4">{"Webhooks"}</h2>
+                <SavedAccountsSelect selected_user={selected_user.clone()} is_loading={is_loading.clone()} />
+                { body }
+                {
+                    if let Some((is_error, text)) = &*message {
+                        html! {
+                            <div class={classes!("alert", "mt-3", if *is_error { "alert-danger" } else { "alert-success" })}>
+                                { text }
+                            </div>
+                        }
+                    } else {
+                        html! {}
+                    }
+                }
+            </div>
+        </div>
+    }
+}
+
+fn webhook_item(
+    w: &Webhook,
+    deliveries: &Option<(i64, Vec<WebhookDelivery>)>,
+    on_test: &Callback<i64>,
+    on_show_deliveries: &Callback<i64>,
+    on_delete: &Callback<i64>,
+) -> Html {
+    let id = w.id;
+    let ratings = if w.ratings.is_empty() {
+        "all ratings".to_string()
+    } else {
+        w.ratings.join(", ")
+    };
+    let shown = deliveries.as_ref().filter(|(shown, _)| *shown == id);
+
+    html! {
+        <li key={id} class="list-group-item">
+            <div class="d-flex align-items-center gap-2">
+                <div class="me-auto text-truncate">
+                    <div class="small text-truncate" title={w.url.clone()}>{ &w.url }</div>
+                    <div class="text-muted small">
+                        { format!("score ≥ {:.2} · {ratings}{}", w.min_score, if w.has_secret { " · signed" } else { "" }) }
+                    </div>
+                </div>
+                <div class="btn-group btn-group-sm">
+                    <button class="btn btn-outline-secondary" onclick={on_test.reform(move |_| id)}>{"Test"}</button>
+                    <button class="btn btn-outline-secondary" onclick={on_show_deliveries.reform(move |_| id)}>{"Log"}</button>
+                    <button class="btn btn-outline-danger" onclick={on_delete.reform(move |_| id)}>
+                        <i class="bi bi-trash"></i>
+                    </button>
+                </div>
+            </div>
+            {
+                if let Some((_, list)) = shown {
+                    if list.is_empty() {
+                        html! { <p class="text-muted small mt-2 mb-0">{"Nothing delivered yet."}</p> }
+                    } else {
+                        html! {
+                            <ul class="list-unstyled small mt-2 mb-0">
+                                { for list.iter().map(|d| html! { <li key={d.id}>{ delivery_summary(d) }</li> }) }
+                            </ul>
+                        }
+                    }
+                } else {
+                    html! {}
+                }
+            }
+        </li>
+    }
+}
+
+fn delivery_summary(d: &WebhookDelivery) -> String {
+    let post = d.post_id.map(|id| format!(" #{id}")).unwrap_or_default();
+    let outcome = match (&d.status, &d.error) {
+        (_, Some(e)) => e.clone(),
+        (Some(status), None) => format!("HTTP {status}"),
+        (None, None) => "no response".to_string(),
+    };
+    let retry = if d.attempt > 1 { format!(" (attempt {})", d.attempt) } else { String::new() };
+    format!("{} {}{post}{retry}: {outcome}", d.delivered_at.format("%Y-%m-%d %H:%M"), d.event)
+}
+
+async fn get_json<T: DeserializeOwned>(url: &str) -> Result<T, String> {
+    let response = with_auth(Request::get(url))
+        .send()
+        .await
+        .map_err(|e| format!("Network error: {e}"))?;
+    if !response.ok() {
+        return Err(error_text(response).await);
+    }
+    response
+        .json::<T>()
+        .await
+        .map_err(|e| format!("Failed to parse response: {e}"))
+}
+
+async fn error_text(response: Response) -> String {
+    let status = response.status();
+    let text = response
+        .text()
+        .await
+        .unwrap_or_else(|_| "Unknown error".into());
+    format!("Error {status}: {text}")
+}
@@ -15,6 +15,16 @@ enum Route {
     Account,
     #[at("/feed")]
     Feed,
+    #[at("/compare")]
+    Compare,
+    #[at("/artists")]
+    Artists,
+    #[at("/pools")]
+    Pools,
+    #[at("/pools/:id")]
+    Pool { id: i64 },
+    #[at("/login")]
+    Login,
     #[not_found]
     #[at("/404")]
     NotFound,
@@ -25,6 +35,11 @@ fn switch(routes: Route) -> Html {
         Route::Home => html! { <HomePage /> },
         Route::Account => html! { <Account /> },
         Route::Feed => html! { <FeedPage />},
+        Route::Compare => html! { <ComparePage /> },
+        Route::Artists => html! { <ArtistsPage /> },
+        Route::Pools => html! { <PoolsPage /> },
+        Route::Pool { id } => html! { <PoolReaderPage pool_id={id} /> },
+        Route::Login => html! { <LoginPage /> },
         Route::NotFound => html! { <h1>{ "404" }</h1> },
     }
 }
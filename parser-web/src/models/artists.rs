@@ -0,0 +1,15 @@
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+
+use crate::models::Post;
+
+#[derive(Deserialize, Clone, PartialEq)]
+pub struct ArtistSummary {
+    pub name: String,
+    pub favorites: i64,
+    pub first_favorited: Option<DateTime<Utc>>,
+    pub last_favorited: Option<DateTime<Utc>>,
+    pub last_visit: Option<DateTime<Utc>>,
+    pub new_since_visit: usize,
+    pub recent: Vec<Post>,
+}
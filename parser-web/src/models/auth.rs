@@ -0,0 +1,72 @@
+use reqwasm::http::Request;
+use serde::{Deserialize, Serialize};
+use web_sys::window;
+
+const TOKEN_KEY: &str = "api_token";
+const USER_KEY: &str = "api_user";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct User {
+    pub id: i64,
+    pub username: String,
+    pub role: String,
+}
+
+#[derive(Serialize)]
+pub struct LoginRequest {
+    pub username: String,
+    pub password: String,
+}
+
+#[derive(Deserialize)]
+pub struct IssuedToken {
+    pub token: String,
+    pub user: User,
+}
+
+fn storage() -> Option<web_sys::Storage> {
+    window()?.local_storage().ok()?
+}
+
+/// Token of the logged in user, sent with every API request.
+pub fn auth_token() -> Option<String> {
+    storage()?.get_item(TOKEN_KEY).ok()?
+}
+
+pub fn current_user() -> Option<User> {
+    let json = storage()?.get_item(USER_KEY).ok()??;
+    serde_json::from_str(&json).ok()
+}
+
+pub fn save_login(issued: &IssuedToken) {
+    if let Some(storage) = storage() {
+        let _ = storage.set_item(TOKEN_KEY, &issued.token);
+        let _ = storage.set_item(USER_KEY, &serde_json::to_string(&issued.user).unwrap_or_default());
+    }
+}
+
+pub fn clear_login() {
+    if let Some(storage) = storage() {
+        let _ = storage.remove_item(TOKEN_KEY);
+        let _ = storage.remove_item(USER_KEY);
+    }
+}
+
+/// Adds the `Authorization` header when logged in; the API ignores it with auth disabled.
+pub fn with_auth(request: Request) -> Request {
+    match auth_token() {
+        Some(token) => request.header("Authorization", &format!("Bearer {token}")),
+        None => request,
+    }
+}
+
+/// `url` with the token as a query parameter, for links opened outside the app such as feeds.
+pub fn with_access_token(url: &str) -> String {
+    match auth_token() {
+        Some(token) => {
+            let sep = if url.contains('?') { '&' } else { '?' };
+            format!("{url}{sep}access_token={}", urlencoding::encode(&token))
+        }
+        None => url.to_string(),
+    }
+}
@@ -0,0 +1,24 @@
+use serde::Deserialize;
+
+use crate::pages::{TagCount, UserInfo};
+
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+pub struct SharedTag {
+    pub name: String,
+    pub group_type: String,
+    pub count_a: i64,
+    pub count_b: i64,
+    pub contribution: f32,
+}
+
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+pub struct AccountComparison {
+    pub account_a: UserInfo,
+    pub account_b: UserInfo,
+    pub similarity: f32,
+    pub shared_tags: Vec<SharedTag>,
+    pub unique_a: Vec<TagCount>,
+    pub unique_b: Vec<TagCount>,
+    pub shared_post_count: usize,
+    pub shared_post_ids: Vec<i64>,
+}
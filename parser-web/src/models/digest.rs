@@ -0,0 +1,12 @@
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+
+use crate::models::ScoredPost;
+
+#[derive(Deserialize, Clone, PartialEq)]
+pub struct Digest {
+    pub account_id: i64,
+    pub created_at: DateTime<Utc>,
+    pub since: Option<DateTime<Utc>>,
+    pub posts: Vec<ScoredPost>,
+}
@@ -1,7 +1,27 @@
+mod artists;
+mod auth;
+mod compare;
+mod digest;
+mod pool;
 mod post;
+mod related;
+mod scoring;
 mod config;
+mod timeline;
+mod topics;
 mod tour;
+mod webhooks;
 
+pub use artists::*;
+pub use auth::*;
+pub use compare::*;
+pub use digest::*;
+pub use pool::*;
 pub use post::*;
+pub use related::*;
+pub use scoring::*;
 pub use config::*;
+pub use timeline::*;
+pub use topics::*;
 pub use tour::*;
+pub use webhooks::*;
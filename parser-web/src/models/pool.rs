@@ -0,0 +1,39 @@
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+
+use crate::models::ScoredPost;
+
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+pub struct Pool {
+    pub id: i64,
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    pub category: String,
+    pub is_active: bool,
+    pub post_ids: Vec<i64>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl Pool {
+    pub fn display_name(&self) -> String {
+        self.name.replace('_', " ")
+    }
+}
+
+#[derive(Deserialize, Clone, PartialEq)]
+pub struct PoolRecommendation {
+    pub pool: Pool,
+    pub score: f32,
+    pub best: f32,
+    pub scored_posts: usize,
+    pub cover: Option<ScoredPost>,
+}
+
+#[derive(Deserialize, Clone, PartialEq)]
+pub struct PoolPage {
+    pub pool: Pool,
+    pub page: usize,
+    pub pages: usize,
+    pub posts: Vec<ScoredPost>,
+}
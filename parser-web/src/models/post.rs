@@ -132,8 +132,10 @@ pub struct Relationships {
     pub children: Vec<i64>,
 }
 
-#[derive(Serialize, Deserialize, Clone)]
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
 pub struct ScoredPost {
     pub post: Post,
     pub score: f32,
+    #[serde(default)]
+    pub related: Vec<ScoredPost>,
 }
@@ -0,0 +1,20 @@
+use serde::Deserialize;
+
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+pub struct RelatedTag {
+    pub name: String,
+    pub group_type: String,
+    pub together: i64,
+    pub count: i64,
+    pub lift: f64,
+    pub pmi: f64,
+    pub jaccard: f64,
+}
+
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+pub struct RelatedTags {
+    pub tag: String,
+    pub scope_posts: i64,
+    pub tag_posts: i64,
+    pub related: Vec<RelatedTag>,
+}
@@ -0,0 +1,34 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(default)]
+pub struct PriorsOverride {
+    pub recency_tau_days: Option<f32>,
+    pub quality_a: Option<f32>,
+    pub quality_b: Option<f32>,
+    pub mix_sim: Option<f32>,
+    pub mix_quality: Option<f32>,
+    pub mix_recency: Option<f32>,
+    pub mix_collab: Option<f32>,
+    pub idf_lambda: Option<f32>,
+    pub idf_alpha: Option<f32>,
+    pub freq_alpha: Option<f32>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(default)]
+pub struct ScoringProfile {
+    pub model: Option<String>,
+    pub group_weights: HashMap<String, f32>,
+    pub priors: PriorsOverride,
+    pub df_floor: Option<f32>,
+    pub idf_max: Option<f32>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(default)]
+pub struct AccountScoring {
+    pub profile: Option<String>,
+    pub overrides: ScoringProfile,
+}
@@ -0,0 +1,34 @@
+use serde::Deserialize;
+
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+pub struct PeriodTagCount {
+    pub name: String,
+    pub group_type: String,
+    pub count: i64,
+    pub share: f64,
+}
+
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+pub struct TimelineBucket {
+    pub period: String,
+    pub favorites: i64,
+    pub tags: Vec<PeriodTagCount>,
+}
+
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+pub struct TagTrend {
+    pub name: String,
+    pub group_type: String,
+    pub earlier_count: i64,
+    pub recent_count: i64,
+    pub earlier_share: f64,
+    pub recent_share: f64,
+}
+
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+pub struct TagTimeline {
+    pub period: String,
+    pub buckets: Vec<TimelineBucket>,
+    pub new_interests: Vec<TagTrend>,
+    pub abandoned_interests: Vec<TagTrend>,
+}
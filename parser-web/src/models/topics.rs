@@ -0,0 +1,18 @@
+use serde::Deserialize;
+
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+pub struct TopicTag {
+    pub name: String,
+    pub group_type: String,
+    pub weight: f32,
+}
+
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+pub struct Topic {
+    pub id: usize,
+    pub label: String,
+    pub top_tags: Vec<TopicTag>,
+    pub size: usize,
+    pub share: f32,
+    pub example_post_ids: Vec<i64>,
+}
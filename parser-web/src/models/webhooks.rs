@@ -0,0 +1,31 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Clone, PartialEq, Default)]
+pub struct NewWebhook {
+    pub url: String,
+    pub min_score: f32,
+    pub ratings: Vec<String>,
+    pub secret: Option<String>,
+}
+
+#[derive(Deserialize, Clone, PartialEq)]
+pub struct Webhook {
+    pub id: i64,
+    pub url: String,
+    pub min_score: f32,
+    pub ratings: Vec<String>,
+    pub has_secret: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Deserialize, Clone, PartialEq)]
+pub struct WebhookDelivery {
+    pub id: i64,
+    pub event: String,
+    pub post_id: Option<i64>,
+    pub attempt: u32,
+    pub status: Option<u16>,
+    pub error: Option<String>,
+    pub delivered_at: DateTime<Utc>,
+}
@@ -1,4 +1,5 @@
-use crate::models::read_config_from_head;
+use crate::components::{ScoringProfileCard, WebhooksCard};
+use crate::models::{read_config_from_head, with_auth};
 use crate::pages::UserInfo;
 use reqwasm::http::Request;
 use serde_json::to_string;
@@ -106,7 +107,7 @@ pub fn account_creator() -> Html {
             let mut saved_accounts = saved_accounts.clone().to_vec();
 
             wasm_bindgen_futures::spawn_local(async move {
-                let response = Request::post(&format!("{0}/account", cfg.backend_domain))
+                let response = with_auth(Request::post(&format!("{0}/account", cfg.backend_domain)))
                     .header("Content-Type", "application/json")
                     .body(to_string(&account).unwrap())
                     .send()
@@ -234,6 +235,8 @@ pub fn account_creator() -> Html {
                             </form>
                         </div>
                     </div>
+                    <ScoringProfileCard api_base={read_config_from_head().unwrap().backend_domain} />
+                    <WebhooksCard api_base={read_config_from_head().unwrap().backend_domain} />
                 </div>
             </div>
         </div>
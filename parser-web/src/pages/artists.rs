@@ -0,0 +1,206 @@
+use chrono::{DateTime, Utc};
+use reqwasm::http::Request;
+use serde::de::DeserializeOwned;
+use web_sys::js_sys::encode_uri_component;
+use yew::prelude::*;
+
+use crate::components::*;
+use crate::models::{ArtistSummary, read_config_from_head, with_auth};
+use crate::pages::UserInfo;
+
+#[function_component(ArtistsPage)]
+pub fn artists_page() -> Html {
+    let cfg = read_config_from_head().unwrap();
+    let selected_user = use_state(|| Option::<UserInfo>::None);
+    let is_loading = use_state(|| false);
+    let error = use_state(|| Option::<String>::None);
+    let artists = use_state(Vec::<ArtistSummary>::new);
+
+    {
+        let api_base = cfg.backend_domain.clone();
+        let is_loading = is_loading.clone();
+        let error = error.clone();
+        let artists = artists.clone();
+
+        use_effect_with(selected_user.as_ref().map(|u| u.id), move |id| {
+            artists.set(Vec::new());
+            if let Some(id) = *id {
+                is_loading.set(true);
+                error.set(None);
+                wasm_bindgen_futures::spawn_local(async move {
+                    match fetch::<Vec<ArtistSummary>>(&format!("{api_base}/account/{id}/artists")).await {
+                        Ok(a) => artists.set(a),
+                        Err(e) => error.set(Some(e)),
+                    }
+                    is_loading.set(false);
+                });
+            }
+            || ()
+        });
+    }
+
+    let on_seen = {
+        let api_base = cfg.backend_domain.clone();
+        let artists = artists.clone();
+        let error = error.clone();
+        let account_id = selected_user.as_ref().map(|u| u.id);
+
+        Callback::from(move |name: String| {
+            let Some(id) = account_id else {
+                return;
+            };
+            let url = format!(
+                "{api_base}/account/{id}/artists/{}/visit",
+                String::from(encode_uri_component(&name))
+            );
+            let artists = artists.clone();
+            let error = error.clone();
+
+            wasm_bindgen_futures::spawn_local(async move {
+                match with_auth(Request::put(&url)).send().await {
+                    Ok(r) if r.ok() => {
+                        let updated = artists
+                            .iter()
+                            .cloned()
+                            .map(|mut a| {
+                                if a.name == name {
+                                    a.last_visit = Some(Utc::now());
+                                    a.new_since_visit = 0;
+                                }
+                                a
+                            })
+                            .collect();
+                        artists.set(updated);
+                    }
+                    Ok(r) => error.set(Some(format!("Error {}: failed to mark artist as seen", r.status()))),
+                    Err(e) => error.set(Some(format!("Network error: {e}"))),
+                }
+            });
+        })
+    };
+
+    html! {
+        <div class="container my-4" id="artists-page">
+            <h2 class="mb-3">{ "Favorite Artists" }</h2>
+            <SavedAccountsSelect selected_user={selected_user.clone()} is_loading={is_loading.clone()} />
+
+            {
+                if let Some(err) = &*error {
+                    html! { <div class="alert alert-danger mt-3">{ err }</div> }
+                } else {
+                    html! {}
+                }
+            }
+
+            {
+                if *is_loading {
+                    html! {
+                        <div class="d-flex justify-content-center my-4">
+                            <div class="spinner-border" role="status">
+                                <span class="visually-hidden">{ "Loading..." }</span>
+                            </div>
+                        </div>
+                    }
+                } else if selected_user.is_some() && error.is_none() && artists.is_empty() {
+                    html! { <div class="text-center text-muted my-5">{ "No favorited artists yet. Analyze the account first." }</div> }
+                } else {
+                    html! {}
+                }
+            }
+
+            <div class="d-flex flex-column gap-3 mt-3">
+                { for artists.iter().map(|a| artist_card(a, &cfg.posts_domain, on_seen.clone())) }
+            </div>
+        </div>
+    }
+}
+
+fn artist_card(artist: &ArtistSummary, posts_domain: &str, on_seen: Callback<String>) -> Html {
+    let onclick = {
+        let name = artist.name.clone();
+        Callback::from(move |_: MouseEvent| on_seen.emit(name.clone()))
+    };
+    let search = format!(
+        "{posts_domain}/posts?tags={}",
+        String::from(encode_uri_component(&artist.name))
+    );
+
+    html! {
+        <div key={artist.name.clone()} class="card shadow-sm">
+            <div class="card-body">
+                <div class="d-flex align-items-center gap-2 mb-1">
+                    <a class="h5 mb-0" href={search} target="_blank">{ artist.name.replace('_', " ") }</a>
+                    {
+                        if artist.new_since_visit > 0 {
+                            html! { <span class="badge bg-danger">{ format!("{} new", artist.new_since_visit) }</span> }
+                        } else {
+                            html! {}
+                        }
+                    }
+                    <button class="btn btn-sm btn-outline-secondary ms-auto" {onclick}>{ "Mark seen" }</button>
+                </div>
+                <p class="text-muted small mb-2">
+                    { format!(
+                        "{} favorites · first {} · last {} · visited {}",
+                        artist.favorites,
+                        date(artist.first_favorited),
+                        date(artist.last_favorited),
+                        date(artist.last_visit),
+                    ) }
+                </p>
+                {
+                    if artist.recent.is_empty() {
+                        html! { <p class="text-muted small mb-0">{ "No recent posts you haven't favorited." }</p> }
+                    } else {
+                        html! {
+                            <div class="d-flex flex-wrap gap-2">
+                                {
+                                    for artist.recent.iter().map(|post| {
+                                        let thumb = fallback_image_url(post);
+                                        html! {
+                                            <a href={format!("{posts_domain}/posts/{}", post.id)} target="_blank" title={format!("#{}", post.id)}>
+                                                {
+                                                    if thumb.is_empty() {
+                                                        html! { <span class="badge bg-secondary">{ format!("#{}", post.id) }</span> }
+                                                    } else {
+                                                        html! { <img src={thumb} class="rounded" style="height: 96px;" loading="lazy" alt={format!("Post #{}", post.id)} /> }
+                                                    }
+                                                }
+                                            </a>
+                                        }
+                                    })
+                                }
+                            </div>
+                        }
+                    }
+                }
+            </div>
+        </div>
+    }
+}
+
+fn date(d: Option<DateTime<Utc>>) -> String {
+    d.map(|d| d.format("%Y-%m-%d").to_string())
+        .unwrap_or_else(|| "never".into())
+}
+
+async fn fetch<T: DeserializeOwned>(url: &str) -> Result<T, String> {
+    let response = with_auth(Request::get(url))
+        .send()
+        .await
+        .map_err(|e| format!("Network error: {e}"))?;
+
+    if !response.ok() {
+        let status = response.status();
+        let text = response
+            .text()
+            .await
+            .unwrap_or_else(|_| "Unknown error".into());
+        return Err(format!("Error {status}: {text}"));
+    }
+
+    response
+        .json::<T>()
+        .await
+        .map_err(|e| format!("Failed to parse response: {e}"))
+}
@@ -0,0 +1,227 @@
+use reqwasm::http::Request;
+use serde::de::DeserializeOwned;
+use yew::prelude::*;
+
+use crate::components::*;
+use crate::models::{AccountComparison, read_config_from_head, with_auth};
+use crate::pages::{TagCount, UserInfo};
+
+#[function_component(ComparePage)]
+pub fn compare_page() -> Html {
+    let cfg = read_config_from_head().unwrap();
+    let user_a: UseStateHandle<Option<UserInfo>> = use_state(|| None::<UserInfo>);
+    let user_b: UseStateHandle<Option<UserInfo>> = use_state(|| None::<UserInfo>);
+    let is_loading: UseStateHandle<bool> = use_state(|| false);
+    let error: UseStateHandle<Option<String>> = use_state(|| None::<String>);
+    let comparison = use_state(|| None::<AccountComparison>);
+    let tag_counts_a: UseStateHandle<Vec<TagCount>> = use_state(Vec::<TagCount>::new);
+    let tag_counts_b: UseStateHandle<Vec<TagCount>> = use_state(Vec::<TagCount>::new);
+    let canvas_a = use_node_ref();
+    let canvas_b = use_node_ref();
+
+    {
+        let api_base = cfg.backend_domain.clone();
+        let is_loading = is_loading.clone();
+        let error = error.clone();
+        let comparison = comparison.clone();
+        let tag_counts_a = tag_counts_a.clone();
+        let tag_counts_b = tag_counts_b.clone();
+        let ids = (
+            user_a.as_ref().map(|u| u.id),
+            user_b.as_ref().map(|u| u.id),
+        );
+
+        use_effect_with(ids, move |ids| {
+            comparison.set(None);
+            tag_counts_a.set(Vec::new());
+            tag_counts_b.set(Vec::new());
+
+            if let (Some(a), Some(b)) = *ids {
+                is_loading.set(true);
+                error.set(None);
+
+                wasm_bindgen_futures::spawn_local(async move {
+                    let result = async {
+                        let cmp: AccountComparison =
+                            fetch(&format!("{api_base}/compare/{a}/{b}")).await?;
+                        let counts_a: Vec<TagCount> =
+                            fetch(&format!("{api_base}/account/{a}/tag_counts")).await?;
+                        let counts_b: Vec<TagCount> =
+                            fetch(&format!("{api_base}/account/{b}/tag_counts")).await?;
+                        Ok::<_, String>((cmp, counts_a, counts_b))
+                    }
+                    .await;
+
+                    match result {
+                        Ok((cmp, counts_a, counts_b)) => {
+                            comparison.set(Some(cmp));
+                            tag_counts_a.set(counts_a);
+                            tag_counts_b.set(counts_b);
+                        }
+                        Err(e) => error.set(Some(e)),
+                    }
+                    is_loading.set(false);
+                });
+            }
+            || ()
+        });
+    }
+
+    html! {
+        <div class="container mt-4" id="compare-page">
+            <div class="card shadow-sm">
+                <div class="card-body">
+                    <h1 class="card-title text-center mb-4">{"Compare Accounts"}</h1>
+                    <div class="row">
+                        <div class="col-md-6">
+                            <SavedAccountsSelect selected_user={user_a.clone()} is_loading={is_loading.clone()} />
+                        </div>
+                        <div class="col-md-6">
+                            <SavedAccountsSelect selected_user={user_b.clone()} is_loading={is_loading.clone()} />
+                        </div>
+                    </div>
+
+                    {
+                        if let Some(err) = &*error {
+                            html! { <div class="alert alert-danger mb-3">{err}</div> }
+                        } else {
+                            html! {}
+                        }
+                    }
+
+                    {
+                        if *is_loading {
+                            html! {
+                                <div class="d-flex justify-content-center my-4">
+                                    <div class="spinner-border" role="status">
+                                        <span class="visually-hidden">{ "Loading..." }</span>
+                                    </div>
+                                </div>
+                            }
+                        } else {
+                            html! {}
+                        }
+                    }
+
+                    {
+                        if let Some(cmp) = &*comparison {
+                            comparison_summary(cmp, &cfg.posts_domain)
+                        } else {
+                            html! {}
+                        }
+                    }
+                </div>
+            </div>
+
+            <div class="row">
+                <div class="col-lg-6">
+                    <TagChartCard canvas_ref={canvas_a.clone()} tag_counts={tag_counts_a.clone()} account_id={user_a.as_ref().map(|u| u.id)} />
+                </div>
+                <div class="col-lg-6">
+                    <TagChartCard canvas_ref={canvas_b.clone()} tag_counts={tag_counts_b.clone()} account_id={user_b.as_ref().map(|u| u.id)} />
+                </div>
+            </div>
+        </div>
+    }
+}
+
+fn comparison_summary(cmp: &AccountComparison, posts_domain: &str) -> Html {
+    let percent = (cmp.similarity * 100.0).clamp(0.0, 100.0);
+
+    html! {
+        <>
+            <div class="mb-4">
+                <div class="d-flex justify-content-between">
+                    <span>{"Taste similarity"}</span>
+                    <strong>{ format!("{percent:.1}%") }</strong>
+                </div>
+                <div class="progress" role="progressbar" aria-valuenow={format!("{percent:.0}")} aria-valuemin="0" aria-valuemax="100">
+                    <div class="progress-bar" style={format!("width: {percent:.1}%")}></div>
+                </div>
+            </div>
+
+            <div class="row">
+                <div class="col-md-4">
+                    <h6>{"Shared top tags"}</h6>
+                    <ul class="list-group list-group-flush">
+                        {
+                            for cmp.shared_tags.iter().map(|t| html! {
+                                <li class="list-group-item d-flex justify-content-between px-0">
+                                    <span>
+                                        <span class="badge bg-secondary me-2">{ &t.group_type }</span>
+                                        { &t.name }
+                                    </span>
+                                    <span class="small text-muted">{ format!("{} / {}", t.count_a, t.count_b) }</span>
+                                </li>
+                            })
+                        }
+                    </ul>
+                </div>
+                <div class="col-md-4">
+                    <h6>{ format!("Only {}", cmp.account_a.name) }</h6>
+                    { tag_list(&cmp.unique_a) }
+                </div>
+                <div class="col-md-4">
+                    <h6>{ format!("Only {}", cmp.account_b.name) }</h6>
+                    { tag_list(&cmp.unique_b) }
+                </div>
+            </div>
+
+            <div class="mt-4">
+                <h6>{ format!("Favorited by both: {} post(s)", cmp.shared_post_count) }</h6>
+                <div class="d-flex flex-wrap gap-2">
+                    {
+                        for cmp.shared_post_ids.iter().map(|id| html! {
+                            <a class="badge bg-primary text-decoration-none" href={format!("{posts_domain}/posts/{id}")} target="_blank">
+                                { format!("#{id}") }
+                            </a>
+                        })
+                    }
+                </div>
+            </div>
+        </>
+    }
+}
+
+fn tag_list(tags: &[TagCount]) -> Html {
+    if tags.is_empty() {
+        return html! { <p class="text-muted small mb-0">{"—"}</p> };
+    }
+
+    html! {
+        <ul class="list-group list-group-flush">
+            {
+                for tags.iter().map(|t| html! {
+                    <li class="list-group-item d-flex justify-content-between px-0">
+                        <span>
+                            <span class="badge bg-secondary me-2">{ &t.group_type }</span>
+                            { &t.name }
+                        </span>
+                        <span class="small text-muted">{ t.count }</span>
+                    </li>
+                })
+            }
+        </ul>
+    }
+}
+
+async fn fetch<T: DeserializeOwned>(url: &str) -> Result<T, String> {
+    let response = with_auth(Request::get(url))
+        .send()
+        .await
+        .map_err(|e| format!("Network error: {e}"))?;
+
+    if !response.ok() {
+        let status = response.status();
+        let text = response
+            .text()
+            .await
+            .unwrap_or_else(|_| "Unknown error".into());
+        return Err(format!("Error {status}: {text}"));
+    }
+
+    response
+        .json::<T>()
+        .await
+        .map_err(|e| format!("Failed to parse response: {e}"))
+}
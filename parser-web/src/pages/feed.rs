@@ -5,7 +5,7 @@ use wasm_bindgen::JsCast;
 use wasm_bindgen::prelude::Closure;
 use wasm_bindgen_futures::spawn_local;
 use web_sys::HtmlInputElement;
-use web_sys::{Request, RequestInit, RequestMode, Response, window};
+use web_sys::{Headers, Request, RequestInit, RequestMode, Response, window};
 use yew::prelude::*;
 
 use crate::components::*;
@@ -14,6 +14,8 @@ use crate::pages::UserInfo;
 
 const PIXELS_BEFORE_REFETCH: f64 = 1000.0;
 
+type ScrollListener = (web_sys::Window, Closure<dyn FnMut(Event)>);
+
 #[derive(Clone, Copy, PartialEq, Eq)]
 enum GridType {
     Auto,
@@ -155,11 +157,6 @@ pub fn feed_page() -> Html {
                         );
                         let added = new_items.len();
                         if added > 0 {
-                            new_items.sort_by(|a, b| {
-                                b.score
-                                    .partial_cmp(&a.score)
-                                    .unwrap_or(std::cmp::Ordering::Equal)
-                            });
                             merged.extend(new_items);
                             posts.set(merged);
                             page.set(*page + 1);
@@ -205,7 +202,7 @@ pub fn feed_page() -> Html {
         let fetch_page = fetch_page.clone();
 
         use_effect(move || {
-            let mut listener: Option<(web_sys::Window, Closure<dyn FnMut(Event)>)> = None;
+            let mut listener: Option<ScrollListener> = None;
 
             if let Some(win) = window() {
                 let is_loading_cb = is_loading.clone();
@@ -303,12 +300,11 @@ pub fn feed_page() -> Html {
                             oninput={{
                                 let affinity = affinity.clone();
                                 Callback::from(move |e: InputEvent| {
-                                    if let Some(target) = e.target() {
-                                        if let Ok(input) = target.dyn_into::<HtmlInputElement>() {
-                                            if let Ok(v) = input.value().parse::<f32>() {
-                                                affinity.set(v);
-                                            }
-                                        }
+                                    if let Some(target) = e.target()
+                                        && let Ok(input) = target.dyn_into::<HtmlInputElement>()
+                                        && let Ok(v) = input.value().parse::<f32>()
+                                    {
+                                        affinity.set(v);
                                     }
                                 })
                             }}
@@ -366,6 +362,8 @@ pub fn feed_page() -> Html {
                 </div>
             </div>
 
+            <DigestCard account_id={selected_user.as_ref().map(|u| u.id)} />
+
             <div class="position-fixed bottom-0 start-50 translate-middle-x w-100 d-flex justify-content-between z-1">
                 {
                     if let Some(u) = &*selected_user {
@@ -417,7 +415,7 @@ pub fn feed_page() -> Html {
                         let sp = sp.clone();
                         html! {
                             <div key={sp.post.id} class={ (*grid).col_class() } style="min-width: 200px">
-                                <PostCard affinity={sp.score} post={Rc::new(sp.post)}/>
+                                <PostCard affinity={sp.score} post={Rc::new(sp.post)} related={Rc::new(sp.related)}/>
                             </div>
                         }
                     }).collect::<Html>()
@@ -445,6 +443,13 @@ async fn fetch_json<T: DeserializeOwned>(url: &str) -> Result<T, String> {
     let opts = RequestInit::new();
     opts.set_method("GET");
     opts.set_mode(RequestMode::Cors);
+    if let Some(token) = auth_token() {
+        let headers = Headers::new().map_err(|e| format!("Failed to create headers: {e:?}"))?;
+        headers
+            .set("Authorization", &format!("Bearer {token}"))
+            .map_err(|e| format!("Failed to set headers: {e:?}"))?;
+        opts.set_headers(&headers);
+    }
 
     let request = Request::new_with_str_and_init(url, &opts)
         .map_err(|e| format!("Failed to create request: {e:?}"))?;
@@ -72,6 +72,17 @@ pub fn home_page() -> Html {
             <TagChartCard
                 canvas_ref={canvas_ref.clone()}
                 tag_counts={tag_counts.clone()}
+                account_id={selected_user.as_ref().map(|u| u.id)}
+            />
+            <TopicsCard
+                user={selected_user.clone()}
+                api_base={cfg.backend_domain.clone()}
+                tag_counts={tag_counts.clone()}
+            />
+            <TagTimelineCard
+                user={selected_user.clone()}
+                api_base={cfg.backend_domain.clone()}
+                tag_counts={tag_counts.clone()}
             />
         </div>
     }
@@ -0,0 +1,139 @@
+use crate::models::{IssuedToken, LoginRequest, read_config_from_head, save_login};
+use reqwasm::http::Request;
+use serde_json::to_string;
+use web_sys::{HtmlInputElement, window};
+use yew::prelude::*;
+
+#[function_component(LoginPage)]
+pub fn login_page() -> Html {
+    let username = use_state(String::new);
+    let password = use_state(String::new);
+    let error = use_state(|| None::<String>);
+    let loading = use_state(|| false);
+
+    let on_username_change = {
+        let username = username.clone();
+        Callback::from(move |e: Event| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            username.set(input.value());
+        })
+    };
+
+    let on_password_change = {
+        let password = password.clone();
+        Callback::from(move |e: Event| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            password.set(input.value());
+        })
+    };
+
+    let onsubmit = {
+        let username = username.clone();
+        let password = password.clone();
+        let error = error.clone();
+        let loading = loading.clone();
+
+        Callback::from(move |e: SubmitEvent| {
+            e.prevent_default();
+            if username.trim().is_empty() || password.is_empty() {
+                error.set(Some("Username and password are required".to_string()));
+                return;
+            }
+            loading.set(true);
+            error.set(None);
+
+            let cfg = read_config_from_head().unwrap();
+            let login = LoginRequest {
+                username: username.trim().to_string(),
+                password: (*password).clone(),
+            };
+            let error = error.clone();
+            let loading = loading.clone();
+
+            wasm_bindgen_futures::spawn_local(async move {
+                let response = Request::post(&format!("{}/auth/login", cfg.backend_domain))
+                    .header("Content-Type", "application/json")
+                    .body(to_string(&login).unwrap())
+                    .send()
+                    .await;
+
+                match response {
+                    Ok(resp) if resp.ok() => match resp.json::<IssuedToken>().await {
+                        Ok(issued) => {
+                            save_login(&issued);
+                            // Full reload so every page picks up the new token.
+                            if let Some(w) = window() {
+                                let _ = w.location().set_href("/");
+                            }
+                        }
+                        Err(e) => error.set(Some(format!("Invalid login response: {e}"))),
+                    },
+                    Ok(resp) => {
+                        let text = resp.text().await.unwrap_or_else(|_| "Unknown error".to_string());
+                        error.set(Some(text));
+                    }
+                    Err(e) => error.set(Some(format!("Network error: {e}"))),
+                }
+                loading.set(false);
+            });
+        })
+    };
+
+    html! {
+        <div class="container mt-5" id="login-page">
+            <div class="row justify-content-center">
+                <div class="col-md-4">
+                    <div class="card shadow">
+                        <div class="card-body">
+                            <h1 class="card-title text-center mb-4">{"Log in"}</h1>
+                            <form onsubmit={onsubmit}>
+                                <div class="mb-3">
+                                    <label for="login-username" class="form-label">{"Username"}</label>
+                                    <input
+                                        type="text"
+                                        class="form-control"
+                                        id="login-username"
+                                        autocomplete="username"
+                                        value={(*username).clone()}
+                                        onchange={on_username_change}
+                                        disabled={*loading}
+                                    />
+                                </div>
+
+                                <div class="mb-3">
+                                    <label for="login-password" class="form-label">{"Password"}</label>
+                                    <input
+                                        type="password"
+                                        class="form-control"
+                                        id="login-password"
+                                        autocomplete="current-password"
+                                        value={(*password).clone()}
+                                        onchange={on_password_change}
+                                        disabled={*loading}
+                                    />
+                                </div>
+
+                                <button type="submit" class="btn btn-primary w-100" disabled={*loading}>
+                                    { if *loading {
+                                        html! {
+                                            <span>
+                                                <span class="spinner-border spinner-border-sm me-2" role="status" aria-hidden="true"></span>
+                                                {"Logging in..."}
+                                            </span>
+                                        }
+                                    } else {
+                                        "Log in".into()
+                                    }}
+                                </button>
+
+                                if let Some(e) = &*error {
+                                    <div class="alert alert-danger mt-3" role="alert">{ e }</div>
+                                }
+                            </form>
+                        </div>
+                    </div>
+                </div>
+            </div>
+        </div>
+    }
+}
@@ -1,7 +1,17 @@
 mod account;
+mod artists;
+mod compare;
 mod feed;
 mod home;
+mod login;
+mod pool_reader;
+mod pools;
 
 pub use account::*;
+pub use artists::*;
+pub use compare::*;
 pub use feed::*;
 pub use home::*;
+pub use login::*;
+pub use pool_reader::*;
+pub use pools::*;
@@ -0,0 +1,170 @@
+use reqwasm::http::Request;
+use serde::Deserialize;
+use serde::de::DeserializeOwned;
+use std::rc::Rc;
+use yew::prelude::*;
+use yew_router::prelude::*;
+
+use crate::components::*;
+use crate::models::{PoolPage, read_config_from_head, with_auth};
+
+#[derive(Deserialize, Default)]
+struct ReaderQuery {
+    account: Option<i32>,
+}
+
+#[derive(Properties, PartialEq)]
+pub struct PoolReaderProps {
+    pub pool_id: i64,
+}
+
+#[function_component(PoolReaderPage)]
+pub fn pool_reader_page(props: &PoolReaderProps) -> Html {
+    let cfg = read_config_from_head().unwrap();
+    let account = use_location()
+        .and_then(|l| l.query::<ReaderQuery>().ok())
+        .unwrap_or_default()
+        .account;
+    let page = use_state(|| 1usize);
+    let data = use_state(|| Option::<PoolPage>::None);
+    let is_loading = use_state(|| false);
+    let error = use_state(|| Option::<String>::None);
+
+    {
+        let api_base = cfg.backend_domain.clone();
+        let data = data.clone();
+        let is_loading = is_loading.clone();
+        let error = error.clone();
+
+        use_effect_with((props.pool_id, account, *page), move |&(pool_id, account, page)| {
+            match account {
+                None => error.set(Some("Open a pool from the Pools page to read it.".into())),
+                Some(account) => {
+                    is_loading.set(true);
+                    error.set(None);
+                    wasm_bindgen_futures::spawn_local(async move {
+                        let url = format!("{api_base}/pool/{pool_id}?account_id={account}&page={page}");
+                        match fetch::<PoolPage>(&url).await {
+                            Ok(p) => data.set(Some(p)),
+                            Err(e) => error.set(Some(e)),
+                        }
+                        is_loading.set(false);
+                    });
+                }
+            }
+            || ()
+        });
+    }
+
+    let go_to = |target: usize| {
+        let page = page.clone();
+        Callback::from(move |_: MouseEvent| {
+            page.set(target);
+            if let Some(win) = web_sys::window() {
+                win.scroll_to_with_x_and_y(0.0, 0.0);
+            }
+        })
+    };
+
+    let pager = match &*data {
+        Some(p) if p.pages > 1 => html! {
+            <div class="d-flex justify-content-between align-items-center my-3">
+                <button class="btn btn-outline-secondary" disabled={*is_loading || p.page <= 1} onclick={go_to(p.page.saturating_sub(1))}>
+                    { "Previous" }
+                </button>
+                <span class="text-muted">{ format!("Page {} of {}", p.page, p.pages) }</span>
+                <button class="btn btn-outline-secondary" disabled={*is_loading || p.page >= p.pages} onclick={go_to(p.page + 1)}>
+                    { "Next" }
+                </button>
+            </div>
+        },
+        _ => html! {},
+    };
+
+    html! {
+        <div class="container my-4" id="pool-reader">
+            {
+                if let Some(p) = &*data {
+                    html! {
+                        <>
+                            <h2 class="mb-1">{ p.pool.display_name() }</h2>
+                            <p class="text-muted small">
+                                { format!("{} · {} posts", p.pool.category, p.pool.post_ids.len()) }
+                                {" · "}
+                                <a href={format!("{}/pools/{}", cfg.posts_domain, p.pool.id)} target="_blank">{ "View upstream" }</a>
+                            </p>
+                            {
+                                if p.pool.description.trim().is_empty() {
+                                    html! {}
+                                } else {
+                                    html! { <p class="text-body-secondary" style="white-space: pre-line;">{ p.pool.description.clone() }</p> }
+                                }
+                            }
+                        </>
+                    }
+                } else {
+                    html! { <h2 class="mb-3">{ "Pool" }</h2> }
+                }
+            }
+
+            {
+                if let Some(err) = &*error {
+                    html! { <div class="alert alert-danger">{ err }</div> }
+                } else {
+                    html! {}
+                }
+            }
+
+            { pager.clone() }
+
+            {
+                if *is_loading {
+                    html! {
+                        <div class="d-flex justify-content-center my-4">
+                            <div class="spinner-border" role="status">
+                                <span class="visually-hidden">{ "Loading..." }</span>
+                            </div>
+                        </div>
+                    }
+                } else if let Some(p) = &*data {
+                    html! {
+                        <div class="d-flex flex-column align-items-center gap-3">
+                            {
+                                for p.posts.iter().map(|sp| html! {
+                                    <div key={sp.post.id} class="w-100" style="max-width: 900px;">
+                                        <PostCard affinity={sp.score} post={Rc::new(sp.post.clone())} />
+                                    </div>
+                                })
+                            }
+                        </div>
+                    }
+                } else {
+                    html! {}
+                }
+            }
+
+            { pager }
+        </div>
+    }
+}
+
+async fn fetch<T: DeserializeOwned>(url: &str) -> Result<T, String> {
+    let response = with_auth(Request::get(url))
+        .send()
+        .await
+        .map_err(|e| format!("Network error: {e}"))?;
+
+    if !response.ok() {
+        let status = response.status();
+        let text = response
+            .text()
+            .await
+            .unwrap_or_else(|_| "Unknown error".into());
+        return Err(format!("Error {status}: {text}"));
+    }
+
+    response
+        .json::<T>()
+        .await
+        .map_err(|e| format!("Failed to parse response: {e}"))
+}
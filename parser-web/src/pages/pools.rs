@@ -0,0 +1,175 @@
+use reqwasm::http::Request;
+use serde::de::DeserializeOwned;
+use std::collections::HashSet;
+use std::rc::Rc;
+use yew::prelude::*;
+
+use crate::components::*;
+use crate::models::{PoolRecommendation, read_config_from_head, with_auth};
+use crate::pages::UserInfo;
+
+#[function_component(PoolsPage)]
+pub fn pools_page() -> Html {
+    let cfg = read_config_from_head().unwrap();
+    let selected_user = use_state(|| Option::<UserInfo>::None);
+    let is_loading = use_state(|| false);
+    let error = use_state(|| Option::<String>::None);
+    let pools = use_state(Vec::<PoolRecommendation>::new);
+    let page = use_state(|| 1usize);
+
+    let fetch_page = {
+        let api_base = cfg.backend_domain.clone();
+        let selected_user = selected_user.clone();
+        let is_loading = is_loading.clone();
+        let error = error.clone();
+        let pools = pools.clone();
+        let page = page.clone();
+
+        Callback::from(move |reset: bool| {
+            let Some(user) = (*selected_user).clone() else {
+                return;
+            };
+            let next = if reset { 1 } else { *page };
+            let url = format!("{api_base}/recommendations/{}/pools?page={next}", user.id);
+
+            let is_loading = is_loading.clone();
+            let error = error.clone();
+            let pools = pools.clone();
+            let page = page.clone();
+            is_loading.set(true);
+            error.set(None);
+
+            wasm_bindgen_futures::spawn_local(async move {
+                match fetch::<Vec<PoolRecommendation>>(&url).await {
+                    Ok(items) => {
+                        let mut merged = if reset { Vec::new() } else { (*pools).clone() };
+                        let mut seen: HashSet<i64> = merged.iter().map(|r| r.pool.id).collect();
+                        merged.extend(items.into_iter().filter(|r| seen.insert(r.pool.id)));
+                        pools.set(merged);
+                        page.set(next + 1);
+                    }
+                    Err(e) => error.set(Some(e)),
+                }
+                is_loading.set(false);
+            });
+        })
+    };
+
+    {
+        let fetch_page = fetch_page.clone();
+        use_effect_with(selected_user.as_ref().map(|u| u.id), move |id| {
+            if id.is_some() {
+                fetch_page.emit(true);
+            }
+            || ()
+        });
+    }
+
+    let account_id = selected_user.as_ref().map(|u| u.id);
+
+    html! {
+        <div class="container my-4" id="pools-page">
+            <h2 class="mb-3">{ "Recommended Pools" }</h2>
+            <SavedAccountsSelect selected_user={selected_user.clone()} is_loading={is_loading.clone()} />
+
+            {
+                if let Some(err) = &*error {
+                    html! { <div class="alert alert-danger mt-3">{ err }</div> }
+                } else {
+                    html! {}
+                }
+            }
+
+            {
+                if account_id.is_some() && !*is_loading && error.is_none() && pools.is_empty() {
+                    html! { <div class="text-center text-muted my-5">{ "No pools found among the latest posts." }</div> }
+                } else {
+                    html! {}
+                }
+            }
+
+            <div class="row g-3 mt-1">
+                {
+                    for pools.iter().map(|r| {
+                        let href = format!("/pools/{}?account={}", r.pool.id, account_id.unwrap_or_default());
+                        html! {
+                            <div key={r.pool.id} class="col-sm-6 col-md-4 col-lg-3">
+                                <div class="card h-100 shadow-sm">
+                                    {
+                                        if let Some(cover) = &r.cover {
+                                            html! { <PostCard affinity={r.score} post={Rc::new(cover.post.clone())} /> }
+                                        } else {
+                                            html! {}
+                                        }
+                                    }
+                                    <div class="card-body">
+                                        <h6 class="card-title mb-1">{ r.pool.display_name() }</h6>
+                                        <p class="card-text text-muted small mb-2">
+                                            { format!(
+                                                "{} · {} posts · best {:.2} over {} scored",
+                                                r.pool.category,
+                                                r.pool.post_ids.len(),
+                                                r.best,
+                                                r.scored_posts
+                                            ) }
+                                        </p>
+                                        <a class="btn btn-sm btn-primary" href={href}>{ "Read" }</a>
+                                    </div>
+                                </div>
+                            </div>
+                        }
+                    })
+                }
+            </div>
+
+            {
+                if *is_loading {
+                    html! {
+                        <div class="d-flex justify-content-center my-4">
+                            <div class="spinner-border" role="status">
+                                <span class="visually-hidden">{ "Loading..." }</span>
+                            </div>
+                        </div>
+                    }
+                } else if account_id.is_some() {
+                    html! {
+                        <div class="d-flex justify-content-center my-4">
+                            <button
+                                class="btn btn-outline-secondary"
+                                onclick={{
+                                    let fetch_page = fetch_page.clone();
+                                    Callback::from(move |_| fetch_page.emit(false))
+                                }}
+                            >
+                                { "Load more" }
+                            </button>
+                        </div>
+                    }
+                } else {
+                    html! {}
+                }
+            }
+        </div>
+    }
+}
+
+async fn fetch<T: DeserializeOwned>(url: &str) -> Result<T, String> {
+    let response = with_auth(Request::get(url))
+        .send()
+        .await
+        .map_err(|e| format!("Network error: {e}"))?;
+
+    if !response.ok() {
+        let status = response.status();
+        let text = response
+            .text()
+            .await
+            .unwrap_or_else(|_| "Unknown error".into());
+        return Err(format!("Error {status}: {text}"));
+    }
+
+    response
+        .json::<T>()
+        .await
+        .map_err(|e| format!("Failed to parse response: {e}"))
+}